@@ -0,0 +1,60 @@
+//! Compares the blocked HWC <-> CHW transposes with a strided copy by `ndarray` on 4K frames.
+//!
+//! Run with `cargo bench --bench layout`, optionally with `--features rayon`.
+
+use ndarray::Array3;
+use ndarray_image::{chw_as_hwc, hwc_as_chw, to_chw, to_hwc};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 20;
+
+fn time(mut f: impl FnMut()) -> Duration {
+    f();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn compare(name: &str, naive: impl FnMut(), blocked: impl FnMut()) {
+    let naive = time(naive);
+    let blocked = time(blocked);
+    println!(
+        "{:<24} strided {:>10.2?}  blocked {:>10.2?}  {:.1}x",
+        name,
+        naive,
+        blocked,
+        naive.as_secs_f64() / blocked.as_secs_f64()
+    );
+}
+
+fn bench<A: image::Primitive + Send + Sync>(name: &str, channels: usize, value: fn(usize) -> A) {
+    let hwc = Array3::from_shape_fn((2160, 3840, channels), |(y, x, c)| value(y + x + c));
+    let chw = to_chw(hwc.view());
+    compare(
+        &format!("{} hwc -> chw", name),
+        || {
+            black_box(hwc_as_chw(hwc.view()).as_standard_layout().into_owned());
+        },
+        || {
+            black_box(to_chw(hwc.view()));
+        },
+    );
+    compare(
+        &format!("{} chw -> hwc", name),
+        || {
+            black_box(chw_as_hwc(chw.view()).as_standard_layout().into_owned());
+        },
+        || {
+            black_box(to_hwc(chw.view()));
+        },
+    );
+}
+
+fn main() {
+    bench("rgb u8", 3, |v| v as u8);
+    bench("rgba u8", 4, |v| v as u8);
+    bench("rgb f32", 3, |v| v as f32);
+}
@@ -0,0 +1,65 @@
+use crate::num::{is_integer, saturate};
+use crate::{Colors, ConversionError, NdColorMut};
+use image::Primitive;
+use ndarray::Axis;
+
+/// Multiplies the colors of a 3d array of pixels with the given colors by their alpha in place.
+///
+/// The alpha is the last channel, and its largest value is the largest value of `A`, or `1.0`
+/// for floating point types. Colors without alpha are opaque, so they are left as they are.
+/// Integer results are rounded. Fails if the array does not have the channels of `colors`.
+pub fn premultiply_alpha<A: Primitive>(
+    image: NdColorMut<'_, A>,
+    colors: Colors,
+) -> Result<(), ConversionError> {
+    map_colors(image, colors, |c, a, max| c * a / max)
+}
+
+/// Divides the colors of a 3d array of premultiplied pixels with the given colors by their alpha
+/// in place.
+///
+/// Fully transparent pixels become black, and colors brighter than their alpha saturate. Integer
+/// results are rounded. Fails if the array does not have the channels of `colors`.
+pub fn unpremultiply_alpha<A: Primitive>(
+    image: NdColorMut<'_, A>,
+    colors: Colors,
+) -> Result<(), ConversionError> {
+    map_colors(image, colors, |c, a, max| {
+        if a == 0.0 {
+            0.0
+        } else {
+            (c * max / a).min(max)
+        }
+    })
+}
+
+/// Maps each color channel with its alpha and the largest alpha, in place.
+fn map_colors<A: Primitive>(
+    mut image: NdColorMut<'_, A>,
+    colors: Colors,
+    f: impl Fn(f64, f64, f64) -> f64,
+) -> Result<(), ConversionError> {
+    let channels = image.dim().2;
+    if channels != colors.channel_count() {
+        return Err(ConversionError::WrongChannelCount {
+            expected: colors.channel_count(),
+            got: channels,
+        });
+    }
+    if !matches!(colors, Colors::LumaA | Colors::Rgba | Colors::Bgra) {
+        return Ok(());
+    }
+    let max = if is_integer::<A>() {
+        A::max_value().to_f64().unwrap()
+    } else {
+        1.0
+    };
+    for mut pixel in image.lanes_mut(Axis(2)) {
+        let a = pixel[channels - 1].to_f64().unwrap();
+        for c in pixel.iter_mut().take(channels - 1) {
+            let v = f(c.to_f64().unwrap(), a, max);
+            *c = saturate(v);
+        }
+    }
+    Ok(())
+}
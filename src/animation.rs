@@ -0,0 +1,123 @@
+use crate::{DecodeLimits, IntoNdColor, NdImageError, NdImageResult};
+use image::error::{ImageFormatHint, UnsupportedError};
+use image::io::Reader;
+use image::{Frame, Frames, ImageError};
+use ndarray::{Array3, Array4, Axis};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::time::Duration;
+
+/// Opens an animated image and decodes every frame into a `(frames, height, width, 4)` array of
+/// RGBA colors, along with the delay of each frame.
+///
+/// Animated GIF and APNG images are supported.
+pub fn open_animation(path: impl AsRef<Path>) -> NdImageResult<(Array4<u8>, Vec<Duration>)> {
+    let path = path.as_ref();
+    let frames = decode_frames(Reader::open(path)?.with_guessed_format()?)?;
+    let delays = frames.iter().map(frame_duration).collect();
+    let mut animation: Option<Array4<u8>> = None;
+    let count = frames.len();
+    for (index, frame) in frames.into_iter().enumerate() {
+        let frame: Array3<u8> = frame.into_buffer().into_nd_color();
+        let (height, width, channels) = frame.dim();
+        let animation =
+            animation.get_or_insert_with(|| Array4::zeros((count, height, width, channels)));
+        let (_, expected_height, expected_width, _) = animation.dim();
+        if (expected_height, expected_width) != (height, width) {
+            return Err(NdImageError::DimensionMismatch {
+                path: path.to_owned(),
+                expected: (expected_height, expected_width),
+                got: (height, width),
+            });
+        }
+        animation.index_axis_mut(Axis(0), index).assign(&frame);
+    }
+    let animation = animation.unwrap_or_else(|| Array4::zeros((0, 0, 0, 4)));
+    Ok((animation, delays))
+}
+
+/// Saves a `(frames, height, width, channels)` array as an animated GIF, showing each frame for
+/// the corresponding delay.
+///
+/// The colors of each frame are quantized to a palette of 256 colors.
+#[cfg(feature = "gif")]
+pub fn save_animation(
+    path: impl AsRef<Path>,
+    frames: ndarray::ArrayView4<'_, u8>,
+    colors: crate::Colors,
+    delays: &[Duration],
+) -> NdImageResult<()> {
+    use image::codecs::gif::{GifEncoder, Repeat};
+    use image::Delay;
+    use std::io::BufWriter;
+
+    if delays.len() != frames.len_of(Axis(0)) {
+        return Err(NdImageError::BatchSizeMismatch {
+            expected: frames.len_of(Axis(0)),
+            got: delays.len(),
+        });
+    }
+    let mut encoder = GifEncoder::new(BufWriter::new(File::create(path)?));
+    encoder.set_repeat(Repeat::Infinite)?;
+    for (frame, &delay) in frames.outer_iter().zip(delays) {
+        let buffer = crate::encode::to_dynamic_image(frame, colors)?.into_rgba8();
+        let delay = Delay::from_saturating_duration(delay);
+        encoder.encode_frame(Frame::from_parts(buffer, 0, 0, delay))?;
+    }
+    Ok(())
+}
+
+/// Decodes every frame of an animated image.
+// The reader is unused when every animated format is disabled.
+#[allow(unused_variables)]
+fn decode_frames(reader: Reader<BufReader<File>>) -> NdImageResult<Vec<Frame>> {
+    let format = reader.format();
+    let file = reader.into_inner();
+    match format {
+        #[cfg(feature = "gif")]
+        Some(image::ImageFormat::Gif) => {
+            use image::{AnimationDecoder, ImageDecoder};
+
+            let decoder = image::codecs::gif::GifDecoder::new(file)?;
+            let (width, height) = decoder.dimensions();
+            collect_frames(decoder.into_frames(), width, height)
+        }
+        #[cfg(feature = "png")]
+        Some(image::ImageFormat::Png) => {
+            use image::{AnimationDecoder, ImageDecoder};
+
+            let decoder = image::codecs::png::PngDecoder::new(file)?;
+            let (width, height) = decoder.dimensions();
+            collect_frames(decoder.apng().into_frames(), width, height)
+        }
+        format => {
+            let hint = format.map_or(ImageFormatHint::Unknown, ImageFormatHint::Exact);
+            Err(ImageError::Unsupported(UnsupportedError::from(hint)).into())
+        }
+    }
+}
+
+/// Collects the frames of an animation with the given dimensions within the global limits.
+///
+/// Every frame is counted twice, as the frames are copied into the array of the animation.
+// Unused when every animated format is disabled.
+#[allow(dead_code)]
+fn collect_frames(frames: Frames<'_>, width: u32, height: u32) -> NdImageResult<Vec<Frame>> {
+    let limits = DecodeLimits::global();
+    limits.check_dimensions(width, height)?;
+    let frame_bytes = 2 * 4 * u64::from(width) * u64::from(height);
+    let mut collected = Vec::new();
+    for frame in frames {
+        let bytes = frame_bytes.saturating_mul(collected.len() as u64 + 1);
+        limits.check_bytes(Some(bytes))?;
+        collected.push(frame?);
+    }
+    Ok(collected)
+}
+
+/// Gets the delay of a frame as a `Duration`.
+fn frame_duration(frame: &Frame) -> Duration {
+    let (numer, denom) = frame.delay().numer_denom_ms();
+    Duration::from_nanos(u64::from(numer) * 1_000_000 / u64::from(denom.max(1)))
+}
@@ -0,0 +1,143 @@
+use crate::layout::copy_to_chw;
+use crate::{open_image, save_image, to_hwc, Colors, Layout, NdImageError, NdImageResult};
+use image::ImageFormat;
+use ndarray::{Array4, ArrayView4, Axis};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Opens images of equal dimensions using the `image` crate and stacks them into a 4d array.
+///
+/// The axes of the batch are ordered according to `layout`. Without any paths, the batch is empty
+/// but still has the number of channels of `colors` on its channel axis.
+pub fn open_images_batch<P: AsRef<Path>>(
+    paths: impl IntoIterator<Item = P>,
+    colors: Colors,
+    layout: Layout,
+) -> NdImageResult<Array4<u8>> {
+    let paths: Vec<P> = paths.into_iter().collect();
+    let mut batch: Option<Array4<u8>> = None;
+    for (index, path) in paths.iter().enumerate() {
+        let image = open_image(path, colors)?;
+        let (height, width, channels) = image.dim();
+        let batch = batch.get_or_insert_with(|| {
+            Array4::zeros(batch_shape(paths.len(), (height, width, channels), layout))
+        });
+        let expected = batch_dimensions(batch.view(), layout);
+        if expected != (height, width) {
+            return Err(NdImageError::DimensionMismatch {
+                path: path.as_ref().to_owned(),
+                expected,
+                got: (height, width),
+            });
+        }
+        let mut slot = batch.index_axis_mut(Axis(0), index);
+        match layout {
+            Layout::Nhwc => slot.assign(&image),
+            Layout::Nchw => copy_to_chw(image.view(), slot),
+        }
+    }
+    Ok(batch
+        .unwrap_or_else(|| Array4::zeros(batch_shape(0, (0, 0, colors.channel_count()), layout))))
+}
+
+/// Opens every image in a directory and stacks them into a `(images, height, width, channels)`
+/// array.
+///
+/// Files are recognized as images by their extension and are loaded in sorted order. The paths of
+/// the images are returned in the same order as they appear in the batch.
+pub fn open_image_dir(
+    dir: impl AsRef<Path>,
+    colors: Colors,
+) -> NdImageResult<(Array4<u8>, Vec<PathBuf>)> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && ImageFormat::from_path(&path).is_ok() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    let batch = open_images_batch(&paths, colors, Layout::Nhwc)?;
+    Ok((batch, paths))
+}
+
+/// Saves every image of a 4d array to the corresponding path using the `image` crate.
+///
+/// The axes of the batch are ordered according to `layout`.
+pub fn save_images_batch<P: AsRef<Path>>(
+    paths: impl IntoIterator<Item = P>,
+    batch: ArrayView4<'_, u8>,
+    colors: Colors,
+    layout: Layout,
+) -> NdImageResult<()> {
+    let paths: Vec<P> = paths.into_iter().collect();
+    if paths.len() != batch.len_of(Axis(0)) {
+        return Err(NdImageError::BatchSizeMismatch {
+            expected: batch.len_of(Axis(0)),
+            got: paths.len(),
+        });
+    }
+    for (path, image) in paths.iter().zip(batch.outer_iter()) {
+        match layout {
+            Layout::Nhwc => save_image(path, image, colors)?,
+            Layout::Nchw => save_image(path, to_hwc(image).view(), colors)?,
+        }
+    }
+    Ok(())
+}
+
+/// Saves every image of a 4d array into a directory as `frame_00000.png`, `frame_00001.png`, ...
+///
+/// The axes of the batch are ordered according to `layout`. Returns the paths of the saved images.
+pub fn save_image_batch(
+    dir: impl AsRef<Path>,
+    batch: ArrayView4<'_, u8>,
+    colors: Colors,
+    layout: Layout,
+) -> NdImageResult<Vec<PathBuf>> {
+    save_image_batch_with_names(dir, batch, colors, layout, |index| {
+        format!("frame_{:05}.png", index)
+    })
+}
+
+/// Saves every image of a 4d array into a directory using `name` to get the file name of each
+/// image from its index in the batch.
+///
+/// The format of each image is chosen from the extension of its file name. The axes of the batch
+/// are ordered according to `layout`. Returns the paths of the saved images.
+pub fn save_image_batch_with_names(
+    dir: impl AsRef<Path>,
+    batch: ArrayView4<'_, u8>,
+    colors: Colors,
+    layout: Layout,
+    name: impl FnMut(usize) -> String,
+) -> NdImageResult<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    let paths: Vec<PathBuf> = (0..batch.len_of(Axis(0)))
+        .map(name)
+        .map(|name| dir.join(name))
+        .collect();
+    save_images_batch(&paths, batch, colors, layout)?;
+    Ok(paths)
+}
+
+/// Gets the shape of a batch of `len` images with the dimensions `(height, width, channels)`.
+fn batch_shape(
+    len: usize,
+    (height, width, channels): (usize, usize, usize),
+    layout: Layout,
+) -> (usize, usize, usize, usize) {
+    match layout {
+        Layout::Nhwc => (len, height, width, channels),
+        Layout::Nchw => (len, channels, height, width),
+    }
+}
+
+/// Gets the `(height, width)` of the images in a batch.
+fn batch_dimensions<A>(batch: ArrayView4<'_, A>, layout: Layout) -> (usize, usize) {
+    let (_, a, b, c) = batch.dim();
+    match layout {
+        Layout::Nhwc => (a, b),
+        Layout::Nchw => (b, c),
+    }
+}
@@ -0,0 +1,170 @@
+use crate::limits::Target;
+use crate::{IntoNdGray, NdGray, NdImageResult};
+use image::error::{ImageFormatHint, UnsupportedError, UnsupportedErrorKind};
+use image::{ColorType, DynamicImage, ImageError};
+use ndarray::{Array2, Array3};
+use std::path::Path;
+
+/// The layout of the color filter array over each 2x2 block of a Bayer mosaic, read row by row
+/// from the top left pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CfaPattern {
+    Rggb,
+    Bggr,
+    Grbg,
+    Gbrg,
+}
+
+impl CfaPattern {
+    /// The channel, `0` red, `1` green, or `2` blue, that the pixel at `(y, x)` samples.
+    fn channel(self, y: usize, x: usize) -> usize {
+        let block = match self {
+            CfaPattern::Rggb => [[0, 1], [1, 2]],
+            CfaPattern::Bggr => [[2, 1], [1, 0]],
+            CfaPattern::Grbg => [[1, 0], [2, 1]],
+            CfaPattern::Gbrg => [[1, 2], [0, 1]],
+        };
+        block[y % 2][x % 2]
+    }
+}
+
+/// The method used to interpolate the two missing colors of each pixel of a Bayer mosaic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemosaicAlgorithm {
+    /// Averages the nearest samples of each color.
+    Bilinear,
+    /// Corrects the bilinear estimate with the gradient of the sampled color, as described by
+    /// Malvar, He, and Cutler, which keeps edges much sharper.
+    Malvar,
+}
+
+/// Opens a raw Bayer mosaic stored as a gray image, such as a 16-bit PNG, TIFF, or PGM image, and
+/// loads it into a `(height, width)` array.
+///
+/// The samples keep their raw values, so 8-bit images are not scaled to the `u16` range. The
+/// pattern of the mosaic is needed later to [`demosaic`] it. Fails for images with colors.
+pub fn open_raw_bayer(path: impl AsRef<Path>) -> NdImageResult<Array2<u16>> {
+    match crate::limits::open(path, Target::Color(ColorType::L16))? {
+        DynamicImage::ImageLuma8(image) => {
+            let image: Array2<u8> = image.into_nd_gray();
+            Ok(image.mapv(u16::from))
+        }
+        DynamicImage::ImageLuma16(image) => Ok(image.into_nd_gray()),
+        image => Err(
+            ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+                ImageFormatHint::Unknown,
+                UnsupportedErrorKind::GenericFeature(format!(
+                    "{:?} images as Bayer mosaics",
+                    image.color()
+                )),
+            ))
+            .into(),
+        ),
+    }
+}
+
+/// Interpolates a Bayer mosaic into a `(height, width, 3)` array of RGB colors.
+///
+/// Samples past the borders are mirrored, which keeps the pattern of the mosaic intact.
+pub fn demosaic(
+    mosaic: NdGray<'_, u16>,
+    pattern: CfaPattern,
+    algorithm: DemosaicAlgorithm,
+) -> Array3<u16> {
+    let (height, width) = mosaic.dim();
+    let sample = |y: isize, x: isize| i32::from(mosaic[[mirror(y, height), mirror(x, width)]]);
+    Array3::from_shape_fn((height, width, 3), |(y, x, c)| {
+        let own = pattern.channel(y, x);
+        let (yi, xi) = (y as isize, x as isize);
+        if own == c {
+            return mosaic[[y, x]];
+        }
+        let value = match algorithm {
+            DemosaicAlgorithm::Bilinear => {
+                let (mut sum, mut count) = (0, 0);
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        let (ny, nx) = (yi + dy, xi + dx);
+                        if pattern.channel(mirror(ny, height), mirror(nx, width)) == c {
+                            sum += sample(ny, nx);
+                            count += 1;
+                        }
+                    }
+                }
+                (sum + count / 2) / count.max(1)
+            }
+            DemosaicAlgorithm::Malvar => {
+                let kernel = if c == 1 {
+                    &GREEN_AT_RED_BLUE
+                } else if own != 1 {
+                    &RED_BLUE_AT_BLUE_RED
+                } else if pattern.channel(y, x + 1) == c {
+                    &RED_BLUE_AT_GREEN_ROW
+                } else {
+                    &RED_BLUE_AT_GREEN_COLUMN
+                };
+                let mut sum = 0;
+                for (dy, row) in (-2..).zip(kernel) {
+                    for (dx, &weight) in (-2..).zip(row) {
+                        if weight != 0 {
+                            sum += weight * sample(yi + dy, xi + dx);
+                        }
+                    }
+                }
+                (sum + 8) >> 4
+            }
+        };
+        value.clamp(0, i32::from(u16::MAX)) as u16
+    })
+}
+
+/// The Malvar kernels, scaled by 16, for green at red and blue samples.
+const GREEN_AT_RED_BLUE: [[i32; 5]; 5] = [
+    [0, 0, -2, 0, 0],
+    [0, 0, 4, 0, 0],
+    [-2, 4, 8, 4, -2],
+    [0, 0, 4, 0, 0],
+    [0, 0, -2, 0, 0],
+];
+
+/// Red or blue at green samples in a row with red or blue samples respectively.
+const RED_BLUE_AT_GREEN_ROW: [[i32; 5]; 5] = [
+    [0, 0, 1, 0, 0],
+    [0, -2, 0, -2, 0],
+    [-2, 8, 10, 8, -2],
+    [0, -2, 0, -2, 0],
+    [0, 0, 1, 0, 0],
+];
+
+/// Red or blue at green samples in a column with red or blue samples respectively.
+const RED_BLUE_AT_GREEN_COLUMN: [[i32; 5]; 5] = [
+    [0, 0, -2, 0, 0],
+    [0, -2, 8, -2, 0],
+    [1, 0, 10, 0, 1],
+    [0, -2, 8, -2, 0],
+    [0, 0, -2, 0, 0],
+];
+
+/// Red at blue samples and blue at red samples.
+const RED_BLUE_AT_BLUE_RED: [[i32; 5]; 5] = [
+    [0, 0, -3, 0, 0],
+    [0, 4, 0, 4, 0],
+    [-3, 0, 12, 0, -3],
+    [0, 4, 0, 4, 0],
+    [0, 0, -3, 0, 0],
+];
+
+/// Mirrors an index past either end of an axis of length `len` back into it, without repeating
+/// the edge so that the parity of the index is kept.
+fn mirror(index: isize, len: usize) -> usize {
+    let last = len as isize - 1;
+    let index = if index < 0 {
+        -index
+    } else if index > last {
+        2 * last - index
+    } else {
+        index
+    };
+    // Axes too short to mirror into are clamped instead.
+    index.clamp(0, last.max(0)) as usize
+}
@@ -0,0 +1,102 @@
+use crate::{ConversionError, NdColor, NdColorMut, NdGray, NdGrayMut};
+use ndarray::{Array2, Array3, ArrayView2, Axis};
+
+/// Copies each channel of a 3d array into its own 2d array.
+pub fn split_channels<A: Clone>(image: NdColor<'_, A>) -> Vec<Array2<A>> {
+    image
+        .axis_iter(Axis(2))
+        .map(|channel| channel.to_owned())
+        .collect()
+}
+
+/// Views each channel of a 3d array as a 2d array without copying.
+///
+/// The views are strided, since the channels of each pixel are next to each other.
+pub fn channel_views<'a, A>(image: NdColor<'a, A>) -> Vec<NdGray<'a, A>> {
+    (0..image.len_of(Axis(2)))
+        .map(|c| image.index_axis_move(Axis(2), c))
+        .collect()
+}
+
+/// Mutably views each channel of a 3d array as a 2d array without copying.
+pub fn channel_views_mut<'a, A>(image: NdColorMut<'a, A>) -> Vec<NdGrayMut<'a, A>> {
+    let mut views = Vec::with_capacity(image.len_of(Axis(2)));
+    let mut rest = image;
+    // Each channel is split off the front so that every view keeps the lifetime of the array.
+    while rest.len_of(Axis(2)) > 0 {
+        let (channel, tail) = rest.split_at(Axis(2), 1);
+        views.push(channel.index_axis_move(Axis(2), 0));
+        rest = tail;
+    }
+    views
+}
+
+/// Copies 2d arrays into the channels of a new 3d array in standard layout.
+///
+/// Fails if there are no channels or if they do not all have the same dimensions.
+pub fn merge_channels<A: Clone>(
+    channels: &[ArrayView2<'_, A>],
+) -> Result<Array3<A>, ConversionError> {
+    let first = channels
+        .first()
+        .ok_or(ConversionError::BadShape { shape: vec![0] })?;
+    let (height, width) = first.dim();
+    if let Some(channel) = channels.iter().find(|c| c.dim() != (height, width)) {
+        return Err(ConversionError::BadShape {
+            shape: channel.shape().to_vec(),
+        });
+    }
+    Ok(Array3::from_shape_fn(
+        (height, width, channels.len()),
+        |(y, x, c)| channels[c][[y, x]].clone(),
+    ))
+}
+
+/// Reorders the channels of every pixel in place, so that channel `c` afterwards holds what was
+/// channel `order[c]` before.
+///
+/// For example, `[2, 1, 0]` turns RGB into BGR and `[3, 0, 1, 2]` turns RGBA into ARGB. Fails if
+/// `order` does not have one index for each channel or is not a permutation.
+pub fn reorder_channels<A>(
+    mut image: NdColorMut<'_, A>,
+    order: &[usize],
+) -> Result<(), ConversionError> {
+    let channels = image.len_of(Axis(2));
+    if order.len() != channels {
+        return Err(ConversionError::WrongChannelCount {
+            expected: channels,
+            got: order.len(),
+        });
+    }
+    let mut seen = vec![false; channels];
+    for &c in order {
+        if c >= channels || seen[c] {
+            return Err(ConversionError::BadPermutation {
+                order: order.to_vec(),
+            });
+        }
+        seen[c] = true;
+    }
+    // Following each cycle of the permutation turns it into swaps that are applied to every pixel.
+    let mut swaps = Vec::new();
+    let mut visited = vec![false; channels];
+    for start in 0..channels {
+        let mut c = start;
+        while !visited[c] {
+            visited[c] = true;
+            if order[c] != start {
+                swaps.push((c, order[c]));
+            }
+            c = order[c];
+        }
+    }
+    if swaps.is_empty() {
+        return Ok(());
+    }
+    for mut pixel in image.lanes_mut(Axis(2)) {
+        for &(a, b) in &swaps {
+            pixel.swap(a, b);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use crate::{ConversionError, NdColor, NdColorMut, NdGray, NdGrayMut};
+use image::flat::SampleLayout;
+use image::{ImageBuffer, Luma, Pixel, Primitive};
+use ndarray::{Array2, Array3, ArrayView, ArrayViewMut, Axis, Ix3, ShapeBuilder, StrideShape};
+use std::ops::{Deref, DerefMut};
+
+/// Views a gray image as a 2d array.
+///
+/// Fails if the buffer of the image is shorter than its dimensions require.
+pub fn try_view_gray<C, A>(
+    image: &ImageBuffer<Luma<A>, C>,
+) -> Result<NdGray<'_, A>, ConversionError>
+where
+    A: Primitive + 'static,
+    C: Deref<Target = [A]>,
+{
+    Ok(try_view_color(image)?.index_axis_move(Axis(2), 0))
+}
+
+/// Mutably views a gray image as a 2d array.
+///
+/// Fails if the buffer of the image is shorter than its dimensions require.
+pub fn try_view_gray_mut<C, A>(
+    image: &mut ImageBuffer<Luma<A>, C>,
+) -> Result<NdGrayMut<'_, A>, ConversionError>
+where
+    A: Primitive + 'static,
+    C: Deref<Target = [A]> + DerefMut,
+{
+    Ok(try_view_color_mut(image)?.index_axis_move(Axis(2), 0))
+}
+
+/// Views an image as a 3d array with one dimension for the color channel.
+///
+/// Fails if the buffer of the image is shorter than its dimensions require.
+pub fn try_view_color<P, C, A>(image: &ImageBuffer<P, C>) -> Result<NdColor<'_, A>, ConversionError>
+where
+    P: Pixel<Subpixel = A> + 'static,
+    A: Primitive + 'static,
+    C: Deref<Target = [A]>,
+{
+    let layout = image.sample_layout();
+    let len = checked_len(&layout, image.as_raw().len())?;
+    // The container may be longer than the image.
+    ArrayView::from_shape(layout_shape(&layout), &image.as_raw()[..len])
+        .map_err(|_| layout_error(&layout))
+}
+
+/// Mutably views an image as a 3d array with one dimension for the color channel.
+///
+/// Fails if the buffer of the image is shorter than its dimensions require.
+pub fn try_view_color_mut<P, C, A>(
+    image: &mut ImageBuffer<P, C>,
+) -> Result<NdColorMut<'_, A>, ConversionError>
+where
+    P: Pixel<Subpixel = A> + 'static,
+    A: Primitive + 'static,
+    C: Deref<Target = [A]> + DerefMut,
+{
+    let layout = image.sample_layout();
+    let len = checked_len(&layout, image.as_raw().len())?;
+    ArrayViewMut::from_shape(layout_shape(&layout), &mut image.deref_mut()[..len])
+        .map_err(|_| layout_error(&layout))
+}
+
+/// Turns an owned gray image into a 2d array, reusing its allocation.
+///
+/// Fails if the buffer of the image is shorter than its dimensions require.
+pub fn try_into_gray_array<A>(
+    image: ImageBuffer<Luma<A>, Vec<A>>,
+) -> Result<Array2<A>, ConversionError>
+where
+    A: Primitive + 'static,
+{
+    Ok(try_into_color_array(image)?.index_axis_move(Axis(2), 0))
+}
+
+/// Turns an owned image into a 3d array with one dimension for the color channel, reusing its
+/// allocation.
+///
+/// Fails if the buffer of the image is shorter than its dimensions require.
+pub fn try_into_color_array<P, A>(
+    image: ImageBuffer<P, Vec<A>>,
+) -> Result<Array3<A>, ConversionError>
+where
+    P: Pixel<Subpixel = A> + 'static,
+    A: Primitive + 'static,
+{
+    let layout = image.sample_layout();
+    let mut raw = image.into_raw();
+    raw.truncate(checked_len(&layout, raw.len())?);
+    Array3::from_shape_vec(layout_shape(&layout), raw).map_err(|_| layout_error(&layout))
+}
+
+/// Gets the number of samples a layout needs, failing if a buffer of `len` samples is too short.
+fn checked_len(layout: &SampleLayout, len: usize) -> Result<usize, ConversionError> {
+    match layout.min_length() {
+        Some(min) if min <= len => Ok(min),
+        Some(_) => Err(ConversionError::BufferTooShort {
+            shape: layout_dims(layout),
+            len,
+        }),
+        None => Err(layout_error(layout)),
+    }
+}
+
+/// Gets the `(height, width, channels)` shape of an array with the strides of a sample layout.
+///
+/// Empty layouts get the default strides, since `ndarray` wants the strides of every array to fit
+/// in its samples even when there is nothing to index.
+pub(crate) fn layout_shape(layout: &SampleLayout) -> StrideShape<Ix3> {
+    let shape = (
+        layout.height as usize,
+        layout.width as usize,
+        usize::from(layout.channels),
+    );
+    if shape.0 == 0 || shape.1 == 0 || shape.2 == 0 {
+        return shape.into();
+    }
+    shape.strides((
+        layout.height_stride,
+        layout.width_stride,
+        layout.channel_stride,
+    ))
+}
+
+/// The error for a sample layout that cannot be viewed as an array.
+pub(crate) fn layout_error(layout: &SampleLayout) -> ConversionError {
+    ConversionError::BadShape {
+        shape: layout_dims(layout),
+    }
+}
+
+/// The `(height, width, channels)` dimensions of a sample layout.
+fn layout_dims(layout: &SampleLayout) -> Vec<usize> {
+    vec![
+        layout.height as usize,
+        layout.width as usize,
+        usize::from(layout.channels),
+    ]
+}
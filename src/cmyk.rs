@@ -0,0 +1,157 @@
+use crate::limits::Target;
+use crate::{Colors, ConversionError, NdColor, NdImageResult};
+use ndarray::{Array3, Axis, Zip};
+use std::path::Path;
+
+/// Opens an image and loads it into a `(height, width, 4)` array of CMYK colors, where `0` is no
+/// ink and `255` is full ink.
+///
+/// CMYK JPEG and TIFF images keep the colors they are stored with, while all other images are
+/// converted from RGB with [`rgb_to_cmyk`].
+pub fn open_image_cmyk(path: impl AsRef<Path>) -> NdImageResult<Array3<u8>> {
+    let data = std::fs::read(path)?;
+    let cmyk = match image::guess_format(&data)? {
+        #[cfg(feature = "jpeg")]
+        image::ImageFormat::Jpeg => decode_jpeg_cmyk(&data)?,
+        #[cfg(feature = "tiff")]
+        image::ImageFormat::Tiff => decode_tiff_cmyk(&data)?,
+        _ => None,
+    };
+    match cmyk {
+        Some(cmyk) => Ok(cmyk),
+        None => {
+            // The RGB image is counted along with the CMYK array it is converted to.
+            let image = crate::limits::read(std::io::Cursor::new(&data), Target::Bytes(7))?;
+            let image = crate::decode_u8(image, Colors::Rgb);
+            Ok(rgb_to_cmyk(image.view())?)
+        }
+    }
+}
+
+/// Saves a `(height, width, 4)` array of CMYK colors as a CMYK TIFF image.
+///
+/// Fails if the array does not have 4 channels.
+#[cfg(feature = "tiff")]
+pub fn save_tiff_cmyk(path: impl AsRef<Path>, image: NdColor<'_, u8>) -> NdImageResult<()> {
+    use std::fs::File;
+    use std::io::BufWriter;
+    use tiff::encoder::{colortype, TiffEncoder};
+
+    let (height, width, channels) = image.dim();
+    if channels != 4 {
+        return Err(ConversionError::WrongChannelCount {
+            expected: 4,
+            got: channels,
+        }
+        .into());
+    }
+    let image = image.as_standard_layout();
+    let file = BufWriter::new(File::create(path)?);
+    TiffEncoder::new(file)
+        .and_then(|mut encoder| {
+            encoder.write_image::<colortype::CMYK8>(
+                width as u32,
+                height as u32,
+                image.as_slice().unwrap(),
+            )
+        })
+        .map_err(crate::float::tiff_encoding_error)?;
+    Ok(())
+}
+
+/// Converts a 3d array of `u8` CMYK pixels to RGB, without a color profile.
+///
+/// Fails if the array does not have 4 channels.
+pub fn cmyk_to_rgb(image: NdColor<'_, u8>) -> Result<Array3<u8>, ConversionError> {
+    map_lanes(image, 4, 3, |cmyk, rgb| {
+        let k = 255.0 - f32::from(cmyk[3]);
+        for (rgb, &c) in rgb.iter_mut().zip(cmyk) {
+            *rgb = ((255.0 - f32::from(c)) * k / 255.0).round() as u8;
+        }
+    })
+}
+
+/// Converts a 3d array of `u8` RGB pixels to CMYK, without a color profile.
+///
+/// The black channel takes as much ink as possible from the other channels. Fails if the array
+/// does not have 3 channels.
+pub fn rgb_to_cmyk(image: NdColor<'_, u8>) -> Result<Array3<u8>, ConversionError> {
+    map_lanes(image, 3, 4, |rgb, cmyk| {
+        let max = rgb.iter().copied().max().unwrap();
+        cmyk[3] = 255 - max;
+        if max != 0 {
+            for (cmyk, &c) in cmyk.iter_mut().zip(rgb) {
+                *cmyk = ((f32::from(max - c) * 255.0) / f32::from(max)).round() as u8;
+            }
+        }
+    })
+}
+
+/// Maps each pixel of an array with `from` channels to a new pixel with `to` channels.
+fn map_lanes(
+    image: NdColor<'_, u8>,
+    from: usize,
+    to: usize,
+    f: impl Fn(&[u8], &mut [u8]),
+) -> Result<Array3<u8>, ConversionError> {
+    let (height, width, channels) = image.dim();
+    if channels != from {
+        return Err(ConversionError::WrongChannelCount {
+            expected: from,
+            got: channels,
+        });
+    }
+    let mut out = Array3::zeros((height, width, to));
+    Zip::from(out.lanes_mut(Axis(2)))
+        .and(image.lanes(Axis(2)))
+        .for_each(|mut out, pixel| {
+            let pixel = [0, 1, 2, 3].map(|c| pixel.get(c).copied().unwrap_or(0));
+            let mut mapped = [0; 4];
+            f(&pixel[..from], &mut mapped[..to]);
+            out.iter_mut().zip(mapped).for_each(|(out, c)| *out = c);
+        });
+    Ok(out)
+}
+
+/// Decodes a JPEG image with the colors it is stored with if they are CMYK.
+#[cfg(feature = "jpeg")]
+fn decode_jpeg_cmyk(data: &[u8]) -> NdImageResult<Option<Array3<u8>>> {
+    use crate::DecodeLimits;
+    use image::error::DecodingError;
+    use image::{ImageError, ImageFormat};
+    use jpeg_decoder::{Decoder, PixelFormat};
+
+    let error = |err| ImageError::Decoding(DecodingError::new(ImageFormat::Jpeg.into(), err));
+    let mut decoder = Decoder::new(data);
+    decoder.read_info().map_err(error)?;
+    let info = decoder.info().unwrap();
+    if info.pixel_format != PixelFormat::CMYK32 {
+        return Ok(None);
+    }
+    DecodeLimits::global().check(u32::from(info.width), u32::from(info.height), 4)?;
+    let pixels = decoder.decode().map_err(error)?;
+    let shape = (usize::from(info.height), usize::from(info.width), 4);
+    Ok(Array3::from_shape_vec(shape, pixels).ok())
+}
+
+/// Decodes a TIFF image with the colors it is stored with if they are 8-bit CMYK.
+#[cfg(feature = "tiff")]
+fn decode_tiff_cmyk(data: &[u8]) -> NdImageResult<Option<Array3<u8>>> {
+    use crate::float::tiff_decoding_error;
+    use crate::DecodeLimits;
+    use tiff::decoder::{Decoder, DecodingResult};
+    use tiff::ColorType;
+
+    let mut decoder = Decoder::new(std::io::Cursor::new(data)).map_err(tiff_decoding_error)?;
+    if decoder.colortype().map_err(tiff_decoding_error)? != ColorType::CMYK(8) {
+        return Ok(None);
+    }
+    let (width, height) = decoder.dimensions().map_err(tiff_decoding_error)?;
+    DecodeLimits::global().check(width, height, 4)?;
+    let raw = match decoder.read_image().map_err(tiff_decoding_error)? {
+        DecodingResult::U8(raw) => raw,
+        _ => return Ok(None),
+    };
+    let shape = (height as usize, width as usize, 4);
+    Ok(Array3::from_shape_vec(shape, raw).ok())
+}
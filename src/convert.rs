@@ -0,0 +1,161 @@
+#[allow(deprecated)]
+use crate::NdImage;
+use crate::{ConversionError, NdColor, NdColorMut, NdDynamicImage, NdGray, NdGrayMut};
+use image::{DynamicImage, ImageBuffer, Luma, Pixel, Primitive};
+use ndarray::{Array2, Array3};
+use std::convert::TryFrom;
+use std::ops::{Deref, DerefMut};
+
+/// Turns gray images and references to them into 2d arrays and views without copying.
+///
+/// Like the [`NdGray`] type, the result is always in `(height, width)` layout. The `nshare`
+/// module has traits with the `nshare` crate's names, which use `(channels, height, width)`.
+pub trait IntoNdGray {
+    /// The array or view the image is turned into.
+    type Out;
+
+    /// Turns the image into a `(height, width)` array or view.
+    fn into_nd_gray(self) -> Self::Out;
+}
+
+/// Turns images and references to them into 3d arrays and views without copying.
+///
+/// Like the [`NdColor`] type, the result is always in `(height, width, channels)` layout. Calling
+/// this on a reference gives a view, so `(&image).into_nd_color()` borrows the image.
+pub trait IntoNdColor {
+    /// The array or view the image is turned into.
+    type Out;
+
+    /// Turns the image into a `(height, width, channels)` array or view.
+    fn into_nd_color(self) -> Self::Out;
+}
+
+/// Turns arrays and views into the image buffer `T` without copying contiguous arrays.
+pub trait ToImageBuffer<T> {
+    /// Turns the array into an image buffer, failing if it has the wrong number of channels or
+    /// is a view that is not contiguous.
+    fn into_image_buffer(self) -> Result<T, ConversionError>;
+}
+
+#[allow(deprecated)]
+impl<'a, C, A> IntoNdGray for &'a ImageBuffer<Luma<A>, C>
+where
+    A: Primitive + 'static,
+    C: Deref<Target = [A]> + AsRef<[A]>,
+{
+    type Out = NdGray<'a, A>;
+
+    fn into_nd_gray(self) -> Self::Out {
+        NdImage(self).into()
+    }
+}
+
+#[allow(deprecated)]
+impl<'a, C, A> IntoNdGray for &'a mut ImageBuffer<Luma<A>, C>
+where
+    A: Primitive + 'static,
+    C: Deref<Target = [A]> + DerefMut + AsRef<[A]>,
+{
+    type Out = NdGrayMut<'a, A>;
+
+    fn into_nd_gray(self) -> Self::Out {
+        NdImage(self).into()
+    }
+}
+
+#[allow(deprecated)]
+impl<A: Primitive + 'static> IntoNdGray for ImageBuffer<Luma<A>, Vec<A>> {
+    type Out = Array2<A>;
+
+    fn into_nd_gray(self) -> Self::Out {
+        NdImage(self).into()
+    }
+}
+
+#[allow(deprecated)]
+impl<'a, C, P, A> IntoNdColor for &'a ImageBuffer<P, C>
+where
+    P: Pixel<Subpixel = A> + 'static,
+    A: Primitive + 'static,
+    C: Deref<Target = [A]> + AsRef<[A]>,
+{
+    type Out = NdColor<'a, A>;
+
+    fn into_nd_color(self) -> Self::Out {
+        NdImage(self).into()
+    }
+}
+
+#[allow(deprecated)]
+impl<'a, C, P, A> IntoNdColor for &'a mut ImageBuffer<P, C>
+where
+    P: Pixel<Subpixel = A> + 'static,
+    A: Primitive + 'static,
+    C: Deref<Target = [A]> + DerefMut + AsRef<[A]>,
+{
+    type Out = NdColorMut<'a, A>;
+
+    fn into_nd_color(self) -> Self::Out {
+        NdImage(self).into()
+    }
+}
+
+#[allow(deprecated)]
+impl<P, A> IntoNdColor for ImageBuffer<P, Vec<A>>
+where
+    P: Pixel<Subpixel = A> + 'static,
+    A: Primitive + 'static,
+{
+    type Out = Array3<A>;
+
+    fn into_nd_color(self) -> Self::Out {
+        NdImage(self).into()
+    }
+}
+
+/// Dynamic images become the array of their native color type.
+impl IntoNdColor for DynamicImage {
+    type Out = NdDynamicImage;
+
+    fn into_nd_color(self) -> Self::Out {
+        self.into()
+    }
+}
+
+#[allow(deprecated)]
+impl<'a, A: Primitive + 'static> ToImageBuffer<ImageBuffer<Luma<A>, &'a [A]>> for NdGray<'a, A> {
+    fn into_image_buffer(self) -> Result<ImageBuffer<Luma<A>, &'a [A]>, ConversionError> {
+        ImageBuffer::try_from(NdImage(self))
+    }
+}
+
+#[allow(deprecated)]
+impl<'a, P, A> ToImageBuffer<ImageBuffer<P, &'a [A]>> for NdColor<'a, A>
+where
+    P: Pixel<Subpixel = A> + 'static,
+    A: Primitive + 'static,
+{
+    fn into_image_buffer(self) -> Result<ImageBuffer<P, &'a [A]>, ConversionError> {
+        ImageBuffer::try_from(NdImage(self))
+    }
+}
+
+/// This copies if the array is not in standard layout, so it never fails.
+#[allow(deprecated)]
+impl<A: Primitive + 'static> ToImageBuffer<ImageBuffer<Luma<A>, Vec<A>>> for Array2<A> {
+    fn into_image_buffer(self) -> Result<ImageBuffer<Luma<A>, Vec<A>>, ConversionError> {
+        Ok(NdImage(self).into())
+    }
+}
+
+/// This copies if the array is not in standard layout.
+#[allow(deprecated)]
+impl<P, A> ToImageBuffer<ImageBuffer<P, Vec<A>>> for Array3<A>
+where
+    P: Pixel<Subpixel = A> + 'static,
+    A: Primitive + 'static,
+{
+    fn into_image_buffer(self) -> Result<ImageBuffer<P, Vec<A>>, ConversionError> {
+        ImageBuffer::try_from(NdImage(self))
+    }
+}
@@ -0,0 +1,42 @@
+use crate::num::is_integer;
+use crate::scale::convert_values;
+use crate::NdColor;
+use image::Primitive;
+use ndarray::Array3;
+
+/// Converts the samples of a 3d array from the full range of `A` to the full range of `B`.
+///
+/// Integer types span `0..=MAX` and floating point types `0.0..=1.0`, so `u8` and `u16` map
+/// `255` to `65535`, which multiplies by `257`. Results are rounded to the nearest integer for
+/// integer types, with halves rounded away from zero, and saturate at the bounds of `B`.
+pub fn convert_depth<A, B>(image: NdColor<'_, A>) -> Array3<B>
+where
+    A: Primitive,
+    B: Primitive,
+{
+    convert_values(image, unit::<B>() / unit::<A>())
+}
+
+/// Converts the samples of a 3d array from the full range of `A` to `f32` in `0.0..=1.0`.
+///
+/// This is [`convert_depth`] into `f32`, so `u8` samples are divided by `255.0`.
+pub fn to_f32_unit<A: Primitive>(image: NdColor<'_, A>) -> Array3<f32> {
+    convert_depth(image)
+}
+
+/// Converts the samples of a 3d array of `f32` in `0.0..=1.0` to the full range of `A`.
+///
+/// This is [`convert_depth`] from `f32`, so samples are scaled by the largest value of `A`,
+/// rounded, and values outside of `0.0..=1.0` saturate.
+pub fn from_f32_unit<A: Primitive>(image: NdColor<'_, f32>) -> Array3<A> {
+    convert_depth(image)
+}
+
+/// The sample that represents full intensity in `A`.
+fn unit<A: Primitive>() -> f64 {
+    if is_integer::<A>() {
+        A::max_value().to_f64().unwrap()
+    } else {
+        1.0
+    }
+}
@@ -0,0 +1,121 @@
+use crate::NdGray;
+use ndarray::{Array2, ArrayViewMut1, Axis};
+
+/// How the distance between two pixels is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceMetric {
+    /// The straight line distance.
+    Euclidean,
+    /// The larger of the horizontal and vertical distances, so diagonal steps count as one.
+    Chebyshev,
+}
+
+/// Computes the distance of each nonzero pixel of a mask to the closest zero pixel.
+///
+/// Zero pixels have a distance of zero, like in OpenCV's `distanceTransform`. Euclidean distances
+/// are exact and computed in linear time with the lower envelopes of parabolas from Felzenszwalb
+/// and Huttenlocher. All distances are infinite if the mask has no zero pixels.
+pub fn distance_transform(mask: NdGray<'_, u8>, metric: DistanceMetric) -> Array2<f32> {
+    match metric {
+        DistanceMetric::Euclidean => euclidean(mask),
+        DistanceMetric::Chebyshev => chebyshev(mask),
+    }
+}
+
+/// Transforms the columns and then the rows of the squared distances.
+fn euclidean(mask: NdGray<'_, u8>) -> Array2<f32> {
+    let mut squared = mask.mapv(|v| if v == 0 { 0.0 } else { f64::INFINITY });
+    let mut buffers = Buffers::default();
+    for axis in [Axis(0), Axis(1)] {
+        for lane in squared.lanes_mut(axis) {
+            buffers.transform(lane);
+        }
+    }
+    squared.mapv(|v| v.sqrt() as f32)
+}
+
+/// The reused storage of the one-dimensional squared distance transform.
+#[derive(Default)]
+struct Buffers {
+    values: Vec<f64>,
+    /// The positions of the parabolas in the lower envelope.
+    vertices: Vec<usize>,
+    /// The positions where each parabola of the envelope starts being the lowest.
+    starts: Vec<f64>,
+}
+
+impl Buffers {
+    /// Replaces each value of `lane` with the smallest `(q - p)^2 + lane[p]` over all `p`.
+    fn transform(&mut self, mut lane: ArrayViewMut1<'_, f64>) {
+        self.values.clear();
+        self.values.extend(lane.iter());
+        self.vertices.clear();
+        self.starts.clear();
+        let f = &self.values;
+        let parabola = |p: usize| f[p] + (p * p) as f64;
+        // Infinite values never form part of the envelope, and leaving them out avoids NaNs.
+        for q in (0..f.len()).filter(|&q| f[q].is_finite()) {
+            while let Some(&p) = self.vertices.last() {
+                let start = (parabola(q) - parabola(p)) / (2 * (q - p)) as f64;
+                if start > *self.starts.last().unwrap() {
+                    self.vertices.push(q);
+                    self.starts.push(start);
+                    break;
+                }
+                self.vertices.pop();
+                self.starts.pop();
+            }
+            if self.vertices.is_empty() {
+                self.vertices.push(q);
+                self.starts.push(f64::NEG_INFINITY);
+            }
+        }
+        if self.vertices.is_empty() {
+            return;
+        }
+        let mut k = 0;
+        for (q, v) in lane.iter_mut().enumerate() {
+            while k + 1 < self.vertices.len() && self.starts[k + 1] <= q as f64 {
+                k += 1;
+            }
+            let p = self.vertices[k];
+            let d = q.abs_diff(p) as f64;
+            *v = d * d + f[p];
+        }
+    }
+}
+
+/// Propagates the distances forwards and then backwards through the 8-connected neighbours.
+fn chebyshev(mask: NdGray<'_, u8>) -> Array2<f32> {
+    let (height, width) = mask.dim();
+    let mut distances = mask.mapv(|v| if v == 0 { 0.0 } else { f32::INFINITY });
+    for y in 0..height {
+        for x in 0..width {
+            let mut d = distances[[y, x]];
+            if x > 0 {
+                d = d.min(distances[[y, x - 1]] + 1.0);
+            }
+            if y > 0 {
+                for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                    d = d.min(distances[[y - 1, nx]] + 1.0);
+                }
+            }
+            distances[[y, x]] = d;
+        }
+    }
+    for y in (0..height).rev() {
+        for x in (0..width).rev() {
+            let mut d = distances[[y, x]];
+            if x + 1 < width {
+                d = d.min(distances[[y, x + 1]] + 1.0);
+            }
+            if y + 1 < height {
+                for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                    d = d.min(distances[[y + 1, nx]] + 1.0);
+                }
+            }
+            distances[[y, x]] = d;
+        }
+    }
+    distances
+}
@@ -0,0 +1,140 @@
+use crate::limits::Target;
+use crate::{Colors, ConversionError, IntoNdColor, IntoNdGray, NdImageResult, ToImageBuffer};
+use image::DynamicImage;
+use ndarray::{Array2, Array3};
+use std::convert::TryFrom;
+use std::path::Path;
+
+/// Opens an image using the `image` crate and loads it into an array of its native color type.
+///
+/// The colors and bit depth of the image are available from [`NdDynamicImage::colors`] and
+/// [`NdDynamicImage::bit_depth`].
+pub fn open_dynamic_image(path: impl AsRef<Path>) -> NdImageResult<NdDynamicImage> {
+    Ok(crate::limits::open(path, Target::Decoded)?.into())
+}
+
+/// An owned array holding an image in the color type and bit depth it was decoded with.
+///
+/// This mirrors `image::DynamicImage`, so no color conversion is forced on the image.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NdDynamicImage {
+    GrayU8(Array2<u8>),
+    GrayAlphaU8(Array3<u8>),
+    RgbU8(Array3<u8>),
+    RgbaU8(Array3<u8>),
+    BgrU8(Array3<u8>),
+    BgraU8(Array3<u8>),
+    GrayU16(Array2<u16>),
+    GrayAlphaU16(Array3<u16>),
+    RgbU16(Array3<u16>),
+    RgbaU16(Array3<u16>),
+}
+
+/// Saves an image from an array of its native color type using the `image` crate.
+///
+/// Together with [`open_dynamic_image`], this passes images through without converting their
+/// colors or bit depth. The format must support the color type of the image.
+pub fn save_dynamic_image(path: impl AsRef<Path>, image: &NdDynamicImage) -> NdImageResult<()> {
+    let colors = image.colors();
+    match image {
+        NdDynamicImage::GrayU8(image) => crate::save_gray_image(path, image.view()),
+        NdDynamicImage::GrayAlphaU8(image)
+        | NdDynamicImage::RgbU8(image)
+        | NdDynamicImage::RgbaU8(image)
+        | NdDynamicImage::BgrU8(image)
+        | NdDynamicImage::BgraU8(image) => crate::save_image(path, image.view(), colors),
+        NdDynamicImage::GrayU16(image) => crate::save_gray_image_u16(path, image.view()),
+        NdDynamicImage::GrayAlphaU16(image)
+        | NdDynamicImage::RgbU16(image)
+        | NdDynamicImage::RgbaU16(image) => crate::save_image_u16(path, image.view(), colors),
+    }
+}
+
+impl NdDynamicImage {
+    /// The colors of the image.
+    pub fn colors(&self) -> Colors {
+        match self {
+            NdDynamicImage::GrayU8(_) | NdDynamicImage::GrayU16(_) => Colors::Luma,
+            NdDynamicImage::GrayAlphaU8(_) | NdDynamicImage::GrayAlphaU16(_) => Colors::LumaA,
+            NdDynamicImage::RgbU8(_) | NdDynamicImage::RgbU16(_) => Colors::Rgb,
+            NdDynamicImage::RgbaU8(_) | NdDynamicImage::RgbaU16(_) => Colors::Rgba,
+            NdDynamicImage::BgrU8(_) => Colors::Bgr,
+            NdDynamicImage::BgraU8(_) => Colors::Bgra,
+        }
+    }
+
+    /// The number of bits in each channel of the image.
+    pub fn bit_depth(&self) -> u8 {
+        match self {
+            NdDynamicImage::GrayU8(_)
+            | NdDynamicImage::GrayAlphaU8(_)
+            | NdDynamicImage::RgbU8(_)
+            | NdDynamicImage::RgbaU8(_)
+            | NdDynamicImage::BgrU8(_)
+            | NdDynamicImage::BgraU8(_) => 8,
+            _ => 16,
+        }
+    }
+
+    /// The `(height, width)` dimensions of the image.
+    pub fn dimensions(&self) -> (usize, usize) {
+        match self {
+            NdDynamicImage::GrayU8(image) => image.dim(),
+            NdDynamicImage::GrayU16(image) => image.dim(),
+            NdDynamicImage::GrayAlphaU8(image)
+            | NdDynamicImage::RgbU8(image)
+            | NdDynamicImage::RgbaU8(image)
+            | NdDynamicImage::BgrU8(image)
+            | NdDynamicImage::BgraU8(image) => (image.dim().0, image.dim().1),
+            NdDynamicImage::GrayAlphaU16(image)
+            | NdDynamicImage::RgbU16(image)
+            | NdDynamicImage::RgbaU16(image) => (image.dim().0, image.dim().1),
+        }
+    }
+}
+
+/// Turn a `DynamicImage` into the matching array without copying.
+impl From<DynamicImage> for NdDynamicImage {
+    fn from(image: DynamicImage) -> Self {
+        match image {
+            DynamicImage::ImageLuma8(image) => NdDynamicImage::GrayU8(image.into_nd_gray()),
+            DynamicImage::ImageLumaA8(image) => NdDynamicImage::GrayAlphaU8(image.into_nd_color()),
+            DynamicImage::ImageRgb8(image) => NdDynamicImage::RgbU8(image.into_nd_color()),
+            DynamicImage::ImageRgba8(image) => NdDynamicImage::RgbaU8(image.into_nd_color()),
+            DynamicImage::ImageBgr8(image) => NdDynamicImage::BgrU8(image.into_nd_color()),
+            DynamicImage::ImageBgra8(image) => NdDynamicImage::BgraU8(image.into_nd_color()),
+            DynamicImage::ImageLuma16(image) => NdDynamicImage::GrayU16(image.into_nd_gray()),
+            DynamicImage::ImageLumaA16(image) => {
+                NdDynamicImage::GrayAlphaU16(image.into_nd_color())
+            }
+            DynamicImage::ImageRgb16(image) => NdDynamicImage::RgbU16(image.into_nd_color()),
+            DynamicImage::ImageRgba16(image) => NdDynamicImage::RgbaU16(image.into_nd_color()),
+        }
+    }
+}
+
+/// Turn an `NdDynamicImage` back into a `DynamicImage`.
+///
+/// Fails if the array of a variant has the wrong number of channels for it.
+impl TryFrom<NdDynamicImage> for DynamicImage {
+    type Error = ConversionError;
+
+    fn try_from(image: NdDynamicImage) -> Result<Self, Self::Error> {
+        Ok(match image {
+            NdDynamicImage::GrayU8(image) => DynamicImage::ImageLuma8(image.into_image_buffer()?),
+            NdDynamicImage::GrayAlphaU8(image) => {
+                DynamicImage::ImageLumaA8(image.into_image_buffer()?)
+            }
+            NdDynamicImage::RgbU8(image) => DynamicImage::ImageRgb8(image.into_image_buffer()?),
+            NdDynamicImage::RgbaU8(image) => DynamicImage::ImageRgba8(image.into_image_buffer()?),
+            NdDynamicImage::BgrU8(image) => DynamicImage::ImageBgr8(image.into_image_buffer()?),
+            NdDynamicImage::BgraU8(image) => DynamicImage::ImageBgra8(image.into_image_buffer()?),
+            NdDynamicImage::GrayU16(image) => DynamicImage::ImageLuma16(image.into_image_buffer()?),
+            NdDynamicImage::GrayAlphaU16(image) => {
+                DynamicImage::ImageLumaA16(image.into_image_buffer()?)
+            }
+            NdDynamicImage::RgbU16(image) => DynamicImage::ImageRgb16(image.into_image_buffer()?),
+            NdDynamicImage::RgbaU16(image) => DynamicImage::ImageRgba16(image.into_image_buffer()?),
+        })
+    }
+}
@@ -0,0 +1,364 @@
+use crate::{Colors, ConversionError, ImageBufferPool, NdColor, NdImageResult, ToImageBuffer};
+use image::{DynamicImage, ImageBuffer, ImageFormat};
+use ndarray::ArrayViewMut3;
+use std::fs::File;
+use std::io::{BufWriter, Cursor, Seek, Write};
+use std::path::Path;
+
+/// Options for the encoders used when saving arrays.
+///
+/// Each option only affects the formats it names, and the defaults match the `image` crate.
+/// There are no WebP options, since the `image` crate only decodes WebP images, so saving them
+/// fails with an unsupported error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaveOptions {
+    /// JPEG quality from 1 to 100.
+    ///
+    /// This is the only JPEG option. The JPEG encoder of the `image` crate always writes
+    /// baseline images with full resolution chroma (4:4:4), and has no chroma subsampling or
+    /// progressive encoding to configure.
+    pub jpeg_quality: u8,
+    /// PNG compression level.
+    pub png_compression: PngCompression,
+    /// PNG filter applied to each row before compression.
+    pub png_filter: PngFilter,
+    /// Encode PNG images in independent chunks of rows, which are compressed in parallel with
+    /// the `rayon` feature.
+    ///
+    /// This is much faster for large images, at the cost of slightly larger files.
+    pub png_parallel: bool,
+    /// Interlace PNG images with Adam7, so they can be displayed progressively as they load.
+    ///
+    /// Interlaced images are always encoded in chunks of rows like with `png_parallel`, since the
+    /// PNG encoder of the `image` crate cannot interlace them.
+    pub png_interlace: bool,
+}
+
+impl SaveOptions {
+    /// Options that favor encoding speed over file size.
+    ///
+    /// PNG rows are not filtered and are compressed in independent chunks, in parallel with the
+    /// `rayon` feature.
+    pub fn fast() -> Self {
+        SaveOptions {
+            png_compression: PngCompression::Fast,
+            png_filter: PngFilter::NoFilter,
+            png_parallel: true,
+            ..SaveOptions::default()
+        }
+    }
+
+    /// Options that favor file size over encoding speed.
+    pub fn best() -> Self {
+        SaveOptions {
+            png_compression: PngCompression::Best,
+            png_filter: PngFilter::Paeth,
+            ..SaveOptions::default()
+        }
+    }
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        SaveOptions {
+            jpeg_quality: 75,
+            png_compression: PngCompression::Fast,
+            png_filter: PngFilter::Sub,
+            png_parallel: false,
+            png_interlace: false,
+        }
+    }
+}
+
+/// Compression level of the PNG encoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PngCompression {
+    Default,
+    Fast,
+    Best,
+    Huffman,
+    Rle,
+}
+
+/// Filter used by the PNG encoder to improve compression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PngFilter {
+    NoFilter,
+    Sub,
+    Up,
+    Avg,
+    Paeth,
+}
+
+/// Encodes a color image from a 3d array into memory in the given format.
+///
+/// This performs a copy.
+pub fn encode_image(
+    image: NdColor<'_, u8>,
+    colors: Colors,
+    format: ImageFormat,
+) -> NdImageResult<Vec<u8>> {
+    let mut encoded = Cursor::new(Vec::new());
+    write_image(&mut encoded, image, colors, format)?;
+    Ok(encoded.into_inner())
+}
+
+/// Encodes a color image from a 3d array into memory in the given format, taking the copied
+/// pixels and the encoded bytes from `pool`.
+///
+/// The copy is returned to the pool after encoding. The encoded bytes can be returned with
+/// [`ImageBufferPool::recycle`] once they are no longer needed.
+pub fn encode_image_with_pool(
+    image: NdColor<'_, u8>,
+    colors: Colors,
+    format: ImageFormat,
+    pool: &ImageBufferPool,
+) -> NdImageResult<Vec<u8>> {
+    let (height, width, channels) = image.dim();
+    if channels != colors.channel_count() {
+        return Err(ConversionError::WrongChannelCount {
+            expected: colors.channel_count(),
+            got: channels,
+        }
+        .into());
+    }
+    let mut pixels = pool.take(image.len());
+    for (dst, &src) in pixels.iter_mut().zip(image.iter()) {
+        *dst = src;
+    }
+    let view = ArrayViewMut3::from_shape((height, width, channels), &mut pixels[..]).unwrap();
+    match colors {
+        Colors::Bgr | Colors::Bgra => crate::swap_rb(view)?,
+        Colors::YCbCr => crate::ycbcr::ycbcr_to_rgb_in_place(view),
+        _ => {}
+    }
+    let (width, height) = (width as u32, height as u32);
+    let dynamic = match colors {
+        Colors::Luma => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageLuma8),
+        Colors::LumaA => {
+            ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageLumaA8)
+        }
+        Colors::Rgb | Colors::Bgr | Colors::YCbCr => {
+            ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgb8)
+        }
+        Colors::Rgba | Colors::Bgra => {
+            ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgba8)
+        }
+    }
+    .unwrap();
+    let mut encoded = pool.take(0);
+    encoded.clear();
+    let mut cursor = Cursor::new(encoded);
+    let result = write_dynamic_image(&mut cursor, &dynamic, format, SaveOptions::default());
+    pool.recycle(dynamic.into_bytes());
+    result?;
+    Ok(cursor.into_inner())
+}
+
+/// Encodes a color image from a 3d array into a writer in the given format.
+///
+/// This performs a copy.
+pub fn write_image(
+    writer: impl Write + Seek,
+    image: NdColor<'_, u8>,
+    colors: Colors,
+    format: ImageFormat,
+) -> NdImageResult<()> {
+    write_image_with_options(writer, image, colors, format, SaveOptions::default())
+}
+
+/// Encodes a color image from a 3d array into a writer in the given format, configuring the
+/// encoder with `options`.
+///
+/// This performs a copy.
+pub fn write_image_with_options(
+    writer: impl Write + Seek,
+    image: NdColor<'_, u8>,
+    colors: Colors,
+    format: ImageFormat,
+    options: SaveOptions,
+) -> NdImageResult<()> {
+    write_dynamic_image(writer, &to_dynamic_image(image, colors)?, format, options)
+}
+
+/// Saves a color image using the `image` crate from a 3d array, configuring the encoder with
+/// `options`.
+///
+/// The format is guessed from the extension of the path.
+pub fn save_image_with_options(
+    path: impl AsRef<Path>,
+    image: NdColor<'_, u8>,
+    colors: Colors,
+    options: SaveOptions,
+) -> NdImageResult<()> {
+    let path = path.as_ref();
+    let format = ImageFormat::from_path(path)?;
+    // Unsupported formats are rejected before an empty file is created for them.
+    if format == ImageFormat::WebP {
+        return Err(webp_unsupported());
+    }
+    let file = BufWriter::new(File::create(path)?);
+    write_image_with_options(file, image, colors, format, options)
+}
+
+/// Saves a 16-bit color image using the `image` crate from a 3d array, configuring the encoder
+/// with `options`.
+///
+/// The format is guessed from the extension of the path and must support 16-bit images, such as
+/// PNG or TIFF. This performs a copy.
+pub fn save_image_u16_with_options(
+    path: impl AsRef<Path>,
+    image: NdColor<'_, u16>,
+    colors: Colors,
+    options: SaveOptions,
+) -> NdImageResult<()> {
+    let path = path.as_ref();
+    let format = ImageFormat::from_path(path)?;
+    // Strided arrays are gathered into standard layout while they are copied.
+    let owned = || image.as_standard_layout().into_owned();
+    // The `image` crate only encodes colors in RGB order, so BGR colors are swapped first and
+    // YCbCr colors are converted.
+    let image = match colors {
+        Colors::Luma => DynamicImage::ImageLuma16(owned().into_image_buffer()?),
+        Colors::LumaA => DynamicImage::ImageLumaA16(owned().into_image_buffer()?),
+        Colors::Rgb => DynamicImage::ImageRgb16(owned().into_image_buffer()?),
+        Colors::Rgba => DynamicImage::ImageRgba16(owned().into_image_buffer()?),
+        Colors::Bgr => {
+            let image = crate::swizzle::swapped_rb(image, 3)?;
+            DynamicImage::ImageRgb16(image.into_image_buffer()?)
+        }
+        Colors::Bgra => {
+            let image = crate::swizzle::swapped_rb(image, 4)?;
+            DynamicImage::ImageRgba16(image.into_image_buffer()?)
+        }
+        Colors::YCbCr => {
+            let image = crate::ycbcr::rgb_from_ycbcr(image)?;
+            DynamicImage::ImageRgb16(image.into_image_buffer()?)
+        }
+    };
+    let file = BufWriter::new(File::create(path)?);
+    write_dynamic_image(file, &image, format, options)
+}
+
+/// Saves a color image using the `image` crate from a 3d array in the given format.
+///
+/// Unlike [`save_image`](crate::save_image), the format is not guessed from the extension of the
+/// path.
+pub fn save_image_with_format(
+    path: impl AsRef<Path>,
+    image: NdColor<'_, u8>,
+    colors: Colors,
+    format: ImageFormat,
+) -> NdImageResult<()> {
+    let file = BufWriter::new(File::create(path)?);
+    write_image(file, image, colors, format)
+}
+
+/// Copies a 3d array into a `DynamicImage`, putting BGR colors into RGB order and converting
+/// YCbCr colors to RGB.
+pub(crate) fn to_dynamic_image(
+    image: NdColor<'_, u8>,
+    colors: Colors,
+) -> NdImageResult<DynamicImage> {
+    // Strided arrays are gathered into standard layout while they are copied.
+    let owned = || image.as_standard_layout().into_owned();
+    Ok(match colors {
+        Colors::Luma => DynamicImage::ImageLuma8(owned().into_image_buffer()?),
+        Colors::LumaA => DynamicImage::ImageLumaA8(owned().into_image_buffer()?),
+        Colors::Rgb => DynamicImage::ImageRgb8(owned().into_image_buffer()?),
+        Colors::Rgba => DynamicImage::ImageRgba8(owned().into_image_buffer()?),
+        Colors::Bgr => {
+            let image = crate::swizzle::swapped_rb(image, 3)?;
+            DynamicImage::ImageRgb8(image.into_image_buffer()?)
+        }
+        Colors::Bgra => {
+            let image = crate::swizzle::swapped_rb(image, 4)?;
+            DynamicImage::ImageRgba8(image.into_image_buffer()?)
+        }
+        Colors::YCbCr => {
+            let image = crate::ycbcr::rgb_from_ycbcr(image)?;
+            DynamicImage::ImageRgb8(image.into_image_buffer()?)
+        }
+    })
+}
+
+/// Encodes a `DynamicImage` into a writer in the given format.
+// Some options and dimensions are unused when their formats are disabled.
+#[allow(unused_variables)]
+pub(crate) fn write_dynamic_image<W: Write + Seek>(
+    mut writer: W,
+    image: &DynamicImage,
+    format: ImageFormat,
+    options: SaveOptions,
+) -> NdImageResult<()> {
+    let (width, height) = image::GenericImageView::dimensions(image);
+    match format {
+        #[cfg(feature = "jpeg")]
+        ImageFormat::Jpeg => {
+            image::codecs::jpeg::JpegEncoder::new_with_quality(&mut writer, options.jpeg_quality)
+                .encode(image.as_bytes(), width, height, image.color())?;
+        }
+        #[cfg(feature = "png")]
+        ImageFormat::Png if options.png_parallel || options.png_interlace => {
+            crate::png::write_png_chunked(
+                writer,
+                image.as_bytes(),
+                width,
+                height,
+                image.color(),
+                &options,
+            )?;
+        }
+        #[cfg(feature = "png")]
+        ImageFormat::Png => {
+            use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+            use image::ImageEncoder;
+
+            let compression = match options.png_compression {
+                PngCompression::Default => CompressionType::Default,
+                PngCompression::Fast => CompressionType::Fast,
+                PngCompression::Best => CompressionType::Best,
+                PngCompression::Huffman => CompressionType::Huffman,
+                PngCompression::Rle => CompressionType::Rle,
+            };
+            let filter = match options.png_filter {
+                PngFilter::NoFilter => FilterType::NoFilter,
+                PngFilter::Sub => FilterType::Sub,
+                PngFilter::Up => FilterType::Up,
+                PngFilter::Avg => FilterType::Avg,
+                PngFilter::Paeth => FilterType::Paeth,
+            };
+            // Unlike `encode`, this expects 16-bit samples in native rather than big endian order.
+            PngEncoder::new_with_quality(writer, compression, filter).write_image(
+                image.as_bytes(),
+                width,
+                height,
+                image.color(),
+            )?;
+        }
+        // TIFF is the only format `image` needs to seek for, so it cannot go through `write_to`.
+        #[cfg(feature = "tiff")]
+        ImageFormat::Tiff => {
+            image::codecs::tiff::TiffEncoder::new(writer).encode(
+                image.as_bytes(),
+                width,
+                height,
+                image.color(),
+            )?;
+        }
+        ImageFormat::WebP => return Err(webp_unsupported()),
+        format => image.write_to(&mut writer, format)?,
+    }
+    Ok(())
+}
+
+/// The error for saving WebP images, which the `image` crate can only decode.
+fn webp_unsupported() -> crate::NdImageError {
+    use image::error::{ImageFormatHint, UnsupportedError, UnsupportedErrorKind};
+
+    image::ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+        ImageFormatHint::Exact(ImageFormat::WebP),
+        UnsupportedErrorKind::GenericFeature("encoding".to_owned()),
+    ))
+    .into()
+}
@@ -0,0 +1,155 @@
+use crate::ChannelOrder;
+use image::ImageError;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+/// The reason a conversion from an `ndarray` array into an `image` buffer failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The channel axis of the array did not match the channel count of the pixel type.
+    WrongChannelCount { expected: usize, got: usize },
+    /// The array was not contiguous in standard layout.
+    NonContiguous,
+    /// The shape of the array cannot be represented as an image.
+    BadShape { shape: Vec<usize> },
+    /// The buffer of an image with the `(height, width, channels)` shape `shape` had only `len`
+    /// samples.
+    BufferTooShort { shape: Vec<usize>, len: usize },
+    /// The color channels of the array were in the other order than the pixel type.
+    ChannelOrderMismatch {
+        expected: ChannelOrder,
+        got: ChannelOrder,
+    },
+    /// The new order of the channels was not a permutation of their indices.
+    BadPermutation { order: Vec<usize> },
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::WrongChannelCount { expected, got } => write!(
+                f,
+                "expected an ndarray Array with {} channels, but it had {}",
+                expected, got
+            ),
+            ConversionError::NonContiguous => write!(f, "non-contiguous ndarray Array"),
+            ConversionError::BadShape { shape } => {
+                write!(f, "ndarray Array of shape {:?} is not a valid image", shape)
+            }
+            ConversionError::BufferTooShort { shape, len } => write!(
+                f,
+                "image buffer of length {} is too short for an image of shape {:?}",
+                len, shape
+            ),
+            ConversionError::ChannelOrderMismatch { expected, got } => write!(
+                f,
+                "expected color channels in {:?} order, but they were in {:?} order",
+                expected, got
+            ),
+            ConversionError::BadPermutation { order } => {
+                write!(f, "channel order {:?} is not a permutation", order)
+            }
+        }
+    }
+}
+
+impl Error for ConversionError {}
+
+/// The reason [`Colors`](crate::Colors) could not be made from a name or a number of channels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColorsError {
+    /// The name did not match any colors.
+    UnknownName(String),
+    /// No gray or RGB colors have this number of channels.
+    UnsupportedChannelCount(usize),
+}
+
+impl fmt::Display for ColorsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorsError::UnknownName(name) => write!(f, "unknown colors {:?}", name),
+            ColorsError::UnsupportedChannelCount(channels) => {
+                write!(f, "no colors have {} channels", channels)
+            }
+        }
+    }
+}
+
+impl Error for ColorsError {}
+
+/// The error type returned by the functions of this crate.
+#[derive(Debug)]
+pub enum NdImageError {
+    /// The `image` crate failed to decode, encode, or access an image.
+    Image(ImageError),
+    /// An array could not be converted into an image.
+    Conversion(ConversionError),
+    /// An image in a batch had different `(height, width)` dimensions than the ones before it.
+    DimensionMismatch {
+        path: PathBuf,
+        expected: (usize, usize),
+        got: (usize, usize),
+    },
+    /// The number of paths did not match the number of images in a batch.
+    BatchSizeMismatch { expected: usize, got: usize },
+    /// A progress callback stopped decoding before the image was loaded.
+    Aborted,
+}
+
+/// Result of a function from this crate.
+pub type NdImageResult<T> = Result<T, NdImageError>;
+
+impl fmt::Display for NdImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NdImageError::Image(err) => err.fmt(f),
+            NdImageError::Conversion(err) => err.fmt(f),
+            NdImageError::DimensionMismatch {
+                path,
+                expected,
+                got,
+            } => write!(
+                f,
+                "expected image {} to have dimensions {:?} like the rest of the batch, but it had {:?}",
+                path.display(),
+                expected,
+                got
+            ),
+            NdImageError::BatchSizeMismatch { expected, got } => write!(
+                f,
+                "expected {} paths for the images in the batch, but got {}",
+                expected, got
+            ),
+            NdImageError::Aborted => write!(f, "decoding was aborted by the progress callback"),
+        }
+    }
+}
+
+impl Error for NdImageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NdImageError::Image(err) => Some(err),
+            NdImageError::Conversion(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ImageError> for NdImageError {
+    fn from(err: ImageError) -> Self {
+        NdImageError::Image(err)
+    }
+}
+
+impl From<std::io::Error> for NdImageError {
+    fn from(err: std::io::Error) -> Self {
+        NdImageError::Image(ImageError::IoError(err))
+    }
+}
+
+impl From<ConversionError> for NdImageError {
+    fn from(err: ConversionError) -> Self {
+        NdImageError::Conversion(err)
+    }
+}
@@ -0,0 +1,361 @@
+use crate::metadata::{jpeg_segments, png_chunks};
+use crate::{flip_h, flip_v, rotate180, rotate270, rotate90};
+use image::DynamicImage;
+use ndarray::Array3;
+
+const MAKE: u16 = 0x010F;
+const MODEL: u16 = 0x0110;
+const ORIENTATION: u16 = 0x0112;
+const X_RESOLUTION: u16 = 0x011A;
+const Y_RESOLUTION: u16 = 0x011B;
+const RESOLUTION_UNIT: u16 = 0x0128;
+const SOFTWARE: u16 = 0x0131;
+const DATE_TIME: u16 = 0x0132;
+const COPYRIGHT: u16 = 0x8298;
+const EXIF_IFD: u16 = 0x8769;
+const ICC_PROFILE: u16 = 0x8773;
+const DATE_TIME_ORIGINAL: u16 = 0x9003;
+
+/// Fields read from the EXIF metadata of an image.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Exif {
+    pub(crate) make: Option<String>,
+    pub(crate) model: Option<String>,
+    /// The orientation of the image, from 1 to 8.
+    pub(crate) orientation: Option<u16>,
+    pub(crate) x_resolution: Option<f64>,
+    pub(crate) y_resolution: Option<f64>,
+    /// The unit of the resolution, 2 meaning inches and 3 centimeters.
+    pub(crate) resolution_unit: Option<u16>,
+    pub(crate) software: Option<String>,
+    pub(crate) date_time: Option<String>,
+    pub(crate) date_time_original: Option<String>,
+    pub(crate) copyright: Option<String>,
+    /// The embedded ICC profile, which only TIFF images store this way.
+    pub(crate) icc_profile: Option<Vec<u8>>,
+}
+
+impl Exif {
+    /// Gets the horizontal and vertical resolution in dots per inch.
+    pub(crate) fn dpi(&self) -> Option<(f64, f64)> {
+        let scale = match self.resolution_unit.unwrap_or(2) {
+            2 => 1.0,
+            3 => 2.54,
+            _ => return None,
+        };
+        Some((self.x_resolution? * scale, self.y_resolution? * scale))
+    }
+
+    /// Encodes the fields as a little endian TIFF structure.
+    ///
+    /// The orientation, original capture time, and ICC profile are not written.
+    pub(crate) fn to_tiff(&self) -> Vec<u8> {
+        // Each entry is a tag, a type, a count, and the bytes of its value.
+        let mut entries: Vec<(u16, u16, u32, Vec<u8>)> = Vec::new();
+        let mut ascii = |tag, value: &Option<String>| {
+            if let Some(value) = value {
+                let mut bytes = value.as_bytes().to_vec();
+                bytes.push(0);
+                entries.push((tag, 2, bytes.len() as u32, bytes));
+            }
+        };
+        ascii(MAKE, &self.make);
+        ascii(MODEL, &self.model);
+        ascii(SOFTWARE, &self.software);
+        ascii(DATE_TIME, &self.date_time);
+        ascii(COPYRIGHT, &self.copyright);
+        let rational = |value: f64| {
+            let mut bytes = ((value * 1000.0).round() as u32).to_le_bytes().to_vec();
+            bytes.extend_from_slice(&1000u32.to_le_bytes());
+            bytes
+        };
+        if let (Some(x), Some(y)) = (self.x_resolution, self.y_resolution) {
+            entries.push((X_RESOLUTION, 5, 1, rational(x)));
+            entries.push((Y_RESOLUTION, 5, 1, rational(y)));
+            let unit = self.resolution_unit.unwrap_or(2);
+            entries.push((RESOLUTION_UNIT, 3, 1, unit.to_le_bytes().to_vec()));
+        }
+        entries.sort_by_key(|entry| entry.0);
+
+        let mut tiff = b"II*\0\x08\0\0\0".to_vec();
+        tiff.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        // Values longer than four bytes are stored after the directory.
+        let mut data = Vec::new();
+        let data_offset = 8 + 2 + 12 * entries.len() + 4;
+        for (tag, kind, count, bytes) in &entries {
+            tiff.extend_from_slice(&tag.to_le_bytes());
+            tiff.extend_from_slice(&kind.to_le_bytes());
+            tiff.extend_from_slice(&count.to_le_bytes());
+            if bytes.len() <= 4 {
+                let mut value = [0; 4];
+                value[..bytes.len()].copy_from_slice(bytes);
+                tiff.extend_from_slice(&value);
+            } else {
+                tiff.extend_from_slice(&((data_offset + data.len()) as u32).to_le_bytes());
+                data.extend_from_slice(bytes);
+                // Values start on a word boundary.
+                if data.len() % 2 == 1 {
+                    data.push(0);
+                }
+            }
+        }
+        tiff.extend_from_slice(&[0; 4]);
+        tiff.extend_from_slice(&data);
+        tiff
+    }
+}
+
+/// Reads the EXIF metadata of an encoded JPEG, PNG, or TIFF image.
+///
+/// Returns `None` if the image has no EXIF metadata or it cannot be parsed.
+pub(crate) fn read_exif(data: &[u8]) -> Option<Exif> {
+    if data.starts_with(&[0xFF, 0xD8]) {
+        jpeg_segments(data)
+            .find(|&(marker, segment)| marker == 0xE1 && segment.starts_with(b"Exif\0\0"))
+            .and_then(|(_, segment)| parse_exif(&segment[6..]))
+    } else if data.starts_with(b"\x89PNG") {
+        png_chunks(data)
+            .find(|&(kind, _)| kind == b"eXIf")
+            .and_then(|(_, chunk)| parse_exif(chunk))
+    } else {
+        // The metadata of TIFF images is stored in the same structure as EXIF.
+        parse_exif(data)
+    }
+}
+
+/// Parses EXIF metadata stored in a TIFF structure.
+pub(crate) fn parse_exif(tiff: &[u8]) -> Option<Exif> {
+    let ifd = Ifd::first(tiff)?;
+    let mut exif = Exif::default();
+    for entry in ifd.entries() {
+        match entry.tag {
+            MAKE => exif.make = entry.ascii(),
+            MODEL => exif.model = entry.ascii(),
+            ORIENTATION => exif.orientation = entry.short(),
+            X_RESOLUTION => exif.x_resolution = entry.rational(),
+            Y_RESOLUTION => exif.y_resolution = entry.rational(),
+            RESOLUTION_UNIT => exif.resolution_unit = entry.short(),
+            SOFTWARE => exif.software = entry.ascii(),
+            DATE_TIME => exif.date_time = entry.ascii(),
+            COPYRIGHT => exif.copyright = entry.ascii(),
+            ICC_PROFILE => exif.icc_profile = entry.bytes().map(<[u8]>::to_vec),
+            EXIF_IFD => {
+                let sub_ifd = entry.long().map(|offset| ifd.at(offset as usize));
+                for entry in sub_ifd.iter().flat_map(Ifd::entries) {
+                    if entry.tag == DATE_TIME_ORIGINAL {
+                        exif.date_time_original = entry.ascii();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(exif)
+}
+
+/// Rotates and flips a decoded image so that it is displayed upright according to its EXIF
+/// orientation.
+pub(crate) fn apply_orientation(image: DynamicImage, orientation: u16) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+/// Rotates and flips a 3d array upright like [`apply_orientation`].
+pub(crate) fn orient_array<A: Clone>(image: Array3<A>, orientation: u16) -> Array3<A> {
+    let image = match orientation {
+        2 => flip_h(image),
+        3 => rotate180(image),
+        4 => flip_v(image),
+        5 => flip_h(rotate90(image)),
+        6 => rotate90(image),
+        7 => flip_h(rotate270(image)),
+        8 => rotate270(image),
+        _ => return image,
+    };
+    image.as_standard_layout().into_owned()
+}
+
+/// An image file directory of a TIFF structure.
+pub(crate) struct Ifd<'a> {
+    tiff: &'a [u8],
+    little_endian: bool,
+    offset: usize,
+}
+
+/// An entry of an image file directory.
+pub(crate) struct Entry<'a> {
+    pub(crate) tag: u16,
+    kind: u16,
+    count: u32,
+    value: [u8; 4],
+    ifd: &'a Ifd<'a>,
+}
+
+impl<'a> Ifd<'a> {
+    /// Gets the first image file directory of a TIFF structure.
+    pub(crate) fn first(tiff: &'a [u8]) -> Option<Self> {
+        let little_endian = match tiff.get(..4)? {
+            b"II*\0" => true,
+            b"MM\0*" => false,
+            _ => return None,
+        };
+        let ifd = Ifd {
+            tiff,
+            little_endian,
+            offset: 0,
+        };
+        Some(ifd.at(ifd.u32_at(4)? as usize))
+    }
+
+    /// Gets another image file directory of the same TIFF structure.
+    fn at(&self, offset: usize) -> Ifd<'a> {
+        Ifd {
+            tiff: self.tiff,
+            little_endian: self.little_endian,
+            offset,
+        }
+    }
+
+    pub(crate) fn entries(&self) -> impl Iterator<Item = Entry<'_>> {
+        let count = self.u16_at(self.offset).unwrap_or(0);
+        (0..usize::from(count)).filter_map(move |index| {
+            let pos = self.offset + 2 + index * 12;
+            let value = self.tiff.get(pos + 8..pos + 12)?;
+            Some(Entry {
+                tag: self.u16_at(pos)?,
+                kind: self.u16_at(pos + 2)?,
+                count: self.u32_at(pos + 4)?,
+                value: [value[0], value[1], value[2], value[3]],
+                ifd: self,
+            })
+        })
+    }
+
+    fn u16_at(&self, pos: usize) -> Option<u16> {
+        let bytes = self.tiff.get(pos..pos + 2)?;
+        Some(self.u16_from([bytes[0], bytes[1]]))
+    }
+
+    fn u32_at(&self, pos: usize) -> Option<u32> {
+        let bytes = self.tiff.get(pos..pos + 4)?;
+        Some(self.u32_from([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u16_from(&self, bytes: [u8; 2]) -> u16 {
+        if self.little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        }
+    }
+
+    fn u32_from(&self, bytes: [u8; 4]) -> u32 {
+        if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        }
+    }
+}
+
+// The types of entries are 2 for ASCII, 3 for shorts, 4 for longs, 5 for rationals, and 7 for
+// undefined bytes.
+impl Entry<'_> {
+    /// Gets the value of an entry holding a single short.
+    fn short(&self) -> Option<u16> {
+        if self.kind != 3 || self.count != 1 {
+            return None;
+        }
+        Some(self.ifd.u16_from([self.value[0], self.value[1]]))
+    }
+
+    /// Gets the values of an entry holding shorts or longs.
+    #[cfg_attr(not(all(feature = "mmap", unix)), allow(dead_code))]
+    pub(crate) fn unsigned(&self) -> Option<Vec<u32>> {
+        let count = self.count as usize;
+        let size = match self.kind {
+            3 => 2,
+            4 => 4,
+            _ => return None,
+        };
+        if count * size <= 4 {
+            return Some(
+                self.value
+                    .chunks_exact(size)
+                    .take(count)
+                    .map(|bytes| match size {
+                        2 => u32::from(self.ifd.u16_from([bytes[0], bytes[1]])),
+                        _ => self.ifd.u32_from(self.value),
+                    })
+                    .collect(),
+            );
+        }
+        let offset = self.ifd.u32_from(self.value) as usize;
+        (0..count)
+            .map(|index| match size {
+                2 => self.ifd.u16_at(offset + index * 2).map(u32::from),
+                _ => self.ifd.u32_at(offset + index * 4),
+            })
+            .collect()
+    }
+
+    /// Gets the value of an entry holding a single long.
+    fn long(&self) -> Option<u32> {
+        if self.kind != 4 || self.count != 1 {
+            return None;
+        }
+        Some(self.ifd.u32_from(self.value))
+    }
+
+    /// Gets the value of an entry holding a single rational.
+    fn rational(&self) -> Option<f64> {
+        if self.kind != 5 || self.count != 1 {
+            return None;
+        }
+        let offset = self.ifd.u32_from(self.value) as usize;
+        let numer = self.ifd.u32_at(offset)?;
+        let denom = self.ifd.u32_at(offset + 4)?;
+        if denom == 0 {
+            return None;
+        }
+        Some(f64::from(numer) / f64::from(denom))
+    }
+
+    /// Gets the value of an entry holding bytes.
+    fn bytes(&self) -> Option<&[u8]> {
+        if self.kind != 7 {
+            return None;
+        }
+        self.data()
+    }
+
+    /// Gets the value of an entry holding a string, without its null terminator.
+    fn ascii(&self) -> Option<String> {
+        if self.kind != 2 {
+            return None;
+        }
+        let bytes = self.data()?;
+        let bytes = bytes.split(|&b| b == 0).next().unwrap_or_default();
+        Some(String::from_utf8_lossy(bytes).trim().to_owned())
+    }
+
+    /// Gets the bytes of an entry holding single byte values, which are stored after the
+    /// directory if they do not fit in the entry.
+    fn data(&self) -> Option<&[u8]> {
+        let count = self.count as usize;
+        if count <= 4 {
+            Some(&self.value[..count])
+        } else {
+            let offset = self.ifd.u32_from(self.value) as usize;
+            self.ifd.tiff.get(offset..offset + count)
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{
+    Colors, ImgBgr, ImgBgra, ImgLuma, ImgLumaA, ImgRgb, ImgRgba, NdColor, NdImageResult,
+    ToImageBuffer,
+};
+use image::{ImageBuffer, Pixel, Primitive};
+use ndarray::{ArrayBase, Axis, Data, Ix2, Ix3};
+use std::path::Path;
+
+/// Methods to view and save 2d and 3d arrays as images.
+///
+/// 2d arrays are treated as `(height, width)` gray images and 3d arrays as
+/// `(height, width, channels)` color images. Views are only created without copying, so they fail
+/// on arrays that are not contiguous or that have the wrong number of channels.
+pub trait ToImageExt {
+    /// The type of each subpixel.
+    type Elem: Primitive + 'static;
+
+    /// Views the array as an image with any pixel type.
+    fn to_image<P>(&self) -> NdImageResult<ImageBuffer<P, &[Self::Elem]>>
+    where
+        P: Pixel<Subpixel = Self::Elem> + 'static;
+
+    /// Saves the array as an image using the `image` crate.
+    ///
+    /// The format is guessed from the extension of the path. 2d arrays must be saved as
+    /// [`Colors::Luma`].
+    fn save_image(&self, path: impl AsRef<Path>, colors: Colors) -> NdImageResult<()>
+    where
+        Self::Elem: SaveElement;
+
+    /// Views the array as a `Luma` image.
+    fn to_image_luma(&self) -> NdImageResult<ImgLuma<'_, Self::Elem>> {
+        self.to_image()
+    }
+
+    /// Views the array as a `LumaA` image.
+    fn to_image_luma_alpha(&self) -> NdImageResult<ImgLumaA<'_, Self::Elem>> {
+        self.to_image()
+    }
+
+    /// Views the array as an `Rgb` image.
+    fn to_image_rgb(&self) -> NdImageResult<ImgRgb<'_, Self::Elem>> {
+        self.to_image()
+    }
+
+    /// Views the array as an `Rgba` image.
+    fn to_image_rgba(&self) -> NdImageResult<ImgRgba<'_, Self::Elem>> {
+        self.to_image()
+    }
+
+    /// Views the array as a `Bgr` image.
+    fn to_image_bgr(&self) -> NdImageResult<ImgBgr<'_, Self::Elem>> {
+        self.to_image()
+    }
+
+    /// Views the array as a `Bgra` image.
+    fn to_image_bgra(&self) -> NdImageResult<ImgBgra<'_, Self::Elem>> {
+        self.to_image()
+    }
+}
+
+impl<S, A> ToImageExt for ArrayBase<S, Ix3>
+where
+    S: Data<Elem = A>,
+    A: Primitive + 'static,
+{
+    type Elem = A;
+
+    fn to_image<P>(&self) -> NdImageResult<ImageBuffer<P, &[A]>>
+    where
+        P: Pixel<Subpixel = A> + 'static,
+    {
+        Ok(self.view().into_image_buffer()?)
+    }
+
+    fn save_image(&self, path: impl AsRef<Path>, colors: Colors) -> NdImageResult<()>
+    where
+        A: SaveElement,
+    {
+        A::save(path.as_ref(), self.view(), colors)
+    }
+}
+
+impl<S, A> ToImageExt for ArrayBase<S, Ix2>
+where
+    S: Data<Elem = A>,
+    A: Primitive + 'static,
+{
+    type Elem = A;
+
+    fn to_image<P>(&self) -> NdImageResult<ImageBuffer<P, &[A]>>
+    where
+        P: Pixel<Subpixel = A> + 'static,
+    {
+        Ok(self.view().insert_axis(Axis(2)).into_image_buffer()?)
+    }
+
+    fn save_image(&self, path: impl AsRef<Path>, colors: Colors) -> NdImageResult<()>
+    where
+        A: SaveElement,
+    {
+        A::save(path.as_ref(), self.view().insert_axis(Axis(2)), colors)
+    }
+}
+
+/// The subpixel types that arrays can be saved with, which are `u8`, `u16`, and `f32`.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait SaveElement: Primitive + private::Sealed {
+    #[doc(hidden)]
+    fn save(path: &Path, image: NdColor<'_, Self>, colors: Colors) -> NdImageResult<()>;
+}
+
+impl SaveElement for u8 {
+    fn save(path: &Path, image: NdColor<'_, Self>, colors: Colors) -> NdImageResult<()> {
+        crate::save_image(path, image, colors)
+    }
+}
+
+impl SaveElement for u16 {
+    fn save(path: &Path, image: NdColor<'_, Self>, colors: Colors) -> NdImageResult<()> {
+        crate::save_image_u16(path, image, colors)
+    }
+}
+
+impl SaveElement for f32 {
+    fn save(path: &Path, image: NdColor<'_, Self>, colors: Colors) -> NdImageResult<()> {
+        crate::save_image_f32(path, image, colors)
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for f32 {}
+}
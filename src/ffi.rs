@@ -0,0 +1,158 @@
+use crate::{Colors, NdImageError};
+use ndarray::{ArrayView3, Axis, ShapeBuilder};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+/// The result of a call to the C interface.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NdImageStatus {
+    Ok = 0,
+    /// A pointer was null, a path was not UTF-8, or the colors or shape were invalid.
+    InvalidArgument = 1,
+    /// The `image` crate failed to decode, encode, or access the image.
+    Image = 2,
+    /// The buffer could not be converted into an image.
+    Conversion = 3,
+}
+
+/// A strided `(height, width, channels)` buffer of `u8` pixels.
+///
+/// The strides are counted in elements and may be negative. Buffers filled by [`ndimage_open`]
+/// are in standard layout and must be released with [`ndimage_free`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct NdImageBuffer {
+    pub data: *mut u8,
+    pub shape: [usize; 3],
+    pub strides: [isize; 3],
+}
+
+/// Opens an image from a UTF-8 path and loads it into `out` with the colors `colors`.
+///
+/// The colors are numbered `0` luma, `1` luma with alpha, `2` RGB, `3` RGBA, `4` BGR, `5` BGRA,
+/// `6` YCbCr.
+///
+/// # Safety
+///
+/// `path` must be a null terminated string and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ndimage_open(
+    path: *const c_char,
+    colors: c_int,
+    out: *mut NdImageBuffer,
+) -> NdImageStatus {
+    let (path, colors) = match (path_arg(path), colors_arg(colors)) {
+        (Some(path), Some(colors)) if !out.is_null() => (path, colors),
+        _ => return NdImageStatus::InvalidArgument,
+    };
+    let image = match crate::open_image(path, colors) {
+        Ok(image) => image,
+        Err(err) => return status(err),
+    };
+    let (height, width, channels) = image.dim();
+    let data = crate::into_standard_vec(image).into_boxed_slice();
+    *out = NdImageBuffer {
+        data: Box::into_raw(data) as *mut u8,
+        shape: [height, width, channels],
+        strides: [(width * channels) as isize, channels as isize, 1],
+    };
+    NdImageStatus::Ok
+}
+
+/// Saves a strided buffer with the colors `colors` to a UTF-8 path.
+///
+/// The format is guessed from the extension of the path. Buffers not in standard layout are
+/// copied before they are saved.
+///
+/// # Safety
+///
+/// `path` must be a null terminated string and `image` must point to a buffer whose data is
+/// valid for reads at every index within its shape and strides.
+#[no_mangle]
+pub unsafe extern "C" fn ndimage_save(
+    path: *const c_char,
+    image: *const NdImageBuffer,
+    colors: c_int,
+) -> NdImageStatus {
+    let (path, colors) = match (path_arg(path), colors_arg(colors)) {
+        (Some(path), Some(colors)) if !image.is_null() && !(*image).data.is_null() => {
+            (path, colors)
+        }
+        _ => return NdImageStatus::InvalidArgument,
+    };
+    let image = &*image;
+    let [height, width, channels] = image.shape;
+    // Views cannot be created with negative strides, so those axes start from their last element
+    // and are inverted afterwards.
+    let mut data = image.data as *const u8;
+    let mut strides = [0; 3];
+    for (axis, (&len, &stride)) in image.shape.iter().zip(&image.strides).enumerate() {
+        if stride < 0 && len > 0 {
+            data = data.offset(stride * (len as isize - 1));
+        }
+        strides[axis] = stride.unsigned_abs();
+    }
+    let shape = (height, width, channels).strides((strides[0], strides[1], strides[2]));
+    let mut view = ArrayView3::from_shape_ptr(shape, data);
+    for (axis, &stride) in image.strides.iter().enumerate() {
+        if stride < 0 {
+            view.invert_axis(Axis(axis));
+        }
+    }
+    match crate::save_image(path, view.as_standard_layout().view(), colors) {
+        Ok(()) => NdImageStatus::Ok,
+        Err(err) => status(err),
+    }
+}
+
+/// Releases a buffer filled by [`ndimage_open`] and sets its data to null.
+///
+/// Buffers with null data are ignored, so a buffer can safely be freed twice.
+///
+/// # Safety
+///
+/// `image` must be null or point to a buffer filled by [`ndimage_open`] that was not modified.
+#[no_mangle]
+pub unsafe extern "C" fn ndimage_free(image: *mut NdImageBuffer) {
+    if image.is_null() || (*image).data.is_null() {
+        return;
+    }
+    let image = &mut *image;
+    let len = image.shape.iter().product();
+    drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+        image.data, len,
+    )));
+    image.data = ptr::null_mut();
+}
+
+/// Reads a UTF-8 path from a C string.
+unsafe fn path_arg<'a>(path: *const c_char) -> Option<&'a str> {
+    if path.is_null() {
+        return None;
+    }
+    CStr::from_ptr(path).to_str().ok()
+}
+
+/// Looks up the colors numbered by the C interface.
+fn colors_arg(colors: c_int) -> Option<Colors> {
+    Some(match colors {
+        0 => Colors::Luma,
+        1 => Colors::LumaA,
+        2 => Colors::Rgb,
+        3 => Colors::Rgba,
+        4 => Colors::Bgr,
+        5 => Colors::Bgra,
+        6 => Colors::YCbCr,
+        _ => return None,
+    })
+}
+
+/// The status reported for an error of this crate.
+fn status(err: NdImageError) -> NdImageStatus {
+    match err {
+        NdImageError::Conversion(_) => NdImageStatus::Conversion,
+        _ => NdImageStatus::Image,
+    }
+}
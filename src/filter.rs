@@ -0,0 +1,225 @@
+use crate::num::saturate;
+use crate::{pad, BorderMode, NdGray};
+use image::Primitive;
+use ndarray::{s, Array, Array2, Array3, ArrayView, ArrayView2, ArrayView3, Axis, Dimension, Zip};
+
+/// Convolves each channel of a 2d gray or 3d color array with a kernel.
+///
+/// The kernel is flipped like in a mathematical convolution, which makes no difference for
+/// symmetric kernels, and centered on each pixel, or just after the center for even dimensions.
+/// Pixels past the borders are filled in according to `border`. Every output pixel is summed
+/// directly from the pixels under the kernel, which is fastest for small kernels. Integer values
+/// are rounded and saturate at the bounds of `A`. Panics if the kernel is empty, if the array does
+/// not have 2 or 3 axes, or if it is empty and the border is not constant.
+pub fn convolve2d<A: Primitive, D: Dimension>(
+    image: ArrayView<'_, A, D>,
+    kernel: ArrayView2<'_, f32>,
+    border: BorderMode<A>,
+) -> Array<A, D> {
+    let (kernel_height, kernel_width) = kernel.dim();
+    assert!(
+        kernel_height > 0 && kernel_width > 0,
+        "convolution kernel must not be empty"
+    );
+    let ndim = image.ndim();
+    let image = as_color(image);
+    let (y, x) = (kernel_height / 2, kernel_width / 2);
+    let padded = pad(
+        image,
+        [(kernel_height - 1 - y, y), (kernel_width - 1 - x, x)],
+        border,
+    )
+    .mapv(|v| v.to_f32().unwrap());
+    let flipped = kernel.slice(s![..;-1, ..;-1]);
+    let mut out = Array3::zeros(image.dim());
+    Zip::from(&mut out)
+        .and(padded.windows((kernel_height, kernel_width, 1)))
+        .for_each(|v, window| {
+            let sum: f32 = window.iter().zip(&flipped).map(|(a, b)| a * b).sum();
+            *v = saturate(f64::from(sum));
+        });
+    from_color(out, ndim)
+}
+
+/// Blurs each channel of a 2d gray or 3d color array with a Gaussian of standard deviation
+/// `sigma` pixels.
+///
+/// The kernel is cut off at three standard deviations and applied separably, first along rows
+/// and then along columns, with the values kept as `f32` in between. Borders are reflected
+/// without repeating the edge pixels. Integer values are rounded and saturate at the bounds of
+/// `A`. A `sigma` of zero or less copies the array. Panics if the array does not have 2 or 3 axes.
+pub fn gaussian_blur<A: Primitive, D: Dimension>(
+    image: ArrayView<'_, A, D>,
+    sigma: f32,
+) -> Array<A, D> {
+    let ndim = image.ndim();
+    let image = as_color(image);
+    let (height, width, _) = image.dim();
+    if sigma.is_nan() || sigma <= 0.0 || height == 0 || width == 0 {
+        return from_color(image.to_owned(), ndim);
+    }
+    let kernel = gaussian_kernel(sigma);
+    let radius = kernel.len() / 2;
+    let padded =
+        pad(image, [(radius, radius); 2], BorderMode::Reflect).mapv(|v| v.to_f32().unwrap());
+    let mut rows = Array3::zeros((height + 2 * radius, width, image.dim().2));
+    Zip::from(&mut rows)
+        .and(padded.windows((1, kernel.len(), 1)))
+        .for_each(|v, window| *v = window.iter().zip(&kernel).map(|(a, b)| a * b).sum::<f32>());
+    let mut out = Array3::zeros(image.dim());
+    Zip::from(&mut out)
+        .and(rows.windows((kernel.len(), 1, 1)))
+        .for_each(|v, window| {
+            let sum: f32 = window.iter().zip(&kernel).map(|(a, b)| a * b).sum();
+            *v = saturate(f64::from(sum));
+        });
+    from_color(out, ndim)
+}
+
+/// Gets the normalized weights of a Gaussian of standard deviation `sigma`, cut off after three
+/// standard deviations on both sides.
+pub(crate) fn gaussian_kernel(sigma: f32) -> Vec<f32> {
+    let radius = (3.0 * sigma).ceil() as isize;
+    let mut kernel: Vec<f32> = (-radius..=radius)
+        .map(|x| (-(x * x) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = kernel.iter().sum();
+    kernel.iter_mut().for_each(|w| *w /= total);
+    kernel
+}
+
+/// Computes the horizontal and vertical derivatives of a gray array with the 3x3 Sobel operator.
+///
+/// The derivatives are not normalized, so they are eight times the change per pixel of a linear
+/// ramp, like OpenCV's `Sobel`. Borders are reflected without repeating the edge pixels. `dx`
+/// increases to the right and `dy` increases downwards.
+pub fn sobel<A: Primitive>(image: NdGray<'_, A>) -> (Array2<f32>, Array2<f32>) {
+    gradients(image, [1.0, 2.0, 1.0])
+}
+
+/// Computes the horizontal and vertical derivatives of a gray array with the 3x3 Scharr operator.
+///
+/// This is like [`sobel`], but more accurate for the direction of the gradient. The derivatives
+/// are 32 times the change per pixel of a linear ramp.
+pub fn scharr<A: Primitive>(image: NdGray<'_, A>) -> (Array2<f32>, Array2<f32>) {
+    gradients(image, [3.0, 10.0, 3.0])
+}
+
+/// Gets the length of each gradient from the derivatives of [`sobel`] or [`scharr`].
+///
+/// Panics if the derivatives have different dimensions.
+pub fn gradient_magnitude(dx: NdGray<'_, f32>, dy: NdGray<'_, f32>) -> Array2<f32> {
+    Zip::from(dx).and(dy).map_collect(|dx, dy| dx.hypot(*dy))
+}
+
+/// Gets the direction of each gradient in radians from the derivatives of [`sobel`] or
+/// [`scharr`].
+///
+/// Angles are in `-pi..=pi`, with zero pointing right and positive angles pointing down. Panics if
+/// the derivatives have different dimensions.
+pub fn gradient_orientation(dx: NdGray<'_, f32>, dy: NdGray<'_, f32>) -> Array2<f32> {
+    Zip::from(dx).and(dy).map_collect(|dx, dy| dy.atan2(*dx))
+}
+
+/// Differentiates a gray array along each axis with a central difference, smoothed across it with
+/// the weights `smooth`.
+fn gradients<A: Primitive>(image: NdGray<'_, A>, smooth: [f32; 3]) -> (Array2<f32>, Array2<f32>) {
+    let dim = image.dim();
+    if dim.0 == 0 || dim.1 == 0 {
+        return (Array2::zeros(dim), Array2::zeros(dim));
+    }
+    let padded = pad(image.insert_axis(Axis(2)), [(1, 1); 2], BorderMode::Reflect)
+        .mapv(|v| v.to_f32().unwrap());
+    let padded = padded.index_axis(Axis(2), 0);
+    let mut dx = Array2::zeros(dim);
+    let mut dy = Array2::zeros(dim);
+    Zip::from(&mut dx)
+        .and(&mut dy)
+        .and(padded.windows((3, 3)))
+        .for_each(|dx, dy, w| {
+            *dx = (0..3).map(|i| smooth[i] * (w[[i, 2]] - w[[i, 0]])).sum();
+            *dy = (0..3).map(|i| smooth[i] * (w[[2, i]] - w[[0, i]])).sum();
+        });
+    (dx, dy)
+}
+
+/// Replaces each pixel of a 2d gray or 3d color array with the median of the square window of
+/// `radius` pixels around it, separately for each channel.
+///
+/// Each row keeps a histogram of the window that is updated as it slides right, so the cost per
+/// pixel grows with the radius instead of its square. Borders are filled in by repeating the edge
+/// pixels. Panics if the array does not have 2 or 3 axes.
+pub fn median_filter<D: Dimension>(image: ArrayView<'_, u8, D>, radius: usize) -> Array<u8, D> {
+    let ndim = image.ndim();
+    let image = as_color(image);
+    let (height, width, channels) = image.dim();
+    if radius == 0 || height == 0 || width == 0 {
+        return from_color(image.to_owned(), ndim);
+    }
+    let padded = pad(image, [(radius, radius); 2], BorderMode::Replicate);
+    let size = 2 * radius + 1;
+    // The median is the first value with more than half of the window at or below it.
+    let half = size * size / 2;
+    let mut out = Array3::zeros((height, width, channels));
+    for c in 0..channels {
+        let padded = padded.index_axis(Axis(2), c);
+        for y in 0..height {
+            let window = padded.slice(s![y..y + size, ..]);
+            let mut histogram = [0usize; 256];
+            window
+                .slice(s![.., ..size])
+                .iter()
+                .for_each(|&v| histogram[usize::from(v)] += 1);
+            let (mut median, mut below) = (0, 0);
+            for x in 0..width {
+                if x > 0 {
+                    let removed = window.column(x - 1);
+                    let added = window.column(x + size - 1);
+                    for (&removed, &added) in removed.iter().zip(&added) {
+                        let (removed, added) = (usize::from(removed), usize::from(added));
+                        histogram[removed] -= 1;
+                        histogram[added] += 1;
+                        below -= usize::from(removed < median);
+                        below += usize::from(added < median);
+                    }
+                }
+                while below > half {
+                    median -= 1;
+                    below -= histogram[median];
+                }
+                while below + histogram[median] <= half {
+                    below += histogram[median];
+                    median += 1;
+                }
+                out[[y, x, c]] = median as u8;
+            }
+        }
+    }
+    from_color(out, ndim)
+}
+
+/// Views a `(height, width)` gray or `(height, width, channels)` color array as a 3d array.
+///
+/// Panics if the array does not have 2 or 3 axes.
+pub(crate) fn as_color<'a, A, D: Dimension>(image: ArrayView<'a, A, D>) -> ArrayView3<'a, A> {
+    let image = image.into_dyn();
+    let image = if image.ndim() == 2 {
+        image.insert_axis(Axis(2))
+    } else {
+        image
+    };
+    image
+        .into_dimensionality()
+        .expect("image arrays must have 2 or 3 axes")
+}
+
+/// Turns a 3d array from [`as_color`] back into an array with `ndim` axes.
+pub(crate) fn from_color<A, D: Dimension>(image: Array3<A>, ndim: usize) -> Array<A, D> {
+    let image = image.into_dyn();
+    let image = if ndim == 2 {
+        image.index_axis_move(Axis(2), 0)
+    } else {
+        image
+    };
+    image.into_dimensionality().unwrap()
+}
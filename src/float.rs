@@ -0,0 +1,450 @@
+use crate::limits::Target;
+use crate::ycbcr::{rgb_to_ycbcr_pixel, ycbcr_to_rgb_pixel};
+use crate::{Colors, ConversionError, DecodeLimits, NdColor, NdImageResult, ToImageBuffer};
+use image::io::Reader;
+use image::{DynamicImage, ImageBuffer, ImageFormat, Luma, LumaA, Pixel, Rgb, Rgba};
+use ndarray::{Array3, Axis};
+use std::io::{BufRead, Seek, SeekFrom};
+use std::path::Path;
+
+/// The luma coefficients used by the `image` crate.
+const SRGB_LUMA: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// Opens an image using the `image` crate and loads it into a 3d array of `f32`.
+///
+/// Radiance HDR and floating point TIFF images keep their original values, while all other
+/// images are scaled to the range `0.0..=1.0`.
+pub fn open_image_f32(path: impl AsRef<Path>, colors: Colors) -> NdImageResult<Array3<f32>> {
+    // Other images are loaded into `u16` before they are converted to `f32`.
+    let target = Target::Bytes(6 * colors.channel_count());
+    Ok(match open_float(path, target)? {
+        Opened::Float(image, native) => convert_colors(image, native, colors),
+        Opened::Image(image) => {
+            let image = crate::decode_u16(image, colors);
+            image.mapv(|v| f32::from(v) / f32::from(u16::MAX))
+        }
+    })
+}
+
+/// An image opened by [`open_float`].
+pub(crate) enum Opened {
+    /// The values of a Radiance HDR or floating point TIFF image, stored as the given colors.
+    Float(Array3<f32>, Colors),
+    /// Any other image, decoded by the `image` crate.
+    Image(DynamicImage),
+}
+
+/// Opens an image to load it into `target` within the global limits, reading the values of
+/// floating point images without quantizing them.
+pub(crate) fn open_float(path: impl AsRef<Path>, target: Target) -> NdImageResult<Opened> {
+    let limits = DecodeLimits::global();
+    let reader = Reader::open(path)?.with_guessed_format()?;
+    let format = reader.format();
+    let mut file = reader.into_inner();
+    if let Some((image, native)) = read_float(&mut file, format, limits, target)? {
+        return Ok(Opened::Float(image, native));
+    }
+    file.seek(SeekFrom::Start(0))?;
+    let reader = Reader::new(file).with_guessed_format()?;
+    Ok(Opened::Image(crate::limits::decode(
+        reader, limits, target,
+    )?))
+}
+
+/// Reads the values of a Radiance HDR or floating point TIFF image with the colors they are
+/// stored as, or gets `None` for other images after reading any part of them.
+///
+/// The image is checked against `limits` before its values are read, counting the bytes per pixel
+/// of `target` next to the decoded values.
+#[cfg_attr(not(any(feature = "hdr", feature = "tiff")), allow(unused_variables))]
+pub(crate) fn read_float<R: BufRead + Seek>(
+    reader: &mut R,
+    format: Option<ImageFormat>,
+    limits: DecodeLimits,
+    target: Target,
+) -> NdImageResult<Option<(Array3<f32>, Colors)>> {
+    match format {
+        #[cfg(feature = "hdr")]
+        Some(ImageFormat::Hdr) => Ok(Some((read_hdr(reader, limits, target)?, Colors::Rgb))),
+        #[cfg(feature = "tiff")]
+        Some(ImageFormat::Tiff) => read_float_tiff(reader, limits, target),
+        _ => Ok(None),
+    }
+}
+
+/// Opens an image using the `image` crate and loads it into a 3d array of `f32`, normalizing
+/// each channel with `(value / 255.0 - mean) / std`.
+///
+/// The conversion and normalization are done in a single pass over the pixels. `mean` and `std`
+/// must have one value per channel of `colors`. With the `simd` feature, 4 pixels are normalized
+/// at a time with SSE4.1 when the CPU supports it, giving the same values.
+pub fn open_image_f32_normalized(
+    path: impl AsRef<Path>,
+    colors: Colors,
+    mean: &[f32],
+    std: &[f32],
+) -> NdImageResult<Array3<f32>> {
+    let channels = colors.channel_count();
+    for values in &[mean, std] {
+        if values.len() != channels {
+            return Err(ConversionError::WrongChannelCount {
+                expected: channels,
+                got: values.len(),
+            }
+            .into());
+        }
+    }
+    let image = crate::open_image(path, colors)?;
+    let (height, width, _) = image.dim();
+    let src = crate::into_standard_vec(image);
+    let mut dst = vec![0.0; src.len()];
+    // Each value is scaled and offset with a single multiply and add.
+    let scale: Vec<f32> = std.iter().map(|s| 1.0 / (255.0 * s)).collect();
+    let bias: Vec<f32> = mean.iter().zip(std).map(|(m, s)| -m / s).collect();
+    match channels {
+        1 => normalize::<1>(&src, &mut dst, &scale, &bias),
+        2 => normalize::<2>(&src, &mut dst, &scale, &bias),
+        3 => normalize::<3>(&src, &mut dst, &scale, &bias),
+        _ => normalize::<4>(&src, &mut dst, &scale, &bias),
+    }
+    Ok(Array3::from_shape_vec((height, width, channels), dst).unwrap())
+}
+
+/// Normalizes pixels with `C` channels, which lets the compiler unroll and vectorize the loop.
+fn normalize<const C: usize>(src: &[u8], dst: &mut [f32], scale: &[f32], bias: &[f32]) {
+    let mut scale_c = [0.0; C];
+    let mut bias_c = [0.0; C];
+    scale_c.copy_from_slice(scale);
+    bias_c.copy_from_slice(bias);
+    let done = normalize_simd(src, dst, &scale_c, &bias_c);
+    for (src, dst) in src[done..]
+        .chunks_exact(C)
+        .zip(dst[done..].chunks_exact_mut(C))
+    {
+        for c in 0..C {
+            dst[c] = f32::from(src[c]) * scale_c[c] + bias_c[c];
+        }
+    }
+}
+
+/// Normalizes as many blocks of 4 pixels as possible with SIMD instructions, returning the
+/// number of values that were processed.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+fn normalize_simd<const C: usize>(
+    src: &[u8],
+    dst: &mut [f32],
+    scale: &[f32; C],
+    bias: &[f32; C],
+) -> usize {
+    if is_x86_feature_detected!("sse4.1") {
+        // SAFETY: the CPU was just checked for SSE4.1.
+        unsafe { normalize_sse41(src, dst, scale, bias) }
+    } else {
+        0
+    }
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+fn normalize_simd<const C: usize>(
+    _src: &[u8],
+    _dst: &mut [f32],
+    _scale: &[f32; C],
+    _bias: &[f32; C],
+) -> usize {
+    0
+}
+
+/// Normalizes 4 pixels at a time, as `C` vectors of 4 values.
+///
+/// The channels repeat every `C` values, so each vector of a block has its own lanes of scales
+/// and biases. The values are multiplied and then added like the scalar loop, without fusing, so
+/// both give the same results.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[target_feature(enable = "sse4.1")]
+unsafe fn normalize_sse41<const C: usize>(
+    src: &[u8],
+    dst: &mut [f32],
+    scale: &[f32; C],
+    bias: &[f32; C],
+) -> usize {
+    use std::arch::x86_64::{
+        __m128, _mm_add_ps, _mm_cvtepi32_ps, _mm_cvtepu8_epi32, _mm_cvtsi32_si128, _mm_loadu_ps,
+        _mm_mul_ps, _mm_setzero_ps, _mm_storeu_ps,
+    };
+    use std::convert::TryFrom;
+
+    let mut scale_v = [_mm_setzero_ps(); C];
+    let mut bias_v = [_mm_setzero_ps(); C];
+    let lanes = |values: &[f32; C], k: usize| -> __m128 {
+        let lanes: [f32; 4] = [0, 1, 2, 3].map(|i| values[(4 * k + i) % C]);
+        _mm_loadu_ps(lanes.as_ptr())
+    };
+    for k in 0..C {
+        scale_v[k] = lanes(scale, k);
+        bias_v[k] = lanes(bias, k);
+    }
+    let blocks = src.len().min(dst.len()) / (4 * C);
+    for block in 0..blocks {
+        for k in 0..C {
+            let i = (block * C + k) * 4;
+            let bytes = i32::from_le_bytes(<[u8; 4]>::try_from(&src[i..i + 4]).unwrap());
+            let values = _mm_cvtepi32_ps(_mm_cvtepu8_epi32(_mm_cvtsi32_si128(bytes)));
+            let values = _mm_add_ps(_mm_mul_ps(values, scale_v[k]), bias_v[k]);
+            _mm_storeu_ps(dst[i..i + 4].as_mut_ptr(), values);
+        }
+    }
+    blocks * 4 * C
+}
+
+/// Saves a 3d array of `f32` as an image using the `image` crate.
+///
+/// Radiance HDR and TIFF images are saved with their original values, while all other formats
+/// expect values in the range `0.0..=1.0` and are quantized to the bit depth of the format.
+pub fn save_image_f32(
+    path: impl AsRef<Path>,
+    image: NdColor<'_, f32>,
+    colors: Colors,
+) -> NdImageResult<()> {
+    let path = path.as_ref();
+    let format = ImageFormat::from_path(path)?;
+    // Every format stores its channels in RGB order.
+    let stored = match colors {
+        Colors::Luma => Colors::Luma,
+        Colors::LumaA => Colors::LumaA,
+        Colors::Rgb | Colors::Bgr | Colors::YCbCr => Colors::Rgb,
+        Colors::Rgba | Colors::Bgra => Colors::Rgba,
+    };
+    match format {
+        #[cfg(feature = "hdr")]
+        ImageFormat::Hdr => {
+            let image = convert_colors(image.to_owned(), colors, Colors::Rgb);
+            write_hdr(path, image)?;
+        }
+        #[cfg(feature = "tiff")]
+        ImageFormat::Tiff => {
+            // TIFF has no floating point gray image with alpha.
+            let stored = if stored == Colors::LumaA {
+                Colors::Rgba
+            } else {
+                stored
+            };
+            let image = convert_colors(image.to_owned(), colors, stored);
+            write_float_tiff(path, image, stored)?;
+        }
+        ImageFormat::Png | ImageFormat::Pnm => {
+            let image = convert_colors(image.to_owned(), colors, stored)
+                .mapv(|v| (v.clamp(0.0, 1.0) * f32::from(u16::MAX)).round() as u16);
+            save_stored(path, image, stored)?;
+        }
+        _ => {
+            let image = convert_colors(image.to_owned(), colors, stored)
+                .mapv(|v| (v.clamp(0.0, 1.0) * f32::from(u8::MAX)).round() as u8);
+            save_stored(path, image, stored)?;
+        }
+    }
+    Ok(())
+}
+
+/// Saves an array with colors in RGB order using the `image` crate.
+fn save_stored<A>(path: &Path, image: Array3<A>, colors: Colors) -> NdImageResult<()>
+where
+    A: image::Primitive + 'static,
+    [A]: image::EncodableLayout,
+{
+    fn save<P>(path: &Path, image: Array3<P::Subpixel>) -> NdImageResult<()>
+    where
+        P: Pixel + 'static,
+        [P::Subpixel]: image::EncodableLayout,
+    {
+        let image: ImageBuffer<P, Vec<P::Subpixel>> = image.into_image_buffer()?;
+        image.save(path)?;
+        Ok(())
+    }
+    match colors {
+        Colors::Luma => save::<Luma<A>>(path, image),
+        Colors::LumaA => save::<LumaA<A>>(path, image),
+        Colors::Rgb => save::<Rgb<A>>(path, image),
+        Colors::Rgba => save::<Rgba<A>>(path, image),
+        Colors::Bgr | Colors::Bgra | Colors::YCbCr => {
+            unreachable!("colors must be stored in RGB order")
+        }
+    }
+}
+
+/// Decodes a Radiance HDR image into a 3d array of RGB values.
+#[cfg(feature = "hdr")]
+fn read_hdr(
+    reader: impl std::io::BufRead,
+    limits: DecodeLimits,
+    target: Target,
+) -> NdImageResult<Array3<f32>> {
+    let decoder = image::codecs::hdr::HdrDecoder::new(reader)?;
+    let metadata = decoder.metadata();
+    // The pixels are decoded and then copied into the array, each with three `f32` values.
+    let pixel_bytes = 2 * 12 + target.pixel_bytes();
+    limits.check(metadata.width, metadata.height, pixel_bytes)?;
+    let (width, height) = (metadata.width as usize, metadata.height as usize);
+    let pixels = decoder.read_image_hdr()?;
+    let raw = pixels
+        .iter()
+        .flat_map(|pixel| pixel.0.iter().copied())
+        .collect();
+    Ok(Array3::from_shape_vec((height, width, 3), raw).unwrap())
+}
+
+/// Encodes a 3d array of RGB values as a Radiance HDR image.
+#[cfg(feature = "hdr")]
+fn write_hdr(path: &Path, image: Array3<f32>) -> NdImageResult<()> {
+    use std::fs::File;
+    use std::io::BufWriter;
+
+    let (height, width, _) = image.dim();
+    let pixels: Vec<Rgb<f32>> = image
+        .lanes(Axis(2))
+        .into_iter()
+        .map(|lane| Rgb([lane[0], lane[1], lane[2]]))
+        .collect();
+    let file = BufWriter::new(File::create(path)?);
+    image::codecs::hdr::HdrEncoder::new(file).encode(&pixels, width, height)?;
+    Ok(())
+}
+
+/// Decodes a TIFF image with floating point samples along with its native colors.
+///
+/// Returns `None` if the samples of the TIFF image are not floating point.
+#[cfg(feature = "tiff")]
+fn read_float_tiff(
+    reader: impl std::io::Read + std::io::Seek,
+    limits: DecodeLimits,
+    target: Target,
+) -> NdImageResult<Option<(Array3<f32>, Colors)>> {
+    use image::error::DecodingError;
+    use image::ImageError;
+    use tiff::decoder::{Decoder, DecodingResult};
+    use tiff::tags::Tag;
+    use tiff::ColorType;
+
+    let mut decoder = Decoder::new(reader).map_err(tiff_decoding_error)?;
+    let sample_format = decoder
+        .find_tag_unsigned_vec::<u16>(Tag::SampleFormat)
+        .map_err(tiff_decoding_error)?
+        .unwrap_or_default();
+    // A sample format of 3 means IEEE floating point.
+    if !sample_format.contains(&3) {
+        return Ok(None);
+    }
+    let colors = match decoder.colortype().map_err(tiff_decoding_error)? {
+        ColorType::Gray(_) => Colors::Luma,
+        ColorType::GrayA(_) => Colors::LumaA,
+        ColorType::RGB(_) => Colors::Rgb,
+        ColorType::RGBA(_) => Colors::Rgba,
+        _ => return Ok(None),
+    };
+    let (width, height) = decoder.dimensions().map_err(tiff_decoding_error)?;
+    // The array reuses `f32` samples, while `f64` samples are copied into it.
+    let sample_bytes = match decoder.colortype().map_err(tiff_decoding_error)? {
+        ColorType::Gray(64) | ColorType::GrayA(64) | ColorType::RGB(64) | ColorType::RGBA(64) => 12,
+        _ => 4,
+    };
+    let pixel_bytes = sample_bytes * colors.channel_count() + target.pixel_bytes();
+    limits.check(width, height, pixel_bytes)?;
+    let raw = match decoder.read_image().map_err(tiff_decoding_error)? {
+        DecodingResult::F32(raw) => raw,
+        DecodingResult::F64(raw) => raw.into_iter().map(|v| v as f32).collect(),
+        _ => return Ok(None),
+    };
+    let shape = (height as usize, width as usize, colors.channel_count());
+    let image = Array3::from_shape_vec(shape, raw).map_err(|err| {
+        ImageError::Decoding(DecodingError::new(
+            ImageFormat::Tiff.into(),
+            err.to_string(),
+        ))
+    })?;
+    Ok(Some((image, colors)))
+}
+
+/// Encodes an array with colors in RGB order as a floating point TIFF image.
+#[cfg(feature = "tiff")]
+fn write_float_tiff(path: &Path, image: Array3<f32>, colors: Colors) -> NdImageResult<()> {
+    use std::fs::File;
+    use std::io::BufWriter;
+    use tiff::encoder::{colortype, TiffEncoder};
+
+    let (height, width, _) = image.dim();
+    let (width, height) = (width as u32, height as u32);
+    let raw = crate::into_standard_vec(image);
+    let mut encoder =
+        TiffEncoder::new(BufWriter::new(File::create(path)?)).map_err(tiff_encoding_error)?;
+    match colors {
+        Colors::Luma => encoder.write_image::<colortype::Gray32Float>(width, height, &raw),
+        Colors::Rgb => encoder.write_image::<colortype::RGB32Float>(width, height, &raw),
+        Colors::Rgba => encoder.write_image::<colortype::RGBA32Float>(width, height, &raw),
+        _ => unreachable!("TIFF colors must be gray, RGB, or RGBA"),
+    }
+    .map_err(tiff_encoding_error)?;
+    Ok(())
+}
+
+#[cfg(feature = "tiff")]
+pub(crate) fn tiff_decoding_error(err: tiff::TiffError) -> image::ImageError {
+    use image::error::DecodingError;
+
+    image::ImageError::Decoding(DecodingError::new(ImageFormat::Tiff.into(), err))
+}
+
+#[cfg(feature = "tiff")]
+pub(crate) fn tiff_encoding_error(err: tiff::TiffError) -> image::ImageError {
+    use image::error::EncodingError;
+
+    image::ImageError::Encoding(EncodingError::new(ImageFormat::Tiff.into(), err))
+}
+
+/// Converts an array of `f32` from one set of colors into another.
+///
+/// Missing alpha channels are filled with `1.0` and gray is computed with the same luma
+/// coefficients as the `image` crate.
+pub(crate) fn convert_colors(image: Array3<f32>, from: Colors, to: Colors) -> Array3<f32> {
+    if from == to {
+        return image;
+    }
+    let (height, width, _) = image.dim();
+    let mut converted = Array3::zeros((height, width, to.channel_count()));
+    for (src, mut dst) in image
+        .lanes(Axis(2))
+        .into_iter()
+        .zip(converted.lanes_mut(Axis(2)))
+    {
+        let [r, g, b, a] = match from {
+            Colors::Luma => [src[0], src[0], src[0], 1.0],
+            Colors::LumaA => [src[0], src[0], src[0], src[1]],
+            Colors::Rgb => [src[0], src[1], src[2], 1.0],
+            Colors::Rgba => [src[0], src[1], src[2], src[3]],
+            Colors::Bgr => [src[2], src[1], src[0], 1.0],
+            Colors::Bgra => [src[2], src[1], src[0], src[3]],
+            Colors::YCbCr => {
+                let [r, g, b] = ycbcr_to_rgb_pixel([src[0], src[1], src[2]], 0.5);
+                [r, g, b, 1.0]
+            }
+        };
+        let luma = || {
+            if from == Colors::Luma || from == Colors::LumaA {
+                r
+            } else {
+                SRGB_LUMA[0] * r + SRGB_LUMA[1] * g + SRGB_LUMA[2] * b
+            }
+        };
+        let pixel: &[f32] = match to {
+            Colors::Luma => &[luma()],
+            Colors::LumaA => &[luma(), a],
+            Colors::Rgb => &[r, g, b],
+            Colors::Rgba => &[r, g, b, a],
+            Colors::Bgr => &[b, g, r],
+            Colors::Bgra => &[b, g, r, a],
+            Colors::YCbCr => &rgb_to_ycbcr_pixel([r, g, b], 0.5),
+        };
+        for (d, &s) in dst.iter_mut().zip(pixel) {
+            *d = s;
+        }
+    }
+    converted
+}
@@ -0,0 +1,55 @@
+use crate::num::saturate;
+use crate::{ConversionError, NdColor};
+use image::Primitive;
+use ndarray::{Array2, Axis};
+
+/// The weights of the red, green, and blue channels when collapsing colors to gray.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GrayWeights {
+    /// The luma coefficients of BT.601, as used by OpenCV and JPEG.
+    Rec601,
+    /// The luma coefficients of BT.709 and sRGB, as used by the `image` crate.
+    Rec709,
+    /// The mean of the three channels.
+    Average,
+    /// Custom weights for the red, green, and blue channels.
+    Custom([f32; 3]),
+}
+
+impl GrayWeights {
+    /// The weights of the red, green, and blue channels.
+    pub fn weights(self) -> [f32; 3] {
+        match self {
+            GrayWeights::Rec601 => [0.299, 0.587, 0.114],
+            GrayWeights::Rec709 => [0.2126, 0.7152, 0.0722],
+            GrayWeights::Average => [1.0 / 3.0; 3],
+            GrayWeights::Custom(weights) => weights,
+        }
+    }
+}
+
+/// Collapses a 3d array of RGB or RGBA pixels into a 2d gray array with the given weights.
+///
+/// The first three channels are weighted as red, green, and blue, and any alpha channel is
+/// dropped, so BGR arrays need their weights reversed. Integer results are rounded and
+/// saturated. Fails if the array has fewer than 3 channels.
+pub fn rgb_to_gray<A>(
+    image: NdColor<'_, A>,
+    weights: GrayWeights,
+) -> Result<Array2<A>, ConversionError>
+where
+    A: Primitive,
+{
+    let channels = image.dim().2;
+    if channels < 3 {
+        return Err(ConversionError::WrongChannelCount {
+            expected: 3,
+            got: channels,
+        });
+    }
+    let [r, g, b] = weights.weights();
+    Ok(image.map_axis(Axis(2), |pixel| {
+        let [pr, pg, pb] = [0, 1, 2].map(|c| pixel[c].to_f32().unwrap());
+        saturate(f64::from(r * pr + g * pg + b * pb))
+    }))
+}
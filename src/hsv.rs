@@ -0,0 +1,194 @@
+use crate::{Colors, ConversionError, NdColor, NdImageResult};
+use ndarray::{Array3, Axis, Zip};
+use std::path::Path;
+
+/// Opens an image using the `image` crate and loads it into a 3d array of `u8` HSV pixels.
+///
+/// Like OpenCV, the hue is stored as half its angle in degrees, in `0..180`, while the
+/// saturation and value are in `0..=255`.
+pub fn open_image_hsv(path: impl AsRef<Path>) -> NdImageResult<Array3<u8>> {
+    let image = crate::open_image(path, Colors::Rgb)?;
+    Ok(rgb_to_hsv(image.view())?)
+}
+
+/// Opens an image using the `image` crate and loads it into a 3d array of `f32` HSV pixels.
+///
+/// The hue is in degrees in `0.0..360.0`, while the saturation and value are in `0.0..=1.0`.
+pub fn open_image_hsv_f32(path: impl AsRef<Path>) -> NdImageResult<Array3<f32>> {
+    let image = crate::open_image_f32(path, Colors::Rgb)?;
+    Ok(rgb_to_hsv_f32(image.view())?)
+}
+
+/// Saves a 3d array of `u8` HSV pixels as an image using the `image` crate, converting it to RGB.
+///
+/// The pixels are expected in the ranges of [`open_image_hsv`].
+pub fn save_image_hsv(path: impl AsRef<Path>, image: NdColor<'_, u8>) -> NdImageResult<()> {
+    let image = hsv_to_rgb(image)?;
+    crate::save_image(path, image.view(), Colors::Rgb)
+}
+
+/// Converts a 3d array of `u8` RGB pixels to HSV, with the hue in `0..180`.
+///
+/// Fails if the array does not have 3 channels.
+pub fn rgb_to_hsv(image: NdColor<'_, u8>) -> Result<Array3<u8>, ConversionError> {
+    map_pixels(image, |[r, g, b]| {
+        let [h, s, v] = rgb_to_hsv_pixel([r, g, b].map(|c| f32::from(c) / 255.0));
+        let h = (h / 2.0).round() as u8 % 180;
+        [h, (s * 255.0).round() as u8, (v * 255.0).round() as u8]
+    })
+}
+
+/// Converts a 3d array of `u8` HSV pixels with the hue in `0..180` to RGB.
+///
+/// Fails if the array does not have 3 channels.
+pub fn hsv_to_rgb(image: NdColor<'_, u8>) -> Result<Array3<u8>, ConversionError> {
+    map_pixels(image, |[h, s, v]| {
+        let hsv = [
+            f32::from(h) * 2.0,
+            f32::from(s) / 255.0,
+            f32::from(v) / 255.0,
+        ];
+        hsv_to_rgb_pixel(hsv).map(|c| (c * 255.0).round() as u8)
+    })
+}
+
+/// Converts a 3d array of `f32` RGB pixels in `0.0..=1.0` to HSV, with the hue in degrees.
+///
+/// Fails if the array does not have 3 channels.
+pub fn rgb_to_hsv_f32(image: NdColor<'_, f32>) -> Result<Array3<f32>, ConversionError> {
+    map_pixels(image, rgb_to_hsv_pixel)
+}
+
+/// Converts a 3d array of `f32` HSV pixels, with the hue in degrees, to RGB.
+///
+/// Fails if the array does not have 3 channels.
+pub fn hsv_to_rgb_f32(image: NdColor<'_, f32>) -> Result<Array3<f32>, ConversionError> {
+    map_pixels(image, hsv_to_rgb_pixel)
+}
+
+/// Converts a 3d array of `u8` RGB pixels to HSL, with the hue in `0..180`.
+///
+/// Fails if the array does not have 3 channels.
+pub fn rgb_to_hsl(image: NdColor<'_, u8>) -> Result<Array3<u8>, ConversionError> {
+    map_pixels(image, |[r, g, b]| {
+        let [h, s, l] = rgb_to_hsl_pixel([r, g, b].map(|c| f32::from(c) / 255.0));
+        let h = (h / 2.0).round() as u8 % 180;
+        [h, (s * 255.0).round() as u8, (l * 255.0).round() as u8]
+    })
+}
+
+/// Converts a 3d array of `u8` HSL pixels with the hue in `0..180` to RGB.
+///
+/// Fails if the array does not have 3 channels.
+pub fn hsl_to_rgb(image: NdColor<'_, u8>) -> Result<Array3<u8>, ConversionError> {
+    map_pixels(image, |[h, s, l]| {
+        let hsl = [
+            f32::from(h) * 2.0,
+            f32::from(s) / 255.0,
+            f32::from(l) / 255.0,
+        ];
+        hsl_to_rgb_pixel(hsl).map(|c| (c * 255.0).round() as u8)
+    })
+}
+
+/// Converts a 3d array of `f32` RGB pixels in `0.0..=1.0` to HSL, with the hue in degrees.
+///
+/// Fails if the array does not have 3 channels.
+pub fn rgb_to_hsl_f32(image: NdColor<'_, f32>) -> Result<Array3<f32>, ConversionError> {
+    map_pixels(image, rgb_to_hsl_pixel)
+}
+
+/// Converts a 3d array of `f32` HSL pixels, with the hue in degrees, to RGB.
+///
+/// Fails if the array does not have 3 channels.
+pub fn hsl_to_rgb_f32(image: NdColor<'_, f32>) -> Result<Array3<f32>, ConversionError> {
+    map_pixels(image, hsl_to_rgb_pixel)
+}
+
+/// Maps each 3 channel pixel of an array to a new one, failing if it has another channel count.
+pub(crate) fn map_pixels<A, B>(
+    image: NdColor<'_, A>,
+    f: impl Fn([A; 3]) -> [B; 3],
+) -> Result<Array3<B>, ConversionError>
+where
+    A: Copy,
+    B: Copy + Default,
+{
+    let (height, width, channels) = image.dim();
+    if channels != 3 {
+        return Err(ConversionError::WrongChannelCount {
+            expected: 3,
+            got: channels,
+        });
+    }
+    let mut out = Array3::default((height, width, 3));
+    Zip::from(out.lanes_mut(Axis(2)))
+        .and(image.lanes(Axis(2)))
+        .for_each(|mut out, pixel| {
+            let pixel = f([pixel[0], pixel[1], pixel[2]]);
+            out.iter_mut().zip(pixel).for_each(|(out, c)| *out = c);
+        });
+    Ok(out)
+}
+
+/// Converts an RGB pixel in `0.0..=1.0` to HSV, with the hue in degrees.
+fn rgb_to_hsv_pixel(rgb: [f32; 3]) -> [f32; 3] {
+    let (hue, min, max) = hue_min_max(rgb);
+    let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+    [hue, saturation, max]
+}
+
+/// Converts an HSV pixel, with the hue in degrees, to RGB.
+fn hsv_to_rgb_pixel([h, s, v]: [f32; 3]) -> [f32; 3] {
+    let chroma = v * s;
+    hue_to_rgb(h, chroma).map(|c| c + v - chroma)
+}
+
+/// Converts an RGB pixel in `0.0..=1.0` to HSL, with the hue in degrees.
+fn rgb_to_hsl_pixel(rgb: [f32; 3]) -> [f32; 3] {
+    let (hue, min, max) = hue_min_max(rgb);
+    let lightness = (max + min) / 2.0;
+    let saturation = if max == min {
+        0.0
+    } else {
+        (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+    };
+    [hue, saturation, lightness]
+}
+
+/// Converts an HSL pixel, with the hue in degrees, to RGB.
+fn hsl_to_rgb_pixel([h, s, l]: [f32; 3]) -> [f32; 3] {
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    hue_to_rgb(h, chroma).map(|c| c + l - chroma / 2.0)
+}
+
+/// The hue in degrees and the smallest and largest channels of an RGB pixel.
+fn hue_min_max([r, g, b]: [f32; 3]) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (hue, min, max)
+}
+
+/// The RGB pixel with a hue in degrees and a chroma, before the smallest channel is added.
+fn hue_to_rgb(h: f32, chroma: f32) -> [f32; 3] {
+    let sector = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    match sector as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    }
+}
@@ -0,0 +1,121 @@
+use crate::NdImageResult;
+use image::error::{ImageFormatHint, UnsupportedError, UnsupportedErrorKind};
+use image::{ImageError, ImageFormat};
+use ndarray::Array2;
+use std::path::Path;
+
+/// Opens a paletted PNG or GIF image and loads its palette indices into a `(height, width)`
+/// array, along with its palette as an `(entries, 3)` array of RGB colors.
+///
+/// The indices are kept as they are stored, so they can be used as class labels. Only the first
+/// frame of animated GIF images is loaded, with the area outside of it filled with the
+/// background index.
+pub fn open_indexed_image(path: impl AsRef<Path>) -> NdImageResult<(Array2<u8>, Array2<u8>)> {
+    let data = std::fs::read(path)?;
+    match image::guess_format(&data)? {
+        #[cfg(feature = "png")]
+        ImageFormat::Png => decode_indexed_png(&data),
+        #[cfg(feature = "gif")]
+        ImageFormat::Gif => decode_indexed_gif(&data),
+        format => Err(unsupported(format, "paletted images".to_owned())),
+    }
+}
+
+/// Decodes the palette indices of a PNG image, unpacking indices of less than 8 bits.
+#[cfg(feature = "png")]
+fn decode_indexed_png(data: &[u8]) -> NdImageResult<(Array2<u8>, Array2<u8>)> {
+    use crate::DecodeLimits;
+    use std::io::Cursor;
+
+    let mut decoder = ::png::Decoder::new(Cursor::new(data));
+    decoder.set_transformations(::png::Transformations::IDENTITY);
+    let (info, mut reader) = decoder
+        .read_info()
+        .map_err(|err| decoding_error(ImageFormat::Png, err))?;
+    if info.color_type != ::png::ColorType::Indexed {
+        let feature = format!("{:?} images as paletted images", info.color_type);
+        return Err(unsupported(ImageFormat::Png, feature));
+    }
+    // The packed rows are counted along with the unpacked indices.
+    DecodeLimits::global().check(info.width, info.height, 2)?;
+    let mut raw = vec![0; info.buffer_size()];
+    reader
+        .next_frame(&mut raw)
+        .map_err(|err| decoding_error(ImageFormat::Png, err))?;
+    let (width, height) = (info.width as usize, info.height as usize);
+    let bits = info.bit_depth as usize;
+    let mask = u8::MAX >> (8 - bits);
+    let mut indices = Vec::with_capacity(width * height);
+    for row in raw.chunks_exact(info.line_size) {
+        // Indices are packed from the most significant bit of each byte.
+        indices.extend((0..width).map(|x| {
+            let bit = x * bits;
+            (row[bit / 8] >> (8 - bits - bit % 8)) & mask
+        }));
+    }
+    let indices = Array2::from_shape_vec((height, width), indices).unwrap();
+    let palette = reader.info().palette.clone().unwrap_or_default();
+    Ok((indices, palette_array(palette)))
+}
+
+/// Decodes the palette indices of the first frame of a GIF image onto its canvas.
+#[cfg(feature = "gif")]
+fn decode_indexed_gif(data: &[u8]) -> NdImageResult<(Array2<u8>, Array2<u8>)> {
+    use crate::DecodeLimits;
+    use std::io::Cursor;
+
+    let mut options = ::gif::DecodeOptions::new();
+    options.set_color_output(::gif::ColorOutput::Indexed);
+    let mut decoder = options
+        .read_info(Cursor::new(data))
+        .map_err(|err| decoding_error(ImageFormat::Gif, err))?;
+    let (width, height) = (usize::from(decoder.width()), usize::from(decoder.height()));
+    DecodeLimits::global().check(u32::from(decoder.width()), u32::from(decoder.height()), 1)?;
+    let background = decoder.bg_color().unwrap_or(0) as u8;
+    let global = decoder.global_palette().map(<[u8]>::to_vec);
+    let mut indices = Array2::from_elem((height, width), background);
+    let frame = decoder
+        .read_next_frame()
+        .map_err(|err| decoding_error(ImageFormat::Gif, err))?;
+    let palette = match frame {
+        Some(frame) => {
+            let (left, top) = (usize::from(frame.left), usize::from(frame.top));
+            let frame_width = usize::from(frame.width);
+            for (y, row) in frame.buffer.chunks_exact(frame_width.max(1)).enumerate() {
+                for (x, &index) in row.iter().enumerate() {
+                    if let Some(dst) = indices.get_mut((top + y, left + x)) {
+                        *dst = index;
+                    }
+                }
+            }
+            frame.palette.clone().or(global)
+        }
+        None => global,
+    };
+    Ok((indices, palette_array(palette.unwrap_or_default())))
+}
+
+/// Puts a palette of RGB triples into an `(entries, 3)` array.
+#[cfg(any(feature = "png", feature = "gif"))]
+fn palette_array(mut palette: Vec<u8>) -> Array2<u8> {
+    palette.truncate(palette.len() / 3 * 3);
+    Array2::from_shape_vec((palette.len() / 3, 3), palette).unwrap()
+}
+
+#[cfg(any(feature = "png", feature = "gif"))]
+fn decoding_error(
+    format: ImageFormat,
+    err: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+) -> crate::NdImageError {
+    use image::error::DecodingError;
+
+    ImageError::Decoding(DecodingError::new(format.into(), err)).into()
+}
+
+fn unsupported(format: ImageFormat, feature: String) -> crate::NdImageError {
+    ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+        ImageFormatHint::Exact(format),
+        UnsupportedErrorKind::GenericFeature(feature),
+    ))
+    .into()
+}
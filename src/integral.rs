@@ -0,0 +1,51 @@
+use crate::NdGray;
+use ndarray::Array2;
+use std::ops::Range;
+
+/// Computes the summed-area table of a gray array.
+///
+/// The table has a row and a column more than the array, with the sum of all pixels above and
+/// to the left of `[y, x]` at `[y, x]`, so the first row and column are zero. Use
+/// [`integral_sum`] to get the sum of any window from it in constant time.
+pub fn integral_image<A: Copy + Into<u64>>(image: NdGray<'_, A>) -> Array2<u64> {
+    integral(image, Into::into)
+}
+
+/// Computes the summed-area table of the squares of a gray array.
+///
+/// Together with [`integral_image`], this gives the variance of any window in constant time.
+/// Only `u8` and `u16` arrays are accepted, since the squares of larger values could overflow
+/// the table.
+pub fn integral_image_squared<A: Copy + Into<u16>>(image: NdGray<'_, A>) -> Array2<u64> {
+    integral(image, |v| u64::from(v.into()).pow(2))
+}
+
+/// Sums the pixels in a window of `rows` and `columns` of an array from its summed-area table.
+///
+/// Panics if the window is reversed or not within the array.
+pub fn integral_sum(integral: NdGray<'_, u64>, rows: Range<usize>, columns: Range<usize>) -> u64 {
+    let (top, bottom, left, right) = (rows.start, rows.end, columns.start, columns.end);
+    assert!(
+        top <= bottom && left <= right,
+        "window {:?} x {:?} is reversed",
+        rows,
+        columns
+    );
+    integral[[bottom, right]] + integral[[top, left]]
+        - integral[[top, right]]
+        - integral[[bottom, left]]
+}
+
+/// Computes the summed-area table of the pixels mapped with `map`.
+fn integral<A: Copy>(image: NdGray<'_, A>, map: impl Fn(A) -> u64) -> Array2<u64> {
+    let (height, width) = image.dim();
+    let mut table = Array2::zeros((height + 1, width + 1));
+    for y in 0..height {
+        let mut row = 0;
+        for x in 0..width {
+            row += map(image[[y, x]]);
+            table[[y + 1, x + 1]] = table[[y, x + 1]] + row;
+        }
+    }
+    table
+}
@@ -0,0 +1,51 @@
+use crate::hsv::map_pixels;
+use crate::linear::{decode_srgb, encode_srgb, linear_to_xyz_pixel, xyz_to_linear_pixel};
+use crate::{ConversionError, NdColor};
+use ndarray::Array3;
+
+/// The XYZ coordinates of the D65 white point.
+const WHITE: [f32; 3] = [0.950_47, 1.0, 1.088_83];
+
+/// The `6 / 29` threshold between the cube root and linear parts of the Lab curve.
+const DELTA: f32 = 6.0 / 29.0;
+
+/// Converts a 3d array of `f32` sRGB pixels in `0.0..=1.0` to CIELAB with the D65 white point.
+///
+/// The values are gamma decoded before they are converted, and `L` is in `0.0..=100.0`.
+/// Fails if the array does not have 3 channels.
+pub fn rgb_to_lab(image: NdColor<'_, f32>) -> Result<Array3<f32>, ConversionError> {
+    map_pixels(image, |rgb| {
+        let xyz = linear_to_xyz_pixel(rgb.map(decode_srgb));
+        let [fx, fy, fz] = [0, 1, 2].map(|i| lab_curve(xyz[i] / WHITE[i]));
+        [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+    })
+}
+
+/// Converts a 3d array of `f32` CIELAB pixels with the D65 white point to sRGB.
+///
+/// The values are gamma encoded but not clamped, so colors outside of sRGB can fall outside
+/// `0.0..=1.0`. Fails if the array does not have 3 channels.
+pub fn lab_to_rgb(image: NdColor<'_, f32>) -> Result<Array3<f32>, ConversionError> {
+    map_pixels(image, |[l, a, b]| {
+        let fy = (l + 16.0) / 116.0;
+        let f = [fy + a / 500.0, fy, fy - b / 200.0];
+        let xyz = [0, 1, 2].map(|i| WHITE[i] * inverse_lab_curve(f[i]));
+        xyz_to_linear_pixel(xyz).map(encode_srgb)
+    })
+}
+
+fn lab_curve(t: f32) -> f32 {
+    if t > DELTA * DELTA * DELTA {
+        t.cbrt()
+    } else {
+        t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+    }
+}
+
+fn inverse_lab_curve(t: f32) -> f32 {
+    if t > DELTA {
+        t * t * t
+    } else {
+        3.0 * DELTA * DELTA * (t - 4.0 / 29.0)
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{open_image, save_image, Colors, NdColor, NdImageResult};
+use image::Primitive;
+use ndarray::{Array3, ArrayView, ArrayViewMut3, Ix3};
+use std::path::Path;
+
+/// A 3d array view with the axes in `(channels, height, width)` order.
+///
+/// Like [`NdColor`], this is an alias of `ArrayView3` rather than a type of its own, so it names
+/// the expected order without enforcing it. Both aliases are the same type and can be passed to
+/// each other's functions, so views should be converted with [`hwc_as_chw`] and [`chw_as_hwc`].
+pub type NdColorChw<'a, A = u8> = ArrayView<'a, A, Ix3>;
+
+/// The order of the axes of a batch of images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// `(images, height, width, channels)`
+    Nhwc,
+    /// `(images, channels, height, width)`
+    Nchw,
+}
+
+/// The number of pixels copied at a time when transposing.
+const BLOCK: usize = 256;
+
+/// The number of pixels each thread transposes at a time.
+#[cfg(feature = "rayon")]
+const BAND: usize = 64 * BLOCK;
+
+/// Opens a color image using the `image` crate and loads it into a `(channels, height, width)`
+/// array.
+pub fn open_image_chw(path: impl AsRef<Path>, colors: Colors) -> NdImageResult<Array3<u8>> {
+    let image = open_image(path, colors)?;
+    Ok(to_chw(image.view()))
+}
+
+/// Saves a color image using the `image` crate from a `(channels, height, width)` array.
+pub fn save_image_chw(
+    path: impl AsRef<Path>,
+    image: NdColorChw<'_, u8>,
+    colors: Colors,
+) -> NdImageResult<()> {
+    let image = to_hwc(image);
+    save_image(path, image.view(), colors)
+}
+
+/// Views a `(height, width, channels)` array as `(channels, height, width)` without copying.
+///
+/// The returned view is strided, use [`to_chw`] to get a contiguous array instead.
+pub fn hwc_as_chw<A>(image: NdColor<'_, A>) -> NdColorChw<'_, A> {
+    image.permuted_axes([2, 0, 1])
+}
+
+/// Views a `(channels, height, width)` array as `(height, width, channels)` without copying.
+///
+/// The returned view is strided, use [`to_hwc`] to get a contiguous array instead.
+pub fn chw_as_hwc<A>(image: NdColorChw<'_, A>) -> NdColor<'_, A> {
+    image.permuted_axes([1, 2, 0])
+}
+
+/// Copies a `(height, width, channels)` array into a contiguous `(channels, height, width)` array.
+///
+/// Contiguous arrays are copied in cache sized blocks, and with the `rayon` feature large arrays
+/// are split between threads.
+pub fn to_chw<A: Primitive + Send + Sync>(image: NdColor<'_, A>) -> Array3<A> {
+    let (height, width, channels) = image.dim();
+    let mut chw = Array3::zeros((channels, height, width));
+    copy_to_chw(image, chw.view_mut());
+    chw
+}
+
+/// Copies a `(channels, height, width)` array into a contiguous `(height, width, channels)` array.
+///
+/// Contiguous arrays are copied in cache sized blocks, and with the `rayon` feature large arrays
+/// are split between threads.
+pub fn to_hwc<A: Primitive + Send + Sync>(image: NdColorChw<'_, A>) -> Array3<A> {
+    let (channels, height, width) = image.dim();
+    let mut hwc = Array3::zeros((height, width, channels));
+    copy_to_hwc(image, hwc.view_mut());
+    hwc
+}
+
+/// Copies a `(height, width, channels)` array into a `(channels, height, width)` array.
+pub(crate) fn copy_to_chw<A: Copy + Send + Sync>(
+    image: NdColor<'_, A>,
+    mut chw: ArrayViewMut3<'_, A>,
+) {
+    match (image.to_slice(), chw.as_slice_mut()) {
+        (Some(src), Some(dst)) if !src.is_empty() => deinterleave(src, dst, image.dim().2),
+        // Strided arrays cannot be blocked, so let ndarray gather them.
+        _ => chw.assign(&hwc_as_chw(image)),
+    }
+}
+
+/// Copies a `(channels, height, width)` array into a `(height, width, channels)` array.
+pub(crate) fn copy_to_hwc<A: Copy + Send + Sync>(
+    image: NdColorChw<'_, A>,
+    mut hwc: ArrayViewMut3<'_, A>,
+) {
+    match (image.to_slice(), hwc.as_slice_mut()) {
+        (Some(src), Some(dst)) if !src.is_empty() => interleave(src, dst, image.dim().0),
+        // Strided arrays cannot be blocked, so let ndarray gather them.
+        _ => hwc.assign(&chw_as_hwc(image)),
+    }
+}
+
+/// Splits interleaved pixels in `src` into one plane per channel in `dst`.
+fn deinterleave<A: Copy + Send + Sync>(src: &[A], dst: &mut [A], channels: usize) {
+    let pixels = src.len() / channels;
+    let mut planes: Vec<&mut [A]> = dst.chunks_mut(pixels).collect();
+    #[cfg(feature = "rayon")]
+    {
+        if pixels > BAND {
+            use rayon::prelude::*;
+
+            let mut bands: Vec<Vec<&mut [A]>> = (0..pixels.div_ceil(BAND))
+                .map(|_| Vec::with_capacity(channels))
+                .collect();
+            for plane in planes {
+                for (band, part) in bands.iter_mut().zip(plane.chunks_mut(BAND)) {
+                    band.push(part);
+                }
+            }
+            src.par_chunks(channels * BAND)
+                .zip(bands)
+                .for_each(|(src, mut planes)| deinterleave_band(src, &mut planes));
+            return;
+        }
+    }
+    deinterleave_band(src, &mut planes);
+}
+
+/// Merges the planes of each channel in `src` into interleaved pixels in `dst`.
+fn interleave<A: Copy + Send + Sync>(src: &[A], dst: &mut [A], channels: usize) {
+    let pixels = src.len() / channels;
+    let planes: Vec<&[A]> = src.chunks(pixels).collect();
+    #[cfg(feature = "rayon")]
+    {
+        if pixels > BAND {
+            use rayon::prelude::*;
+
+            dst.par_chunks_mut(channels * BAND)
+                .enumerate()
+                .for_each(|(index, dst)| {
+                    let start = index * BAND;
+                    let planes: Vec<&[A]> = planes
+                        .iter()
+                        .map(|plane| &plane[start..start + dst.len() / channels])
+                        .collect();
+                    interleave_band(&planes, dst);
+                });
+            return;
+        }
+    }
+    interleave_band(&planes, dst);
+}
+
+// The common channel counts are matched so that the block loops are compiled for each of them.
+fn deinterleave_band<A: Copy>(src: &[A], planes: &mut [&mut [A]]) {
+    match planes.len() {
+        1 => planes[0].copy_from_slice(src),
+        2 => deinterleave_blocks(src, planes, 2),
+        3 => deinterleave_blocks(src, planes, 3),
+        4 => deinterleave_blocks(src, planes, 4),
+        channels => deinterleave_blocks(src, planes, channels),
+    }
+}
+
+fn interleave_band<A: Copy>(planes: &[&[A]], dst: &mut [A]) {
+    match planes.len() {
+        1 => dst.copy_from_slice(planes[0]),
+        2 => interleave_blocks(planes, dst, 2),
+        3 => interleave_blocks(planes, dst, 3),
+        4 => interleave_blocks(planes, dst, 4),
+        channels => interleave_blocks(planes, dst, channels),
+    }
+}
+
+/// Copies one channel at a time out of blocks of pixels that fit in the L1 cache.
+#[inline(always)]
+fn deinterleave_blocks<A: Copy>(src: &[A], planes: &mut [&mut [A]], channels: usize) {
+    for (index, block) in src.chunks(channels * BLOCK).enumerate() {
+        let start = index * BLOCK;
+        for (channel, plane) in planes.iter_mut().enumerate() {
+            let plane = &mut plane[start..start + block.len() / channels];
+            for (dst, pixel) in plane.iter_mut().zip(block.chunks_exact(channels)) {
+                *dst = pixel[channel];
+            }
+        }
+    }
+}
+
+/// Copies one channel at a time into blocks of pixels that fit in the L1 cache.
+#[inline(always)]
+fn interleave_blocks<A: Copy>(planes: &[&[A]], dst: &mut [A], channels: usize) {
+    for (index, block) in dst.chunks_mut(channels * BLOCK).enumerate() {
+        let start = index * BLOCK;
+        for (channel, plane) in planes.iter().enumerate() {
+            let plane = &plane[start..start + block.len() / channels];
+            for (pixel, src) in block.chunks_exact_mut(channels).zip(plane) {
+                pixel[channel] = *src;
+            }
+        }
+    }
+}
@@ -1,14 +1,172 @@
+mod alpha;
+mod animation;
+mod batch;
+mod bayer;
+mod channels;
+mod checked;
+mod cmyk;
+mod convert;
+mod depth;
+mod distance;
+mod dynamic;
+mod encode;
+mod error;
+mod exif;
+mod ext;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod filter;
+mod float;
+mod gray;
+mod hsv;
+mod indexed;
+mod integral;
+mod lab;
+mod layout;
+mod limits;
+mod linear;
+mod metadata;
+#[cfg(all(feature = "mmap", unix))]
+mod mmap;
+mod morphology;
+pub mod nshare;
+mod num;
+mod order;
+mod pad;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod pfm;
+mod pixels;
+#[cfg(feature = "png")]
+mod png;
+mod pool;
+mod probe;
+mod progress;
+mod pyramid;
+mod reader;
+mod region;
+mod resize;
+mod reuse;
+mod rotate;
+mod scale;
+mod swizzle;
+#[cfg(feature = "tiff")]
+mod tiff_stack;
+mod tiles;
+mod tonemap;
+mod typed;
+mod undistort;
+mod warp;
+mod ycbcr;
+mod yuv;
+
+pub use alpha::{premultiply_alpha, unpremultiply_alpha};
+pub use animation::open_animation;
+#[cfg(feature = "gif")]
+pub use animation::save_animation;
+pub use batch::{
+    open_image_dir, open_images_batch, save_image_batch, save_image_batch_with_names,
+    save_images_batch,
+};
+pub use bayer::{demosaic, open_raw_bayer, CfaPattern, DemosaicAlgorithm};
+pub use channels::{
+    channel_views, channel_views_mut, merge_channels, reorder_channels, split_channels,
+};
+pub use checked::{
+    try_into_color_array, try_into_gray_array, try_view_color, try_view_color_mut, try_view_gray,
+    try_view_gray_mut,
+};
+#[cfg(feature = "tiff")]
+pub use cmyk::save_tiff_cmyk;
+pub use cmyk::{cmyk_to_rgb, open_image_cmyk, rgb_to_cmyk};
+pub use convert::{IntoNdColor, IntoNdGray, ToImageBuffer};
+pub use depth::{convert_depth, from_f32_unit, to_f32_unit};
+pub use distance::{distance_transform, DistanceMetric};
+pub use dynamic::{open_dynamic_image, save_dynamic_image, NdDynamicImage};
+pub use encode::{
+    encode_image, encode_image_with_pool, save_image_u16_with_options, save_image_with_format,
+    save_image_with_options, write_image, write_image_with_options, PngCompression, PngFilter,
+    SaveOptions,
+};
+pub use error::{ColorsError, ConversionError, NdImageError, NdImageResult};
+pub use ext::{SaveElement, ToImageExt};
+#[cfg(feature = "ffi")]
+pub use ffi::{ndimage_free, ndimage_open, ndimage_save, NdImageBuffer, NdImageStatus};
+pub use filter::{
+    convolve2d, gaussian_blur, gradient_magnitude, gradient_orientation, median_filter, scharr,
+    sobel,
+};
+pub use float::{open_image_f32, open_image_f32_normalized, save_image_f32};
+pub use gray::{rgb_to_gray, GrayWeights};
+pub use hsv::{
+    hsl_to_rgb, hsl_to_rgb_f32, hsv_to_rgb, hsv_to_rgb_f32, open_image_hsv, open_image_hsv_f32,
+    rgb_to_hsl, rgb_to_hsl_f32, rgb_to_hsv, rgb_to_hsv_f32, save_image_hsv,
+};
+pub use indexed::open_indexed_image;
+pub use integral::{integral_image, integral_image_squared, integral_sum};
+pub use lab::{lab_to_rgb, rgb_to_lab};
+pub use layout::{
+    chw_as_hwc, hwc_as_chw, open_image_chw, save_image_chw, to_chw, to_hwc, Layout, NdColorChw,
+};
+pub use limits::{
+    open_gray_image_with_limits, open_image_with_limits, read_image_with_limits, DecodeLimits,
+};
+pub use linear::{
+    linear_to_srgb, open_image_linear, rgb_to_xyz, save_image_linear, srgb_to_linear, xyz_to_rgb,
+};
+pub use metadata::{
+    open_image_with_metadata, save_image_with_metadata, ImageMetadata, SaveMetadata,
+};
+#[cfg(all(feature = "mmap", unix))]
+pub use mmap::{open_image_mmap, MmapImage};
+pub use morphology::{close, dilate, erode, open, tophat};
+pub use order::{into_image_as, view_image_as, ChannelOrder, ColorPixel};
+pub use pad::{pad, BorderMode};
+#[cfg(feature = "rayon")]
+pub use parallel::{open_images_parallel, save_images_parallel};
+pub use pfm::{open_gray_pfm, open_pfm, save_gray_pfm, save_pfm};
+pub use pixels::NdPixels;
+pub use pool::ImageBufferPool;
+pub use probe::{probe_image, ImageHandle, ImageInfo};
+pub use progress::{open_image_with_progress, DecodeProgress};
+pub use pyramid::{build_pyramid, PyramidFilter};
+pub use reader::NdImageReader;
+pub use region::open_image_region;
+pub use resize::{open_image_resized, resize, ResizeFilter};
+pub use reuse::{open_image_into, open_image_with_pool};
+pub use rotate::{flip_h, flip_v, rotate180, rotate270, rotate90};
+pub use scale::{open_image_as, Scale};
+pub use swizzle::swap_rb;
+#[cfg(feature = "tiff")]
+pub use tiff_stack::{open_tiff_stack, open_tiff_stack_u16, save_tiff_stack};
+pub use tiles::{TileStitcher, Tiles};
+pub use tonemap::{tonemap, ToneMapOperator};
+pub use typed::{NdGrayAlpha, NdRgb, NdRgba};
+pub use undistort::{undistort, undistort_maps, CameraIntrinsics, DistortionCoeffs};
+pub use warp::{remap, warp_affine, warp_perspective, Interpolation};
+pub use ycbcr::{rgb_to_ycbcr, rgb_to_ycbcr_f32, ycbcr_to_rgb, ycbcr_to_rgb_f32};
+pub use yuv::{rgb_to_yuv420, yuv420_to_rgb, Yuv420Format, YuvMatrix};
+
+use crate::limits::Target;
 use image::{
-    Bgr, Bgra, ImageBuffer, ImageError, ImageResult, Luma, LumaA, Pixel, Primitive, Rgb, Rgba,
+    Bgr, Bgra, ColorType, DynamicImage, FlatSamples, ImageBuffer, Luma, LumaA, Pixel, Primitive,
+    Rgb, Rgba,
 };
-use ndarray::ShapeBuilder;
-use ndarray::{Array2, Array3, ArrayView, ArrayViewMut, Ix2, Ix3};
-use std::ops::Deref;
+use ndarray::{Array, Array2, Array3, ArrayView, ArrayViewMut, Dimension, Ix2, Ix3};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{BufRead, Seek};
+use std::ops::{Deref, DerefMut};
 use std::path::Path;
+use std::str::FromStr;
 
 /// This newtype struct can wrap an image from either the `ndarray` or `image` crates to
 /// automatically allow them to be turned `into()` the equivalents in the other crate.
 /// This works without copying.
+///
+/// This newtype is deprecated, use the [`IntoNdGray`], [`IntoNdColor`], and [`ToImageBuffer`]
+/// traits instead. It is kept so that existing conversions keep working.
+#[deprecated(note = "use the `IntoNdGray`, `IntoNdColor`, and `ToImageBuffer` traits instead")]
 pub struct NdImage<T>(pub T);
 
 pub type NdGray<'a, A = u8> = ArrayView<'a, A, Ix2>;
@@ -23,6 +181,7 @@ pub type ImgRgba<'a, A = u8> = ImageBuffer<Rgba<A>, &'a [A]>;
 pub type ImgBgr<'a, A = u8> = ImageBuffer<Bgr<A>, &'a [A]>;
 pub type ImgBgra<'a, A = u8> = ImageBuffer<Bgra<A>, &'a [A]>;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Colors {
     Luma,
     LumaA,
@@ -30,352 +189,616 @@ pub enum Colors {
     Rgba,
     Bgr,
     Bgra,
+    /// Full range YCbCr as used by JPEG, with the chroma channels centered on half the range.
+    YCbCr,
+}
+
+impl Colors {
+    /// The number of channels an image with these colors has.
+    pub fn channel_count(self) -> usize {
+        match self {
+            Colors::Luma => 1,
+            Colors::LumaA => 2,
+            Colors::Rgb | Colors::Bgr | Colors::YCbCr => 3,
+            Colors::Rgba | Colors::Bgra => 4,
+        }
+    }
+
+    /// The lowercase name of the colors, as accepted by [`str::parse`].
+    fn name(self) -> &'static str {
+        match self {
+            Colors::Luma => "luma",
+            Colors::LumaA => "lumaa",
+            Colors::Rgb => "rgb",
+            Colors::Rgba => "rgba",
+            Colors::Bgr => "bgr",
+            Colors::Bgra => "bgra",
+            Colors::YCbCr => "ycbcr",
+        }
+    }
+}
+
+impl fmt::Display for Colors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses the colors from their name, ignoring case.
+///
+/// `gray` and `graya` are accepted as well as `luma` and `lumaa`.
+impl FromStr for Colors {
+    type Err = ColorsError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "luma" | "gray" => Colors::Luma,
+            "lumaa" | "graya" => Colors::LumaA,
+            "rgb" => Colors::Rgb,
+            "rgba" => Colors::Rgba,
+            "bgr" => Colors::Bgr,
+            "bgra" => Colors::Bgra,
+            "ycbcr" => Colors::YCbCr,
+            _ => return Err(ColorsError::UnknownName(name.to_owned())),
+        })
+    }
+}
+
+/// The gray or RGB colors of an image with a number of channels.
+impl TryFrom<usize> for Colors {
+    type Error = ColorsError;
+
+    fn try_from(channels: usize) -> Result<Self, Self::Error> {
+        match channels {
+            1 => Ok(Colors::Luma),
+            2 => Ok(Colors::LumaA),
+            3 => Ok(Colors::Rgb),
+            4 => Ok(Colors::Rgba),
+            _ => Err(ColorsError::UnsupportedChannelCount(channels)),
+        }
+    }
 }
 
 /// Opens a gray image using the `image` crate and loads it into a 2d array.
-/// This performs a copy.
-pub fn open_gray_image(path: impl AsRef<Path>) -> ImageResult<Array2<u8>> {
-    let image = image::open(path)?;
-    let image = image.to_luma8();
-    let image: NdGray = NdImage(&image).into();
-    Ok(image.to_owned())
+/// This only performs a copy if the image is not stored as 8-bit gray.
+pub fn open_gray_image(path: impl AsRef<Path>) -> NdImageResult<Array2<u8>> {
+    let image = limits::open(path, Target::Color(ColorType::L8))?;
+    Ok(decode_gray_u8(image))
 }
 
 /// Opens a color image using the `image` crate and loads it into a 3d array.
+/// This only performs a copy if the image is not stored as 8-bit `colors`.
+pub fn open_image(path: impl AsRef<Path>, colors: Colors) -> NdImageResult<Array3<u8>> {
+    let image = limits::open(path, Target::u8(colors))?;
+    Ok(decode_u8(image, colors))
+}
+
+/// Opens a color image using the `image` crate and loads it into a 3d array, rotating and
+/// flipping it upright according to its EXIF orientation.
+///
+/// Only JPEG, PNG, and TIFF images carry an orientation, all other images are loaded as they are.
 /// This performs a copy.
-pub fn open_image(path: impl AsRef<Path>, colors: Colors) -> ImageResult<Array3<u8>> {
-    let image = image::open(path)?;
-    let image = match colors {
-        Colors::Luma => {
-            let image = image.to_luma8();
-            let image: NdColor = NdImage(&image).into();
-            image.to_owned()
-        }
-        Colors::LumaA => {
-            let image = image.to_luma_alpha8();
-            let image: NdColor = NdImage(&image).into();
-            image.to_owned()
-        }
-        Colors::Rgb => {
-            let image = image.to_rgb8();
-            let image: NdColor = NdImage(&image).into();
-            image.to_owned()
-        }
-        Colors::Rgba => {
-            let image = image.to_rgba8();
-            let image: NdColor = NdImage(&image).into();
-            image.to_owned()
-        }
-        Colors::Bgr => {
-            let image = image.to_bgr8();
-            let image: NdColor = NdImage(&image).into();
-            image.to_owned()
-        }
-        Colors::Bgra => {
-            let image = image.to_bgra8();
-            let image: NdColor = NdImage(&image).into();
-            image.to_owned()
-        }
+pub fn open_image_oriented(path: impl AsRef<Path>, colors: Colors) -> NdImageResult<Array3<u8>> {
+    let data = std::fs::read(path)?;
+    let image = limits::read(std::io::Cursor::new(&data), Target::u8(colors))?;
+    let image = match exif::read_exif(&data).and_then(|exif| exif.orientation) {
+        Some(orientation) => exif::apply_orientation(image, orientation),
+        None => image,
     };
-    Ok(image)
+    Ok(decode_u8(image, colors))
 }
 
-/// Saves a gray image using the `image` crate from a 3d array.
-pub fn save_gray_image(path: impl AsRef<Path>, image: NdGray<'_, u8>) -> ImageResult<()> {
-    let image: Option<ImgLuma> = NdImage(image.view()).into();
-    let image = image.ok_or_else(|| {
-        ImageError::Decoding(image::error::DecodingError::new(
-            image::error::ImageFormatHint::Unknown,
-            "non-contiguous ndarray Array",
-        ))
-    })?;
+/// Reads a gray image from a reader using the `image` crate and loads it into a 2d array.
+///
+/// The format of the image is guessed from its contents.
+pub fn read_gray_image(reader: impl BufRead + Seek) -> NdImageResult<Array2<u8>> {
+    let image = limits::read(reader, Target::Color(ColorType::L8))?;
+    Ok(decode_gray_u8(image))
+}
+
+/// Reads a color image from a reader using the `image` crate and loads it into a 3d array.
+///
+/// The format of the image is guessed from its contents.
+pub fn read_image(reader: impl BufRead + Seek, colors: Colors) -> NdImageResult<Array3<u8>> {
+    let image = limits::read(reader, Target::u8(colors))?;
+    Ok(decode_u8(image, colors))
+}
+
+/// Converts a decoded image into a 2d array of `u8`.
+///
+/// Images already stored as 8-bit gray are moved into the array without a copy.
+pub(crate) fn decode_gray_u8(image: DynamicImage) -> Array2<u8> {
+    image.into_luma8().into_nd_gray()
+}
+
+/// Converts a decoded image into a 3d array of `u8`.
+///
+/// Images already stored with the requested colors are moved into the array without a copy.
+pub(crate) fn decode_u8(image: DynamicImage, colors: Colors) -> Array3<u8> {
+    match colors {
+        Colors::Luma => image.into_luma8().into_nd_color(),
+        Colors::LumaA => image.into_luma_alpha8().into_nd_color(),
+        Colors::Rgb => image.into_rgb8().into_nd_color(),
+        Colors::Rgba => image.into_rgba8().into_nd_color(),
+        Colors::Bgr => bgr_from_rgb(image.into_rgb8()),
+        Colors::Bgra => bgr_from_rgb(image.into_rgba8()),
+        Colors::YCbCr => ycbcr_from_rgb(image.into_rgb8()),
+    }
+}
+
+/// Opens a 16-bit gray image using the `image` crate and loads it into a 2d array.
+///
+/// Images with a lower bit depth are scaled up to the full `u16` range.
+pub fn open_gray_image_u16(path: impl AsRef<Path>) -> NdImageResult<Array2<u16>> {
+    let image = limits::open(path, Target::Color(ColorType::L16))?;
+    Ok(image.into_luma16().into_nd_gray())
+}
+
+/// Opens a 16-bit color image using the `image` crate and loads it into a 3d array.
+///
+/// Images with a lower bit depth are scaled up to the full `u16` range.
+pub fn open_image_u16(path: impl AsRef<Path>, colors: Colors) -> NdImageResult<Array3<u16>> {
+    let image = limits::open(path, Target::u16(colors))?;
+    Ok(decode_u16(image, colors))
+}
+
+/// Converts a decoded image into a 3d array of `u16`.
+pub(crate) fn decode_u16(image: DynamicImage, colors: Colors) -> Array3<u16> {
+    match colors {
+        Colors::Luma => image.into_luma16().into_nd_color(),
+        Colors::LumaA => image.into_luma_alpha16().into_nd_color(),
+        Colors::Rgb => image.into_rgb16().into_nd_color(),
+        Colors::Rgba => image.into_rgba16().into_nd_color(),
+        Colors::Bgr => bgr_from_rgb(image.into_rgb16()),
+        Colors::Bgra => bgr_from_rgb(image.into_rgba16()),
+        Colors::YCbCr => ycbcr_from_rgb(image.into_rgb16()),
+    }
+}
+
+/// Moves an RGB(A) image into an array and swaps it into BGR(A) order in place.
+fn bgr_from_rgb<P, A>(image: ImageBuffer<P, Vec<A>>) -> Array3<A>
+where
+    P: Pixel<Subpixel = A> + 'static,
+    A: Primitive + 'static,
+{
+    let mut image = image.into_nd_color();
+    swizzle::swap_rb_owned(&mut image);
+    image
+}
+
+/// Moves an RGB image into an array and converts it to YCbCr in place.
+///
+/// The `image` crate always decodes JPEG images to RGB, so this cannot skip that conversion.
+fn ycbcr_from_rgb<A>(image: ImageBuffer<Rgb<A>, Vec<A>>) -> Array3<A>
+where
+    A: Primitive + 'static,
+{
+    let mut image: Array3<A> = image.into_nd_color();
+    ycbcr::rgb_to_ycbcr_in_place(image.view_mut());
+    image
+}
+
+/// Saves a gray image using the `image` crate from a 2d array.
+///
+/// Arrays that are not contiguous, such as sliced or reversed views, are copied first.
+pub fn save_gray_image(path: impl AsRef<Path>, image: NdGray<'_, u8>) -> NdImageResult<()> {
+    let image = image.as_standard_layout();
+    let image: ImgLuma<'_, _> = image.view().into_image_buffer()?;
     image.save(path)?;
     Ok(())
 }
 
 /// Saves a color image using the `image` crate from a 3d array.
+///
+/// BGR and YCbCr images are saved as RGB, which performs a copy. Arrays that are not contiguous,
+/// such as sliced or reversed views, are also copied first.
 pub fn save_image(
     path: impl AsRef<Path>,
     image: NdColor<'_, u8>,
     colors: Colors,
-) -> ImageResult<()> {
+) -> NdImageResult<()> {
     match colors {
-        Colors::Luma => {
-            let image: Option<ImgLuma> = NdImage(image.view()).into();
-            let image = image.ok_or_else(|| {
-                ImageError::Decoding(image::error::DecodingError::new(
-                    image::error::ImageFormatHint::Unknown,
-                    "non-contiguous ndarray Array",
-                ))
-            })?;
-            image.save(path)?;
-        }
-        Colors::LumaA => {
-            let image: Option<ImgLumaA> = NdImage(image.view()).into();
-            let image = image.ok_or_else(|| {
-                ImageError::Decoding(image::error::DecodingError::new(
-                    image::error::ImageFormatHint::Unknown,
-                    "non-contiguous ndarray Array",
-                ))
-            })?;
-            image.save(path)?;
-        }
-        Colors::Rgb => {
-            let image: Option<ImgRgb> = NdImage(image.view()).into();
-            let image = image.ok_or_else(|| {
-                ImageError::Decoding(image::error::DecodingError::new(
-                    image::error::ImageFormatHint::Unknown,
-                    "non-contiguous ndarray Array",
-                ))
-            })?;
-            image.save(path)?;
-        }
-        Colors::Rgba => {
-            let image: Option<ImgRgba> = NdImage(image.view()).into();
-            let image = image.ok_or_else(|| {
-                ImageError::Decoding(image::error::DecodingError::new(
-                    image::error::ImageFormatHint::Unknown,
-                    "non-contiguous ndarray Array",
-                ))
-            })?;
-            image.save(path)?;
-        }
-        Colors::Bgr => {
-            let image: Option<ImgBgr> = NdImage(image.view()).into();
-            let image = image.ok_or_else(|| {
-                ImageError::Decoding(image::error::DecodingError::new(
-                    image::error::ImageFormatHint::Unknown,
-                    "non-contiguous ndarray Array",
-                ))
-            })?;
-            image.save(path)?;
-        }
-        Colors::Bgra => {
-            let image: Option<ImgBgra> = NdImage(image.view()).into();
-            let image = image.ok_or_else(|| {
-                ImageError::Decoding(image::error::DecodingError::new(
-                    image::error::ImageFormatHint::Unknown,
-                    "non-contiguous ndarray Array",
-                ))
-            })?;
-            image.save(path)?;
-        }
+        Colors::Luma => save_color_image::<Luma<u8>>(path, image),
+        Colors::LumaA => save_color_image::<LumaA<u8>>(path, image),
+        Colors::Rgb => save_color_image::<Rgb<u8>>(path, image),
+        Colors::Rgba => save_color_image::<Rgba<u8>>(path, image),
+        Colors::Bgr => save_color_image::<Rgb<u8>>(path, swizzle::swapped_rb(image, 3)?.view()),
+        Colors::Bgra => save_color_image::<Rgba<u8>>(path, swizzle::swapped_rb(image, 4)?.view()),
+        Colors::YCbCr => save_color_image::<Rgb<u8>>(path, ycbcr::rgb_from_ycbcr(image)?.view()),
     }
+}
+
+/// Saves a color image using the `image` crate from a 3d array, choosing the colors from the
+/// number of channels.
+///
+/// Arrays with 1, 2, 3, or 4 channels are saved as Luma, LumaA, RGB, or RGBA images.
+pub fn save_image_auto(path: impl AsRef<Path>, image: NdColor<'_, u8>) -> NdImageResult<()> {
+    let colors = Colors::try_from(image.dim().2).map_err(|_| ConversionError::BadShape {
+        shape: image.shape().to_vec(),
+    })?;
+    save_image(path, image, colors)
+}
+
+/// Saves a 16-bit gray image using the `image` crate from a 2d array.
+///
+/// The format must support 16-bit images, such as PNG or TIFF.
+pub fn save_gray_image_u16(path: impl AsRef<Path>, image: NdGray<'_, u16>) -> NdImageResult<()> {
+    let image = image.as_standard_layout();
+    let image: ImgLuma<'_, _> = image.view().into_image_buffer()?;
+    image.save(path)?;
+    Ok(())
+}
+
+/// Saves a 16-bit color image using the `image` crate from a 3d array.
+///
+/// The format must support 16-bit images, such as PNG or TIFF. BGR and YCbCr images are saved
+/// as RGB, which performs a copy.
+pub fn save_image_u16(
+    path: impl AsRef<Path>,
+    image: NdColor<'_, u16>,
+    colors: Colors,
+) -> NdImageResult<()> {
+    match colors {
+        Colors::Luma => save_color_image::<Luma<u16>>(path, image),
+        Colors::LumaA => save_color_image::<LumaA<u16>>(path, image),
+        Colors::Rgb => save_color_image::<Rgb<u16>>(path, image),
+        Colors::Rgba => save_color_image::<Rgba<u16>>(path, image),
+        Colors::Bgr => save_color_image::<Rgb<u16>>(path, swizzle::swapped_rb(image, 3)?.view()),
+        Colors::Bgra => save_color_image::<Rgba<u16>>(path, swizzle::swapped_rb(image, 4)?.view()),
+        Colors::YCbCr => save_color_image::<Rgb<u16>>(path, ycbcr::rgb_from_ycbcr(image)?.view()),
+    }
+}
+
+/// Saves a 3d array as an image with the pixel type `P`.
+fn save_color_image<P>(path: impl AsRef<Path>, image: NdColor<'_, P::Subpixel>) -> NdImageResult<()>
+where
+    P: Pixel + 'static,
+    [P::Subpixel]: image::EncodableLayout,
+{
+    let image = image.as_standard_layout();
+    let image: ImageBuffer<P, &[P::Subpixel]> = image.view().into_image_buffer()?;
+    image.save(path)?;
     Ok(())
 }
 
 /// Turn grayscale images into 2d array views.
-impl<'a, C, A: 'static> Into<NdGray<'a, A>> for NdImage<&'a ImageBuffer<Luma<A>, C>>
+#[allow(deprecated)]
+impl<'a, C, A: 'static> From<NdImage<&'a ImageBuffer<Luma<A>, C>>> for NdGray<'a, A>
 where
     A: Primitive,
     C: Deref<Target = [A]> + AsRef<[A]>,
 {
-    fn into(self) -> NdGray<'a, A> {
-        let NdImage(image) = self;
-        let (width, height) = image.dimensions();
-        let (width, height) = (width as usize, height as usize);
-        let slice: &'a [A] = unsafe { std::mem::transmute(image.as_flat_samples().as_slice()) };
-        ArrayView::from_shape((height, width).strides((width, 1)), slice).unwrap()
+    fn from(NdImage(image): NdImage<&'a ImageBuffer<Luma<A>, C>>) -> Self {
+        checked::try_view_gray(image).expect("malformed image buffer")
     }
 }
 
 /// Turn grayscale images into mutable 2d array views.
-impl<'a, C, A: 'static> Into<NdGrayMut<'a, A>> for NdImage<&'a mut ImageBuffer<Luma<A>, C>>
+#[allow(deprecated)]
+impl<'a, C, A: 'static> From<NdImage<&'a mut ImageBuffer<Luma<A>, C>>> for NdGrayMut<'a, A>
 where
     A: Primitive,
-    C: Deref<Target = [A]> + AsRef<[A]>,
+    C: Deref<Target = [A]> + DerefMut + AsRef<[A]>,
 {
-    fn into(self) -> NdGrayMut<'a, A> {
-        let NdImage(image) = self;
-        let (width, height) = image.dimensions();
-        let (width, height) = (width as usize, height as usize);
-        #[allow(clippy::transmute_ptr_to_ref)]
-        let slice: &'a mut [A] =
-            unsafe { std::mem::transmute(image.as_flat_samples().as_slice() as *const [A]) };
-        ArrayViewMut::from_shape((height, width).strides((width, 1)), slice).unwrap()
+    fn from(NdImage(image): NdImage<&'a mut ImageBuffer<Luma<A>, C>>) -> Self {
+        checked::try_view_gray_mut(image).expect("malformed image buffer")
     }
 }
 
 /// Turn arbitrary images into 3d array views with one dimension for the color channel.
-impl<'a, C, P: 'static, A: 'static> Into<NdColor<'a, A>> for NdImage<&'a ImageBuffer<P, C>>
+#[allow(deprecated)]
+impl<'a, C, P: 'static, A: 'static> From<NdImage<&'a ImageBuffer<P, C>>> for NdColor<'a, A>
 where
     A: Primitive,
     P: Pixel<Subpixel = A>,
     C: Deref<Target = [P::Subpixel]> + AsRef<[A]>,
 {
-    fn into(self) -> NdColor<'a, A> {
-        let NdImage(image) = self;
-        let (width, height) = image.dimensions();
-        let (width, height) = (width as usize, height as usize);
-        let channels = P::CHANNEL_COUNT as usize;
-        let slice: &'a [A] = unsafe { std::mem::transmute(image.as_flat_samples().as_slice()) };
-        ArrayView::from_shape(
-            (height, width, channels).strides((width * channels, channels, 1)),
-            slice,
-        )
-        .unwrap()
+    fn from(NdImage(image): NdImage<&'a ImageBuffer<P, C>>) -> Self {
+        checked::try_view_color(image).expect("malformed image buffer")
     }
 }
 
 /// Turn arbitrary images into mutable 3d array views with one dimension for the color channel.
-impl<'a, C, P: 'static, A: 'static> Into<NdColorMut<'a, A>> for NdImage<&'a mut ImageBuffer<P, C>>
+#[allow(deprecated)]
+impl<'a, C, P: 'static, A: 'static> From<NdImage<&'a mut ImageBuffer<P, C>>> for NdColorMut<'a, A>
 where
     A: Primitive,
     P: Pixel<Subpixel = A>,
-    C: Deref<Target = [P::Subpixel]> + AsRef<[A]>,
+    C: Deref<Target = [P::Subpixel]> + DerefMut + AsRef<[A]>,
 {
-    fn into(self) -> NdColorMut<'a, A> {
-        let NdImage(image) = self;
-        let (width, height) = image.dimensions();
-        let (width, height) = (width as usize, height as usize);
-        let channels = P::CHANNEL_COUNT as usize;
-        #[allow(clippy::transmute_ptr_to_ref)]
-        let slice: &'a mut [A] =
-            unsafe { std::mem::transmute(image.as_flat_samples().as_slice() as *const [A]) };
-        ArrayViewMut::from_shape(
-            (height, width, channels).strides((width * channels, channels, 1)),
-            slice,
-        )
-        .unwrap()
+    fn from(NdImage(image): NdImage<&'a mut ImageBuffer<P, C>>) -> Self {
+        checked::try_view_color_mut(image).expect("malformed image buffer")
+    }
+}
+
+/// Turn flat samples into 3d array views with the strides of their layout.
+///
+/// Fails if the layout does not fit in the samples.
+#[allow(deprecated)]
+impl<'a, A> TryFrom<NdImage<FlatSamples<&'a [A]>>> for NdColor<'a, A> {
+    type Error = ConversionError;
+
+    fn try_from(NdImage(flat): NdImage<FlatSamples<&'a [A]>>) -> Result<Self, Self::Error> {
+        ArrayView::from_shape(checked::layout_shape(&flat.layout), flat.samples)
+            .map_err(|_| checked::layout_error(&flat.layout))
+    }
+}
+
+/// Turn mutable flat samples into mutable 3d array views with the strides of their layout.
+///
+/// Fails if the layout does not fit in the samples or has samples that alias each other.
+#[allow(deprecated)]
+impl<'a, A> TryFrom<NdImage<FlatSamples<&'a mut [A]>>> for NdColorMut<'a, A> {
+    type Error = ConversionError;
+
+    fn try_from(NdImage(flat): NdImage<FlatSamples<&'a mut [A]>>) -> Result<Self, Self::Error> {
+        let layout = flat.layout;
+        ArrayViewMut::from_shape(checked::layout_shape(&layout), flat.samples)
+            .map_err(|_| checked::layout_error(&layout))
+    }
+}
+
+/// Turn owned grayscale images into 2d arrays.
+///
+/// This reuses the allocation of the image buffer and does not copy.
+#[allow(deprecated)]
+impl<A: 'static> From<NdImage<ImageBuffer<Luma<A>, Vec<A>>>> for Array2<A>
+where
+    A: Primitive,
+{
+    fn from(NdImage(image): NdImage<ImageBuffer<Luma<A>, Vec<A>>>) -> Self {
+        checked::try_into_gray_array(image).expect("malformed image buffer")
+    }
+}
+
+/// Turn arbitrary owned images into 3d arrays with one dimension for the color channel.
+///
+/// This reuses the allocation of the image buffer and does not copy.
+#[allow(deprecated)]
+impl<P: 'static, A: 'static> From<NdImage<ImageBuffer<P, Vec<A>>>> for Array3<A>
+where
+    A: Primitive,
+    P: Pixel<Subpixel = A>,
+{
+    fn from(NdImage(image): NdImage<ImageBuffer<P, Vec<A>>>) -> Self {
+        checked::try_into_color_array(image).expect("malformed image buffer")
+    }
+}
+
+/// Turn 2d `ArrayView` into a `Luma` image.
+///
+/// Fails if the `ArrayView` is not contiguous.
+#[allow(deprecated)]
+impl<'a, A: 'static> TryFrom<NdImage<NdGray<'a, A>>> for ImgLuma<'a, A>
+where
+    A: Primitive,
+{
+    type Error = ConversionError;
+
+    fn try_from(NdImage(image): NdImage<NdGray<'a, A>>) -> Result<Self, Self::Error> {
+        let (width, height) = image_dimensions(image.shape())?;
+        let slice = image.to_slice().ok_or(ConversionError::NonContiguous)?;
+        Ok(ImageBuffer::from_raw(width, height, slice).expect("failed to create image from slice"))
+    }
+}
+
+/// Turn 3d `ArrayView` into an image of any pixel type.
+///
+/// Fails if the `ArrayView` is not contiguous or has the wrong number of channels.
+#[allow(deprecated)]
+impl<'a, P: 'static, A: 'static> TryFrom<NdImage<NdColor<'a, A>>> for ImageBuffer<P, &'a [A]>
+where
+    A: Primitive,
+    P: Pixel<Subpixel = A>,
+{
+    type Error = ConversionError;
+
+    fn try_from(NdImage(image): NdImage<NdColor<'a, A>>) -> Result<Self, Self::Error> {
+        check_channels::<P>(image.shape())?;
+        let (width, height) = image_dimensions(image.shape())?;
+        let slice = image.to_slice().ok_or(ConversionError::NonContiguous)?;
+        Ok(ImageBuffer::from_raw(width, height, slice).expect("failed to create image from slice"))
     }
 }
 
 /// Turn 2d `ArrayView` into a `Luma` image.
 ///
 /// Can fail if the `ArrayView` is not contiguous.
-impl<'a, A: 'static> Into<Option<ImgLuma<'a, A>>> for NdImage<NdGray<'a, A>>
+///
+/// This conversion is deprecated, use the `TryFrom` conversion instead to find out why it failed.
+#[allow(deprecated)]
+impl<'a, A: 'static> From<NdImage<NdGray<'a, A>>> for Option<ImgLuma<'a, A>>
 where
     A: Primitive,
 {
-    fn into(self) -> Option<ImgLuma<'a, A>> {
-        let NdImage(image) = self;
-        if let [height, width] = *image.shape() {
-            image.to_slice().map(|slice| {
-                ImageBuffer::from_raw(width as u32, height as u32, slice)
-                    .expect("failed to create image from slice")
-            })
-        } else {
-            unreachable!("the ndarray had more than 2 dimensions");
-        }
+    fn from(image: NdImage<NdGray<'a, A>>) -> Self {
+        ImgLuma::try_from(image).ok()
     }
 }
 
 /// Turn 3d `ArrayView` into a `Luma` image.
 ///
 /// Can fail if the `ArrayView` is not contiguous or has the wrong number of channels.
-impl<'a, A: 'static> Into<Option<ImgLuma<'a, A>>> for NdImage<NdColor<'a, A>>
+///
+/// This conversion is deprecated, use the `TryFrom` conversion instead to find out why it failed.
+#[allow(deprecated)]
+impl<'a, A: 'static> From<NdImage<NdColor<'a, A>>> for Option<ImgLuma<'a, A>>
 where
     A: Primitive,
 {
-    fn into(self) -> Option<ImgLuma<'a, A>> {
-        let NdImage(image) = self;
-        if let [height, width, 1] = *image.shape() {
-            image.to_slice().map(|slice| {
-                ImageBuffer::from_raw(width as u32, height as u32, slice)
-                    .expect("failed to create image from raw vec")
-            })
-        } else {
-            None
-        }
+    fn from(image: NdImage<NdColor<'a, A>>) -> Self {
+        ImgLuma::try_from(image).ok()
     }
 }
 
 /// Turn 3d `ArrayView` into a `LumaA` image.
 ///
 /// Can fail if the `ArrayView` is not contiguous or has the wrong number of channels.
-impl<'a, A: 'static> Into<Option<ImgLumaA<'a, A>>> for NdImage<NdColor<'a, A>>
+///
+/// This conversion is deprecated, use the `TryFrom` conversion instead to find out why it failed.
+#[allow(deprecated)]
+impl<'a, A: 'static> From<NdImage<NdColor<'a, A>>> for Option<ImgLumaA<'a, A>>
 where
     A: Primitive,
 {
-    fn into(self) -> Option<ImgLumaA<'a, A>> {
-        let NdImage(image) = self;
-        if let [height, width, 2] = *image.shape() {
-            image.to_slice().map(|slice| {
-                ImageBuffer::from_raw(width as u32, height as u32, slice)
-                    .expect("failed to create image from raw vec")
-            })
-        } else {
-            None
-        }
+    fn from(image: NdImage<NdColor<'a, A>>) -> Self {
+        ImgLumaA::try_from(image).ok()
     }
 }
 
 /// Turn 3d `ArrayView` into a `Rgb` image.
 ///
 /// Can fail if the `ArrayView` is not contiguous or has the wrong number of channels.
-impl<'a, A: 'static> Into<Option<ImgRgb<'a, A>>> for NdImage<NdColor<'a, A>>
+///
+/// This conversion is deprecated, use the `TryFrom` conversion instead to find out why it failed.
+#[allow(deprecated)]
+impl<'a, A: 'static> From<NdImage<NdColor<'a, A>>> for Option<ImgRgb<'a, A>>
 where
     A: Primitive,
 {
-    fn into(self) -> Option<ImgRgb<'a, A>> {
-        let NdImage(image) = self;
-        if let [height, width, 3] = *image.shape() {
-            image.to_slice().map(|slice| {
-                ImageBuffer::from_raw(width as u32, height as u32, slice)
-                    .expect("failed to create image from raw vec")
-            })
-        } else {
-            None
-        }
+    fn from(image: NdImage<NdColor<'a, A>>) -> Self {
+        ImgRgb::try_from(image).ok()
     }
 }
 
 /// Turn 3d `ArrayView` into a `Rgba` image.
 ///
 /// Can fail if the `ArrayView` is not contiguous or has the wrong number of channels.
-impl<'a, A: 'static> Into<Option<ImgRgba<'a, A>>> for NdImage<NdColor<'a, A>>
+///
+/// This conversion is deprecated, use the `TryFrom` conversion instead to find out why it failed.
+#[allow(deprecated)]
+impl<'a, A: 'static> From<NdImage<NdColor<'a, A>>> for Option<ImgRgba<'a, A>>
 where
     A: Primitive,
 {
-    fn into(self) -> Option<ImgRgba<'a, A>> {
-        let NdImage(image) = self;
-        if let [height, width, 4] = *image.shape() {
-            image.to_slice().map(|slice| {
-                ImageBuffer::from_raw(width as u32, height as u32, slice)
-                    .expect("failed to create image from raw vec")
-            })
-        } else {
-            None
-        }
+    fn from(image: NdImage<NdColor<'a, A>>) -> Self {
+        ImgRgba::try_from(image).ok()
     }
 }
 
 /// Turn 3d `ArrayView` into a `Bgr` image.
 ///
 /// Can fail if the `ArrayView` is not contiguous or has the wrong number of channels.
-impl<'a, A: 'static> Into<Option<ImgBgr<'a, A>>> for NdImage<NdColor<'a, A>>
+///
+/// The channels are not swapped, so an array in RGB order is reinterpreted as BGR. Use
+/// [`view_image_as`] to refuse arrays in the other order.
+///
+/// This conversion is deprecated, use the `TryFrom` conversion instead to find out why it failed.
+#[allow(deprecated)]
+impl<'a, A: 'static> From<NdImage<NdColor<'a, A>>> for Option<ImgBgr<'a, A>>
 where
     A: Primitive,
 {
-    fn into(self) -> Option<ImgBgr<'a, A>> {
-        let NdImage(image) = self;
-        if let [height, width, 3] = *image.shape() {
-            image.to_slice().map(|slice| {
-                ImageBuffer::from_raw(width as u32, height as u32, slice)
-                    .expect("failed to create image from raw vec")
-            })
-        } else {
-            None
-        }
+    fn from(image: NdImage<NdColor<'a, A>>) -> Self {
+        ImgBgr::try_from(image).ok()
     }
 }
 
 /// Turn 3d `ArrayView` into a `Bgra` image.
 ///
 /// Can fail if the `ArrayView` is not contiguous or has the wrong number of channels.
-impl<'a, A: 'static> Into<Option<ImgBgra<'a, A>>> for NdImage<NdColor<'a, A>>
+///
+/// The channels are not swapped, so an array in RGB order is reinterpreted as BGR. Use
+/// [`view_image_as`] to refuse arrays in the other order.
+///
+/// This conversion is deprecated, use the `TryFrom` conversion instead to find out why it failed.
+#[allow(deprecated)]
+impl<'a, A: 'static> From<NdImage<NdColor<'a, A>>> for Option<ImgBgra<'a, A>>
 where
     A: Primitive,
 {
-    fn into(self) -> Option<ImgBgra<'a, A>> {
-        let NdImage(image) = self;
-        if let [height, width, 4] = *image.shape() {
-            image.to_slice().map(|slice| {
-                ImageBuffer::from_raw(width as u32, height as u32, slice)
-                    .expect("failed to create image from raw vec")
-            })
-        } else {
-            None
-        }
+    fn from(image: NdImage<NdColor<'a, A>>) -> Self {
+        ImgBgra::try_from(image).ok()
+    }
+}
+
+/// Turn an owned 2d `Array` into a `Luma` image.
+///
+/// This reuses the allocation of the array when it is in standard layout and copies otherwise.
+#[allow(deprecated)]
+impl<A: 'static> From<NdImage<Array2<A>>> for ImageBuffer<Luma<A>, Vec<A>>
+where
+    A: Primitive,
+{
+    fn from(NdImage(image): NdImage<Array2<A>>) -> Self {
+        let (height, width) = image.dim();
+        ImageBuffer::from_raw(width as u32, height as u32, into_standard_vec(image))
+            .expect("failed to create image from raw vec")
+    }
+}
+
+/// Turn an owned 3d `Array` into an image of any pixel type.
+///
+/// This reuses the allocation of the array when it is in standard layout and copies otherwise.
+///
+/// Fails if the `Array` has the wrong number of channels.
+#[allow(deprecated)]
+impl<P: 'static, A: 'static> TryFrom<NdImage<Array3<A>>> for ImageBuffer<P, Vec<A>>
+where
+    A: Primitive,
+    P: Pixel<Subpixel = A>,
+{
+    type Error = ConversionError;
+
+    fn try_from(NdImage(image): NdImage<Array3<A>>) -> Result<Self, Self::Error> {
+        check_channels::<P>(image.shape())?;
+        let (width, height) = image_dimensions(image.shape())?;
+        Ok(
+            ImageBuffer::from_raw(width, height, into_standard_vec(image))
+                .expect("failed to create image from raw vec"),
+        )
+    }
+}
+
+/// Turn an owned 3d `Array` into an image of any pixel type.
+///
+/// Can fail if the `Array` has the wrong number of channels.
+///
+/// This conversion is deprecated, use the `TryFrom` conversion instead to find out why it failed.
+#[allow(deprecated)]
+impl<P: 'static, A: 'static> From<NdImage<Array3<A>>> for Option<ImageBuffer<P, Vec<A>>>
+where
+    A: Primitive,
+    P: Pixel<Subpixel = A>,
+{
+    fn from(image: NdImage<Array3<A>>) -> Self {
+        ImageBuffer::try_from(image).ok()
+    }
+}
+
+/// Gets the `(width, height)` of an image from the shape of an array.
+fn image_dimensions(shape: &[usize]) -> Result<(u32, u32), ConversionError> {
+    let bad_shape = || ConversionError::BadShape {
+        shape: shape.to_vec(),
+    };
+    let width = u32::try_from(shape[1]).map_err(|_| bad_shape())?;
+    let height = u32::try_from(shape[0]).map_err(|_| bad_shape())?;
+    Ok((width, height))
+}
+
+/// Checks that the channel axis of an array matches the channel count of `P`.
+fn check_channels<P: Pixel>(shape: &[usize]) -> Result<(), ConversionError> {
+    let expected = P::CHANNEL_COUNT as usize;
+    match shape[2] {
+        got if got == expected => Ok(()),
+        got => Err(ConversionError::WrongChannelCount { expected, got }),
+    }
+}
+
+/// Extracts the elements of an owned array as a `Vec` in standard (row-major) order.
+///
+/// The array's allocation is reused when it is already in standard layout.
+pub(crate) fn into_standard_vec<A: Clone, D: Dimension>(array: Array<A, D>) -> Vec<A> {
+    if !array.is_standard_layout() {
+        return array.iter().cloned().collect();
     }
+    let len = array.len();
+    let ptr = array.as_ptr();
+    let mut raw = array.into_raw_vec();
+    // The array may have been sliced in place, so its first element is not necessarily at the
+    // start of the allocation.
+    let offset = (ptr as usize - raw.as_ptr() as usize) / std::mem::size_of::<A>();
+    raw.truncate(offset + len);
+    raw.drain(..offset);
+    raw
 }
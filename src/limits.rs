@@ -0,0 +1,301 @@
+use crate::{Colors, NdImageResult};
+use image::error::{ImageFormatHint, LimitError, LimitErrorKind, UnsupportedError};
+use image::io::Reader;
+use image::{ColorType, DynamicImage, ImageDecoder, ImageError};
+use ndarray::{Array2, Array3};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek};
+use std::path::Path;
+use std::sync::{PoisonError, RwLock};
+
+/// The limits applied by every loader that does not take limits of its own.
+static GLOBAL: RwLock<DecodeLimits> = RwLock::new(DecodeLimits::NONE);
+
+/// Limits on the size of images to decode, to defend against untrusted images.
+///
+/// The dimensions of an image are checked before it is decoded, so images exceeding the limits
+/// are rejected without decoding their pixels. Functions that take limits, like
+/// [`open_image_with_limits`] and [`NdImageReader::with_limits`](crate::NdImageReader::with_limits),
+/// apply those, while every other function that opens or decodes images applies the limits set
+/// with [`DecodeLimits::set_global`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// The largest width of an image in pixels.
+    pub max_width: u32,
+    /// The largest height of an image in pixels.
+    pub max_height: u32,
+    /// The most bytes that decoding may allocate for pixels.
+    ///
+    /// This counts the loaded array along with the image decoded by the `image` crate when the
+    /// array cannot reuse its memory, such as when its colors or depth have to be converted.
+    pub max_bytes: usize,
+}
+
+impl Default for DecodeLimits {
+    /// No limits at all.
+    fn default() -> Self {
+        DecodeLimits::NONE
+    }
+}
+
+impl DecodeLimits {
+    /// No limits at all.
+    const NONE: DecodeLimits = DecodeLimits {
+        max_width: u32::MAX,
+        max_height: u32::MAX,
+        max_bytes: usize::MAX,
+    };
+
+    /// Gets the limits applied by every loader that does not take limits of its own.
+    ///
+    /// There are no limits until they are set with [`DecodeLimits::set_global`].
+    pub fn global() -> DecodeLimits {
+        *GLOBAL.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Sets the limits applied by every loader that does not take limits of its own, such as
+    /// [`open_image`](crate::open_image), on all threads.
+    pub fn set_global(limits: DecodeLimits) {
+        *GLOBAL.write().unwrap_or_else(PoisonError::into_inner) = limits;
+    }
+
+    /// Checks the dimensions of an image.
+    pub(crate) fn check_dimensions(&self, width: u32, height: u32) -> NdImageResult<()> {
+        if width > self.max_width || height > self.max_height {
+            return Err(limit_error(LimitErrorKind::DimensionError));
+        }
+        Ok(())
+    }
+
+    /// Checks the number of bytes allocated for pixels, which is `None` if it overflows.
+    pub(crate) fn check_bytes(&self, bytes: Option<u64>) -> NdImageResult<()> {
+        match bytes {
+            Some(bytes) if bytes <= self.max_bytes as u64 => Ok(()),
+            _ => Err(limit_error(LimitErrorKind::InsufficientMemory)),
+        }
+    }
+
+    /// Checks the dimensions of an image loaded with `pixel_bytes` bytes per pixel.
+    pub(crate) fn check(&self, width: u32, height: u32, pixel_bytes: usize) -> NdImageResult<()> {
+        self.check_dimensions(width, height)?;
+        self.check_bytes(pixel_count(width, height).checked_mul(pixel_bytes as u64))
+    }
+
+    /// Checks an image against the limits before `decoder` decodes it to load it into `target`.
+    pub(crate) fn check_decoder<'a>(
+        &self,
+        decoder: &impl ImageDecoder<'a>,
+        target: Target,
+    ) -> NdImageResult<()> {
+        let (width, height) = decoder.dimensions();
+        self.check_dimensions(width, height)?;
+        let decoded = decoder.total_bytes();
+        let loaded = match target {
+            Target::Color(color) if color == decoder.color_type() => Some(0),
+            Target::Decoded => Some(0),
+            Target::Color(color) => {
+                pixel_count(width, height).checked_mul(u64::from(color.bytes_per_pixel()))
+            }
+            Target::Bytes(pixel_bytes) => {
+                pixel_count(width, height).checked_mul(pixel_bytes as u64)
+            }
+        };
+        self.check_bytes(loaded.and_then(|loaded| loaded.checked_add(decoded)))
+    }
+}
+
+/// What a decoded image is loaded into, to count its memory next to the decoded image.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Target {
+    /// The decoded image itself.
+    Decoded,
+    /// An array with pixels of this color type, which reuses the memory of decoded images that
+    /// already have it.
+    Color(ColorType),
+    /// Arrays with this many bytes per pixel in total, which are allocated next to the decoded
+    /// image.
+    Bytes(usize),
+}
+
+impl Target {
+    /// The bytes per pixel of the arrays, not counting the decoded image itself.
+    #[cfg_attr(not(any(feature = "hdr", feature = "tiff")), allow(dead_code))]
+    pub(crate) fn pixel_bytes(self) -> usize {
+        match self {
+            Target::Decoded => 0,
+            Target::Color(color) => usize::from(color.bytes_per_pixel()),
+            Target::Bytes(pixel_bytes) => pixel_bytes,
+        }
+    }
+
+    /// The target of an array of `u8` with the given colors.
+    pub(crate) fn u8(colors: Colors) -> Target {
+        Target::Color(match colors {
+            Colors::Luma => ColorType::L8,
+            Colors::LumaA => ColorType::La8,
+            Colors::Rgb | Colors::Bgr | Colors::YCbCr => ColorType::Rgb8,
+            Colors::Rgba | Colors::Bgra => ColorType::Rgba8,
+        })
+    }
+
+    /// The target of an array of `u16` with the given colors.
+    pub(crate) fn u16(colors: Colors) -> Target {
+        Target::Color(match colors {
+            Colors::Luma => ColorType::L16,
+            Colors::LumaA => ColorType::La16,
+            Colors::Rgb | Colors::Bgr | Colors::YCbCr => ColorType::Rgb16,
+            Colors::Rgba | Colors::Bgra => ColorType::Rgba16,
+        })
+    }
+}
+
+fn pixel_count(width: u32, height: u32) -> u64 {
+    u64::from(width) * u64::from(height)
+}
+
+/// Opens a gray image using the `image` crate and loads it into a 2d array, rejecting images that
+/// exceed `limits`.
+/// This performs a copy.
+pub fn open_gray_image_with_limits(
+    path: impl AsRef<Path>,
+    limits: DecodeLimits,
+) -> NdImageResult<Array2<u8>> {
+    let reader = Reader::new(BufReader::new(File::open(path)?)).with_guessed_format()?;
+    let image = decode(reader, limits, Target::Color(ColorType::L8))?;
+    Ok(crate::decode_gray_u8(image))
+}
+
+/// Opens a color image using the `image` crate and loads it into a 3d array, rejecting images
+/// that exceed `limits`.
+/// This performs a copy.
+pub fn open_image_with_limits(
+    path: impl AsRef<Path>,
+    colors: Colors,
+    limits: DecodeLimits,
+) -> NdImageResult<Array3<u8>> {
+    let file = BufReader::new(File::open(path)?);
+    read_image_with_limits(file, colors, limits)
+}
+
+/// Reads a color image from a reader using the `image` crate and loads it into a 3d array,
+/// rejecting images that exceed `limits`.
+///
+/// The format of the image is guessed from its contents.
+pub fn read_image_with_limits(
+    reader: impl BufRead + Seek,
+    colors: Colors,
+    limits: DecodeLimits,
+) -> NdImageResult<Array3<u8>> {
+    let reader = Reader::new(reader).with_guessed_format()?;
+    let image = decode(reader, limits, Target::u8(colors))?;
+    Ok(crate::decode_u8(image, colors))
+}
+
+/// Opens an image like `image::open`, which takes the format from the extension of the path, and
+/// decodes it within the global limits.
+pub(crate) fn open(path: impl AsRef<Path>, target: Target) -> NdImageResult<DynamicImage> {
+    decode(Reader::open(path)?, DecodeLimits::global(), target)
+}
+
+/// Decodes an image from a reader within the global limits, guessing its format from its
+/// contents.
+pub(crate) fn read(reader: impl BufRead + Seek, target: Target) -> NdImageResult<DynamicImage> {
+    let reader = Reader::new(reader).with_guessed_format()?;
+    decode(reader, DecodeLimits::global(), target)
+}
+
+/// Decodes an image with the decoder for its format after checking it against `limits`.
+// The reader and limits are unused when every format is disabled.
+#[allow(unused_variables, dead_code)]
+pub(crate) fn decode<R: BufRead + Seek>(
+    reader: Reader<R>,
+    limits: DecodeLimits,
+    target: Target,
+) -> NdImageResult<DynamicImage> {
+    fn checked<'a>(
+        decoder: impl ImageDecoder<'a>,
+        limits: DecodeLimits,
+        target: Target,
+    ) -> NdImageResult<DynamicImage> {
+        limits.check_decoder(&decoder, target)?;
+        Ok(DynamicImage::from_decoder(decoder)?)
+    }
+
+    let format = match reader.format() {
+        Some(format) => format,
+        None => return Err(unsupported(ImageFormatHint::Unknown)),
+    };
+    let reader = reader.into_inner();
+    #[allow(unreachable_patterns)]
+    match format {
+        #[cfg(feature = "bmp")]
+        image::ImageFormat::Bmp => {
+            let decoder = image::codecs::bmp::BmpDecoder::new(reader)?;
+            checked(decoder, limits, target)
+        }
+        #[cfg(feature = "dds")]
+        image::ImageFormat::Dds => {
+            let decoder = image::codecs::dds::DdsDecoder::new(reader)?;
+            checked(decoder, limits, target)
+        }
+        #[cfg(feature = "farbfeld")]
+        image::ImageFormat::Farbfeld => {
+            let decoder = image::codecs::farbfeld::FarbfeldDecoder::new(reader)?;
+            checked(decoder, limits, target)
+        }
+        #[cfg(feature = "gif")]
+        image::ImageFormat::Gif => {
+            let decoder = image::codecs::gif::GifDecoder::new(reader)?;
+            checked(decoder, limits, target)
+        }
+        #[cfg(feature = "hdr")]
+        image::ImageFormat::Hdr => {
+            let decoder = image::codecs::hdr::HdrAdapter::new(reader)?;
+            checked(decoder, limits, target)
+        }
+        #[cfg(feature = "ico")]
+        image::ImageFormat::Ico => {
+            let decoder = image::codecs::ico::IcoDecoder::new(reader)?;
+            checked(decoder, limits, target)
+        }
+        #[cfg(feature = "jpeg")]
+        image::ImageFormat::Jpeg => {
+            let decoder = image::codecs::jpeg::JpegDecoder::new(reader)?;
+            checked(decoder, limits, target)
+        }
+        #[cfg(feature = "png")]
+        image::ImageFormat::Png => {
+            let decoder = image::codecs::png::PngDecoder::new(reader)?;
+            checked(decoder, limits, target)
+        }
+        #[cfg(feature = "pnm")]
+        image::ImageFormat::Pnm => {
+            let decoder = image::codecs::pnm::PnmDecoder::new(reader)?;
+            checked(decoder, limits, target)
+        }
+        #[cfg(feature = "tga")]
+        image::ImageFormat::Tga => {
+            let decoder = image::codecs::tga::TgaDecoder::new(reader)?;
+            checked(decoder, limits, target)
+        }
+        #[cfg(feature = "tiff")]
+        image::ImageFormat::Tiff => {
+            let decoder = image::codecs::tiff::TiffDecoder::new(reader)?;
+            checked(decoder, limits, target)
+        }
+        #[cfg(feature = "webp")]
+        image::ImageFormat::WebP => {
+            let decoder = image::codecs::webp::WebPDecoder::new(reader)?;
+            checked(decoder, limits, target)
+        }
+        format => Err(unsupported(ImageFormatHint::Exact(format))),
+    }
+}
+
+fn unsupported(hint: ImageFormatHint) -> crate::NdImageError {
+    ImageError::Unsupported(UnsupportedError::from(hint)).into()
+}
+
+fn limit_error(kind: LimitErrorKind) -> crate::NdImageError {
+    ImageError::Limits(LimitError::from_kind(kind)).into()
+}
@@ -0,0 +1,171 @@
+use crate::hsv::map_pixels;
+use crate::metadata::png_chunks;
+use crate::{Colors, ConversionError, NdColor, NdImageReader, NdImageResult};
+use image::ImageFormat;
+use ndarray::{s, Array3, ArrayViewMut3};
+use std::path::Path;
+
+/// The matrix from linear sRGB to XYZ with the D65 white point.
+const RGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.412_456_4, 0.357_576_1, 0.180_437_5],
+    [0.212_672_9, 0.715_152_2, 0.072_175],
+    [0.019_333_9, 0.119_192, 0.950_304_1],
+];
+
+/// The matrix from XYZ with the D65 white point to linear sRGB.
+const XYZ_TO_RGB: [[f32; 3]; 3] = [
+    [3.240_454_2, -1.537_138_5, -0.498_531_4],
+    [-0.969_266, 1.876_010_8, 0.041_556],
+    [0.055_643_4, -0.204_025_9, 1.057_225_2],
+];
+
+/// The transfer function that the samples of an image file are encoded with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Transfer {
+    Srgb,
+    /// A power law, with the exponent that decodes samples to linear light.
+    Gamma(f32),
+}
+
+impl Transfer {
+    /// Reads the transfer function of an encoded image, which is sRGB unless a PNG image has a
+    /// gamma without an sRGB chunk or ICC profile.
+    pub(crate) fn read(data: &[u8]) -> Transfer {
+        if !data.starts_with(b"\x89PNG") {
+            return Transfer::Srgb;
+        }
+        let mut gamma = None;
+        for (kind, chunk) in png_chunks(data) {
+            match kind {
+                b"sRGB" | b"iCCP" => return Transfer::Srgb,
+                // The gamma is stored times 100000 and encodes linear light.
+                b"gAMA" if chunk.len() == 4 => {
+                    let gamma_int = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+                    if gamma_int != 0 {
+                        gamma = Some(100_000.0 / gamma_int as f32);
+                    }
+                }
+                // Color chunks must come before the image data.
+                b"IDAT" => break,
+                _ => {}
+            }
+        }
+        gamma.map_or(Transfer::Srgb, Transfer::Gamma)
+    }
+
+    /// Decodes every channel of an array, except a trailing alpha channel, to linear light.
+    pub(crate) fn decode(self, image: ArrayViewMut3<'_, f32>) {
+        match self {
+            Transfer::Srgb => map_colors(image, decode_srgb),
+            Transfer::Gamma(exponent) => map_colors(image, |c| c.max(0.0).powf(exponent)),
+        }
+    }
+}
+
+/// Opens an image and loads it into a 3d array of linear light `f32` values.
+///
+/// PNG images with a gamma are decoded with it, while all other images are decoded with the sRGB
+/// transfer function. Radiance HDR and floating point TIFF images already store linear light, so
+/// their values are kept as they are, like [`save_image_linear`] saves them. This is the same as
+/// [`NdImageReader::linear`].
+pub fn open_image_linear(path: impl AsRef<Path>, colors: Colors) -> NdImageResult<Array3<f32>> {
+    NdImageReader::open(path)?
+        .with_colors(colors)
+        .linear(true)
+        .decode3_f32()
+}
+
+/// Saves a 3d array of linear light `f32` values as an image, encoding them with the sRGB
+/// transfer function first.
+///
+/// Radiance HDR and TIFF images store floating point values, so they are saved with the linear
+/// values as they are, like [`save_image_f32`](crate::save_image_f32).
+pub fn save_image_linear(
+    path: impl AsRef<Path>,
+    image: NdColor<'_, f32>,
+    colors: Colors,
+) -> NdImageResult<()> {
+    let path = path.as_ref();
+    match ImageFormat::from_path(path)? {
+        ImageFormat::Hdr | ImageFormat::Tiff => crate::save_image_f32(path, image, colors),
+        _ => crate::save_image_f32(path, linear_to_srgb(image).view(), colors),
+    }
+}
+
+/// Decodes a 3d array of `f32` sRGB values in `0.0..=1.0` to linear light with the sRGB transfer
+/// function.
+///
+/// The last channel of arrays with 2 or 4 channels is treated as alpha and left as it is.
+pub fn srgb_to_linear(image: NdColor<'_, f32>) -> Array3<f32> {
+    let mut image = image.to_owned();
+    map_colors(image.view_mut(), decode_srgb);
+    image
+}
+
+/// Encodes a 3d array of `f32` linear light values in `0.0..=1.0` to sRGB with the sRGB transfer
+/// function.
+///
+/// The last channel of arrays with 2 or 4 channels is treated as alpha and left as it is.
+pub fn linear_to_srgb(image: NdColor<'_, f32>) -> Array3<f32> {
+    let mut image = image.to_owned();
+    map_colors(image.view_mut(), encode_srgb);
+    image
+}
+
+/// Converts a 3d array of `f32` linear sRGB pixels to CIE XYZ with the D65 white point.
+///
+/// Gamma encoded pixels must be decoded with [`srgb_to_linear`] first. Fails if the array does
+/// not have 3 channels.
+pub fn rgb_to_xyz(image: NdColor<'_, f32>) -> Result<Array3<f32>, ConversionError> {
+    map_pixels(image, linear_to_xyz_pixel)
+}
+
+/// Converts a 3d array of `f32` CIE XYZ pixels with the D65 white point to linear sRGB.
+///
+/// The values are not clamped, so colors outside of sRGB can fall outside `0.0..=1.0`. Fails if
+/// the array does not have 3 channels.
+pub fn xyz_to_rgb(image: NdColor<'_, f32>) -> Result<Array3<f32>, ConversionError> {
+    map_pixels(image, xyz_to_linear_pixel)
+}
+
+/// Maps every channel of an array in place, except a trailing alpha channel.
+fn map_colors(mut image: ArrayViewMut3<'_, f32>, f: impl Fn(f32) -> f32) {
+    let channels = image.dim().2;
+    let colors = match channels {
+        2 | 4 => channels - 1,
+        _ => channels,
+    };
+    image.slice_mut(s![.., .., ..colors]).mapv_inplace(f);
+}
+
+/// Decodes an sRGB value to linear light.
+pub(crate) fn decode_srgb(c: f32) -> f32 {
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes a linear light value to sRGB.
+pub(crate) fn encode_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Converts a linear sRGB pixel to XYZ.
+pub(crate) fn linear_to_xyz_pixel(rgb: [f32; 3]) -> [f32; 3] {
+    multiply(&RGB_TO_XYZ, rgb)
+}
+
+/// Converts an XYZ pixel to linear sRGB.
+pub(crate) fn xyz_to_linear_pixel(xyz: [f32; 3]) -> [f32; 3] {
+    multiply(&XYZ_TO_RGB, xyz)
+}
+
+fn multiply(matrix: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    matrix.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
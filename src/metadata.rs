@@ -0,0 +1,306 @@
+use crate::exif::{self, Exif};
+use crate::limits::Target;
+use crate::{Colors, NdColor, NdImageResult};
+use image::{ColorType, ImageFormat};
+use ndarray::Array3;
+use std::io::Cursor;
+use std::path::Path;
+
+/// Metadata read from an image file alongside its pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageMetadata {
+    /// The color type the image was stored with, before any conversion.
+    pub color_type: ColorType,
+    /// The manufacturer of the camera that captured the image.
+    pub camera_make: Option<String>,
+    /// The model of the camera that captured the image.
+    pub camera_model: Option<String>,
+    /// The time the image was captured, formatted as `YYYY:MM:DD HH:MM:SS`.
+    pub capture_time: Option<String>,
+    /// The software that created the image.
+    pub software: Option<String>,
+    /// The copyright notice of the image.
+    pub copyright: Option<String>,
+    /// The EXIF orientation of the image, from 1 to 8.
+    pub orientation: Option<u16>,
+    /// The horizontal and vertical resolution in dots per inch.
+    pub dpi: Option<(f64, f64)>,
+    /// The embedded ICC color profile.
+    pub icc_profile: Option<Vec<u8>>,
+}
+
+/// The most bytes of an ICC profile that fit into one JPEG segment.
+const ICC_CHUNK_LEN: usize = 65519;
+
+/// Metadata to write into an image file when saving it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SaveMetadata {
+    /// The manufacturer of the camera that captured the image.
+    pub camera_make: Option<String>,
+    /// The model of the camera that captured the image.
+    pub camera_model: Option<String>,
+    /// The time the image was created, formatted as `YYYY:MM:DD HH:MM:SS`.
+    pub date_time: Option<String>,
+    /// The software that created the image.
+    pub software: Option<String>,
+    /// The copyright notice of the image.
+    pub copyright: Option<String>,
+    /// The horizontal and vertical resolution in dots per inch.
+    pub dpi: Option<(f64, f64)>,
+    /// The ICC color profile to embed.
+    pub icc_profile: Option<Vec<u8>>,
+}
+
+/// Keeps the metadata of an opened image when saving it again, including its ICC profile.
+impl From<ImageMetadata> for SaveMetadata {
+    fn from(metadata: ImageMetadata) -> Self {
+        SaveMetadata {
+            camera_make: metadata.camera_make,
+            camera_model: metadata.camera_model,
+            date_time: metadata.capture_time,
+            software: metadata.software,
+            copyright: metadata.copyright,
+            dpi: metadata.dpi,
+            icc_profile: metadata.icc_profile,
+        }
+    }
+}
+
+/// Opens a color image using the `image` crate and loads it into a 3d array along with its
+/// metadata.
+///
+/// EXIF fields are read from JPEG, PNG, and TIFF images.
+/// This performs a copy.
+pub fn open_image_with_metadata(
+    path: impl AsRef<Path>,
+    colors: Colors,
+) -> NdImageResult<(Array3<u8>, ImageMetadata)> {
+    let data = std::fs::read(path)?;
+    let image = crate::limits::read(Cursor::new(&data), Target::u8(colors))?;
+    let metadata = read_metadata(&data, image.color());
+    Ok((crate::decode_u8(image, colors), metadata))
+}
+
+/// Saves a color image using the `image` crate from a 3d array along with metadata.
+///
+/// The format is guessed from the extension of the path. Metadata is only written to JPEG and
+/// PNG images, all other formats are saved without it. The ICC profile is only embedded if it is
+/// set, and should describe the colors of `image`.
+/// This performs a copy.
+pub fn save_image_with_metadata(
+    path: impl AsRef<Path>,
+    image: NdColor<'_, u8>,
+    colors: Colors,
+    metadata: &SaveMetadata,
+) -> NdImageResult<()> {
+    let path = path.as_ref();
+    let format = ImageFormat::from_path(path)?;
+    let data = crate::encode_image(image, colors, format)?;
+    let data = match format {
+        ImageFormat::Jpeg => embed_jpeg_metadata(&data, metadata),
+        ImageFormat::Png => embed_png_metadata(&data, metadata),
+        _ => data,
+    };
+    std::fs::write(path, data)?;
+    Ok(())
+}
+
+/// Converts the metadata to save into the EXIF fields that store it.
+fn to_exif(metadata: &SaveMetadata) -> Exif {
+    Exif {
+        make: metadata.camera_make.clone(),
+        model: metadata.camera_model.clone(),
+        x_resolution: metadata.dpi.map(|(x, _)| x),
+        y_resolution: metadata.dpi.map(|(_, y)| y),
+        software: metadata.software.clone(),
+        date_time: metadata.date_time.clone(),
+        copyright: metadata.copyright.clone(),
+        ..Exif::default()
+    }
+}
+
+/// Inserts metadata into an encoded JPEG image.
+fn embed_jpeg_metadata(data: &[u8], metadata: &SaveMetadata) -> Vec<u8> {
+    let mut app1 = b"Exif\0\0".to_vec();
+    app1.extend_from_slice(&to_exif(metadata).to_tiff());
+    let mut embedded = Vec::with_capacity(data.len() + app1.len() + 4);
+    embedded.extend_from_slice(&data[..2]);
+    // The EXIF segment has to follow the JFIF segment, if there is one.
+    let mut rest = &data[2..];
+    if let Some((0xE0, jfif)) = jpeg_segments(data).next() {
+        let (segment, after) = rest.split_at(4 + jfif.len());
+        let mut segment = segment.to_vec();
+        if let Some((x, y)) = metadata.dpi {
+            if jfif.starts_with(b"JFIF\0") && jfif.len() >= 12 {
+                // A unit of 1 means dots per inch.
+                segment[11] = 1;
+                segment[12..14].copy_from_slice(&(x.round() as u16).to_be_bytes());
+                segment[14..16].copy_from_slice(&(y.round() as u16).to_be_bytes());
+            }
+        }
+        embedded.extend_from_slice(&segment);
+        rest = after;
+    }
+    write_jpeg_segment(&mut embedded, 0xE1, &app1);
+    if let Some(profile) = &metadata.icc_profile {
+        let chunks = profile.chunks(ICC_CHUNK_LEN);
+        let count = chunks.len() as u8;
+        for (index, chunk) in chunks.enumerate() {
+            let mut app2 = b"ICC_PROFILE\0".to_vec();
+            app2.extend_from_slice(&[index as u8 + 1, count]);
+            app2.extend_from_slice(chunk);
+            write_jpeg_segment(&mut embedded, 0xE2, &app2);
+        }
+    }
+    embedded.extend_from_slice(rest);
+    embedded
+}
+
+fn write_jpeg_segment(data: &mut Vec<u8>, marker: u8, segment: &[u8]) {
+    data.extend_from_slice(&[0xFF, marker]);
+    data.extend_from_slice(&((segment.len() + 2) as u16).to_be_bytes());
+    data.extend_from_slice(segment);
+}
+
+/// Inserts metadata into an encoded PNG image.
+fn embed_png_metadata(data: &[u8], metadata: &SaveMetadata) -> Vec<u8> {
+    // The signature and the header chunk always come first.
+    let (header, rest) = data.split_at(33);
+    let mut embedded = header.to_vec();
+    if let Some((x, y)) = metadata.dpi {
+        // A unit of 1 means pixels per meter.
+        let mut phys = ((x / 0.0254).round() as u32).to_be_bytes().to_vec();
+        phys.extend_from_slice(&((y / 0.0254).round() as u32).to_be_bytes());
+        phys.push(1);
+        write_png_chunk(&mut embedded, b"pHYs", &phys);
+    }
+    let text = [
+        ("Software", &metadata.software),
+        ("Copyright", &metadata.copyright),
+    ];
+    for (keyword, value) in text.iter() {
+        if let Some(value) = value {
+            let mut chunk = keyword.as_bytes().to_vec();
+            chunk.push(0);
+            chunk.extend_from_slice(value.as_bytes());
+            write_png_chunk(&mut embedded, b"tEXt", &chunk);
+        }
+    }
+    if let Some(profile) = &metadata.icc_profile {
+        let mut iccp = b"ICC Profile\0\0".to_vec();
+        iccp.extend_from_slice(&miniz_oxide::deflate::compress_to_vec_zlib(profile, 6));
+        write_png_chunk(&mut embedded, b"iCCP", &iccp);
+    }
+    write_png_chunk(&mut embedded, b"eXIf", &to_exif(metadata).to_tiff());
+    embedded.extend_from_slice(rest);
+    embedded
+}
+
+fn write_png_chunk(data: &mut Vec<u8>, kind: &[u8; 4], chunk: &[u8]) {
+    data.extend_from_slice(&(chunk.len() as u32).to_be_bytes());
+    data.extend_from_slice(kind);
+    data.extend_from_slice(chunk);
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(chunk);
+    data.extend_from_slice(&crc.finalize().to_be_bytes());
+}
+
+/// Reads the metadata of an encoded image.
+fn read_metadata(data: &[u8], color_type: ColorType) -> ImageMetadata {
+    let exif = exif::read_exif(data).unwrap_or_default();
+    let mut metadata = ImageMetadata {
+        color_type,
+        camera_make: exif.make.clone(),
+        camera_model: exif.model.clone(),
+        capture_time: exif.date_time_original.clone().or(exif.date_time.clone()),
+        software: exif.software.clone(),
+        copyright: exif.copyright.clone(),
+        orientation: exif.orientation,
+        dpi: exif.dpi(),
+        icc_profile: exif.icc_profile.clone(),
+    };
+    if data.starts_with(&[0xFF, 0xD8]) {
+        // ICC profiles are split into numbered chunks to fit into segments.
+        let mut icc_chunks = Vec::new();
+        for (marker, segment) in jpeg_segments(data) {
+            match marker {
+                0xE0 if segment.starts_with(b"JFIF\0") && segment.len() >= 12 => {
+                    let x = f64::from(u16::from_be_bytes([segment[8], segment[9]]));
+                    let y = f64::from(u16::from_be_bytes([segment[10], segment[11]]));
+                    // A unit of 1 means dots per inch and 2 dots per centimeter.
+                    let scale = match segment[7] {
+                        1 => Some(1.0),
+                        2 => Some(2.54),
+                        _ => None,
+                    };
+                    metadata.dpi = metadata.dpi.or(scale.map(|scale| (x * scale, y * scale)));
+                }
+                0xE2 if segment.starts_with(b"ICC_PROFILE\0") && segment.len() >= 14 => {
+                    icc_chunks.push((segment[12], &segment[14..]));
+                }
+                _ => {}
+            }
+        }
+        if !icc_chunks.is_empty() {
+            icc_chunks.sort_by_key(|&(index, _)| index);
+            metadata.icc_profile = Some(
+                icc_chunks
+                    .into_iter()
+                    .flat_map(|(_, c)| c.to_vec())
+                    .collect(),
+            );
+        }
+    } else if data.starts_with(b"\x89PNG") {
+        for (kind, chunk) in png_chunks(data) {
+            match kind {
+                // A unit of 1 means pixels per meter.
+                b"pHYs" if chunk.len() == 9 && chunk[8] == 1 => {
+                    let x = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+                    let y = u32::from_be_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+                    let dpi = (f64::from(x) * 0.0254, f64::from(y) * 0.0254);
+                    metadata.dpi = metadata.dpi.or(Some(dpi));
+                }
+                // The profile follows its name and the compression method.
+                b"iCCP" => {
+                    let name_len = chunk.iter().position(|&b| b == 0).unwrap_or(chunk.len());
+                    metadata.icc_profile = chunk.get(name_len + 2..).and_then(|profile| {
+                        miniz_oxide::inflate::decompress_to_vec_zlib(profile).ok()
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+    metadata
+}
+
+/// Iterates over the markers and contents of the segments of a JPEG image that come before the
+/// image data.
+pub(crate) fn jpeg_segments(data: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    let mut pos = 2;
+    std::iter::from_fn(move || {
+        let header = data.get(pos..pos + 4)?;
+        // The start of scan marks the beginning of the image data.
+        if header[0] != 0xFF || header[1] == 0xDA || header[1] == 0xD9 {
+            return None;
+        }
+        let len = usize::from(u16::from_be_bytes([header[2], header[3]]));
+        let segment = data.get(pos + 4..pos + 2 + len)?;
+        pos += 2 + len;
+        Some((header[1], segment))
+    })
+}
+
+/// Iterates over the types and contents of the chunks of a PNG image.
+pub(crate) fn png_chunks(data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut pos = 8;
+    std::iter::from_fn(move || {
+        let header = data.get(pos..pos + 8)?;
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let chunk = data.get(pos + 8..pos + 8 + len)?;
+        // Each chunk ends with a checksum.
+        pos += 12 + len;
+        Some((&header[4..], chunk))
+    })
+}
@@ -0,0 +1,278 @@
+use crate::exif::Ifd;
+use crate::{Colors, NdImageResult};
+use image::error::{ImageFormatHint, UnsupportedError, UnsupportedErrorKind};
+use image::ImageError;
+use ndarray::{ArrayView3, Axis, ShapeBuilder};
+use std::convert::TryFrom;
+use std::fs::File;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+/// An uncompressed image file mapped into memory, which owns the mapping of its pixels.
+pub struct MmapImage {
+    ptr: *mut libc::c_void,
+    len: usize,
+    layout: PixelLayout,
+}
+
+/// Where the pixels of a mapped image are and how they are ordered.
+#[derive(Debug, Clone, Copy)]
+struct PixelLayout {
+    offset: usize,
+    height: usize,
+    width: usize,
+    colors: Colors,
+    row_stride: usize,
+    /// Whether the rows are stored from the bottom of the image to the top.
+    bottom_up: bool,
+}
+
+// The mapping is read only and is not tied to the thread that created it.
+unsafe impl Send for MmapImage {}
+unsafe impl Sync for MmapImage {}
+
+impl MmapImage {
+    /// Gets a view of the pixels of the image, which are read directly from the mapped file.
+    pub fn view(&self) -> ArrayView3<'_, u8> {
+        let layout = self.layout;
+        let channels = layout.colors.channel_count();
+        let shape =
+            (layout.height, layout.width, channels).strides((layout.row_stride, channels, 1));
+        let data = unsafe { (self.ptr as *const u8).add(layout.offset) };
+        let mut view = unsafe { ArrayView3::from_shape_ptr(shape, data) };
+        if layout.bottom_up {
+            view.invert_axis(Axis(0));
+        }
+        view
+    }
+
+    /// Gets the colors the pixels of the image are stored with.
+    pub fn colors(&self) -> Colors {
+        self.layout.colors
+    }
+}
+
+impl Drop for MmapImage {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr, self.len);
+        }
+    }
+}
+
+/// Memory maps an uncompressed image file, giving a view of its pixels without decoding them.
+///
+/// Binary PGM and PPM images with 8-bit samples, 24 and 32-bit uncompressed BMP images, and
+/// uncompressed 8-bit TIFF images with contiguous strips are supported. BMP images are stored in
+/// BGR order.
+///
+/// # Safety
+///
+/// The file must not be modified or truncated while it is mapped, since the pixels are read from
+/// it without being copied, and changes to it would be visible through views that are assumed to
+/// be immutable.
+pub unsafe fn open_image_mmap(path: impl AsRef<Path>) -> NdImageResult<MmapImage> {
+    let file = File::open(path)?;
+    let len = file.metadata()?.len() as usize;
+    if len == 0 {
+        return Err(unsupported("memory mapping of empty files"));
+    }
+    let ptr = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ,
+            libc::MAP_PRIVATE,
+            file.as_raw_fd(),
+            0,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    // The mapping is unmapped if the layout cannot be found.
+    let mut image = MmapImage {
+        ptr,
+        len,
+        layout: PixelLayout {
+            offset: 0,
+            height: 0,
+            width: 0,
+            colors: Colors::Luma,
+            row_stride: 0,
+            bottom_up: false,
+        },
+    };
+    let data = unsafe { std::slice::from_raw_parts(ptr as *const u8, len) };
+    let layout = if data.starts_with(b"P5") || data.starts_with(b"P6") {
+        pnm_layout(data)
+    } else if data.starts_with(b"BM") {
+        bmp_layout(data)
+    } else if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        tiff_layout(data)
+    } else {
+        None
+    }
+    .ok_or_else(|| unsupported("memory mapping of compressed or unknown images"))?
+    .ok_or_else(truncated)?;
+    let channels = layout.colors.channel_count();
+    let end = layout
+        .row_stride
+        .checked_mul(layout.height.saturating_sub(1))
+        .and_then(|rows| rows.checked_add(layout.width.checked_mul(channels)?))
+        .and_then(|end| end.checked_add(layout.offset));
+    if !matches!(end, Some(end) if end <= len) {
+        return Err(truncated());
+    }
+    image.layout = layout;
+    Ok(image)
+}
+
+/// Finds the pixels of a binary PGM or PPM image.
+///
+/// Like the other layouts, this is `None` for unsupported images and `Some(None)` if the sizes in
+/// the header overflow.
+fn pnm_layout(data: &[u8]) -> Option<Option<PixelLayout>> {
+    let colors = if data[1] == b'5' {
+        Colors::Luma
+    } else {
+        Colors::Rgb
+    };
+    let mut pos = 2;
+    let mut fields = [0usize; 3];
+    for field in fields.iter_mut() {
+        // Skip whitespace and comments before each field.
+        loop {
+            match data.get(pos)? {
+                b'#' => pos += data[pos..].iter().position(|&b| b == b'\n')?,
+                b if b.is_ascii_whitespace() => pos += 1,
+                _ => break,
+            }
+        }
+        let len = data[pos..].iter().position(|b| !b.is_ascii_digit())?;
+        *field = std::str::from_utf8(&data[pos..pos + len])
+            .ok()?
+            .parse()
+            .ok()?;
+        pos += len;
+    }
+    let [width, height, max_value] = fields;
+    if max_value > 255 {
+        return None;
+    }
+    let row_stride = width.checked_mul(colors.channel_count());
+    Some(row_stride.map(|row_stride| PixelLayout {
+        // A single whitespace byte separates the header from the pixels.
+        offset: pos + 1,
+        height,
+        width,
+        colors,
+        row_stride,
+        bottom_up: false,
+    }))
+}
+
+/// Finds the pixels of an uncompressed 24 or 32-bit BMP image.
+fn bmp_layout(data: &[u8]) -> Option<Option<PixelLayout>> {
+    let u32_at = |pos: usize| {
+        let bytes = data.get(pos..pos + 4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+    let offset = u32_at(10)? as usize;
+    let width = u32_at(18)? as i32;
+    let height = u32_at(22)? as i32;
+    let bits = u32_at(28)? & 0xFFFF;
+    let compression = u32_at(30)?;
+    let colors = match (bits, compression) {
+        (24, 0) => Colors::Bgr,
+        (32, 0) => Colors::Bgra,
+        _ => return None,
+    };
+    let width = usize::try_from(width).ok()?;
+    // Rows are padded to four bytes.
+    let row_stride = width
+        .checked_mul(bits as usize)
+        .and_then(|bits| bits.div_ceil(32).checked_mul(4));
+    Some(row_stride.map(|row_stride| PixelLayout {
+        offset,
+        height: height.unsigned_abs() as usize,
+        width,
+        colors,
+        row_stride,
+        // A negative height means the rows are stored from the top.
+        bottom_up: height > 0,
+    }))
+}
+
+/// Finds the pixels of an uncompressed 8-bit TIFF image whose strips are stored one after
+/// another.
+fn tiff_layout(data: &[u8]) -> Option<Option<PixelLayout>> {
+    let ifd = Ifd::first(data)?;
+    let mut width = None;
+    let mut height = None;
+    let mut bits = vec![1];
+    let mut compression = 1;
+    let mut photometric = None;
+    let mut offsets = None;
+    let mut samples = 1;
+    let mut byte_counts = None;
+    let mut planar = 1;
+    for entry in ifd.entries() {
+        let values = entry.unsigned();
+        let first = values.as_ref().and_then(|values| values.first().copied());
+        match entry.tag {
+            256 => width = first,
+            257 => height = first,
+            258 => bits = values?,
+            259 => compression = first?,
+            262 => photometric = first,
+            273 => offsets = values,
+            277 => samples = first?,
+            279 => byte_counts = values,
+            284 => planar = first?,
+            _ => {}
+        }
+    }
+    let (width, height) = (width? as usize, height? as usize);
+    let (offsets, byte_counts) = (offsets?, byte_counts?);
+    // Only 8-bit, uncompressed, interleaved gray or RGB samples can be viewed.
+    if compression != 1 || planar != 1 || bits.iter().any(|&b| b != 8) {
+        return None;
+    }
+    let colors = match (photometric?, samples) {
+        (1, 1) => Colors::Luma,
+        (1, 2) => Colors::LumaA,
+        (2, 3) => Colors::Rgb,
+        (2, 4) => Colors::Rgba,
+        _ => return None,
+    };
+    let contiguous = offsets
+        .windows(2)
+        .zip(&byte_counts)
+        .all(|(pair, &count)| pair[0].checked_add(count) == Some(pair[1]));
+    if !contiguous || offsets.len() != byte_counts.len() {
+        return None;
+    }
+    let offset = *offsets.first()? as usize;
+    let row_stride = width.checked_mul(colors.channel_count());
+    Some(row_stride.map(|row_stride| PixelLayout {
+        offset,
+        height,
+        width,
+        colors,
+        row_stride,
+        bottom_up: false,
+    }))
+}
+
+fn truncated() -> crate::NdImageError {
+    unsupported("memory mapping of truncated images")
+}
+
+fn unsupported(feature: &str) -> crate::NdImageError {
+    ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+        ImageFormatHint::Unknown,
+        UnsupportedErrorKind::GenericFeature(feature.to_owned()),
+    ))
+    .into()
+}
@@ -0,0 +1,114 @@
+use crate::filter::{as_color, from_color};
+use image::Primitive;
+use ndarray::{Array, Array3, ArrayView, ArrayView2, ArrayView3, Dimension};
+
+/// Erodes each channel of a 2d gray or 3d color array, taking the minimum of the pixels under
+/// the nonzero entries of the structuring element `element`.
+///
+/// The element is centered on each pixel, or just after the center for even dimensions, and
+/// pixels past the borders are ignored. Binary masks work the same as grayscale arrays, as long
+/// as the foreground is brighter than the background. Panics if the array does not have 2 or 3
+/// axes.
+pub fn erode<A: Primitive, D: Dimension>(
+    image: ArrayView<'_, A, D>,
+    element: ArrayView2<'_, u8>,
+) -> Array<A, D> {
+    let ndim = image.ndim();
+    let offsets = offsets(element, 1);
+    let out = extremum(as_color(image), &offsets, A::max_value(), |a, b| {
+        if b < a {
+            b
+        } else {
+            a
+        }
+    });
+    from_color(out, ndim)
+}
+
+/// Dilates each channel of a 2d gray or 3d color array, taking the maximum of the pixels under
+/// the nonzero entries of the structuring element `element`.
+///
+/// The element is mirrored through its center like in the mathematical definition, so that
+/// [`open`] and [`close`] work with asymmetric elements. Pixels past the borders are ignored.
+/// Panics if the array does not have 2 or 3 axes.
+pub fn dilate<A: Primitive, D: Dimension>(
+    image: ArrayView<'_, A, D>,
+    element: ArrayView2<'_, u8>,
+) -> Array<A, D> {
+    let ndim = image.ndim();
+    let offsets = offsets(element, -1);
+    let out = extremum(as_color(image), &offsets, A::min_value(), |a, b| {
+        if b > a {
+            b
+        } else {
+            a
+        }
+    });
+    from_color(out, ndim)
+}
+
+/// Erodes and then dilates an array, which removes bright details smaller than `element`.
+pub fn open<A: Primitive, D: Dimension>(
+    image: ArrayView<'_, A, D>,
+    element: ArrayView2<'_, u8>,
+) -> Array<A, D> {
+    dilate(erode(image, element).view(), element)
+}
+
+/// Dilates and then erodes an array, which fills dark details smaller than `element`.
+pub fn close<A: Primitive, D: Dimension>(
+    image: ArrayView<'_, A, D>,
+    element: ArrayView2<'_, u8>,
+) -> Array<A, D> {
+    erode(dilate(image, element).view(), element)
+}
+
+/// Subtracts the [`open`]ing of an array from it, which keeps only the bright details smaller
+/// than `element`.
+pub fn tophat<A: Primitive, D: Dimension>(
+    image: ArrayView<'_, A, D>,
+    element: ArrayView2<'_, u8>,
+) -> Array<A, D> {
+    // An opening is never brighter than the array, so this cannot underflow.
+    let opened = open(image.view(), element);
+    &image - &opened
+}
+
+/// Gets the `(row, column)` offsets of the nonzero entries of a structuring element from its
+/// center, multiplied by `sign`.
+fn offsets(element: ArrayView2<'_, u8>, sign: isize) -> Vec<(isize, isize)> {
+    let (height, width) = element.dim();
+    let (y, x) = ((height / 2) as isize, (width / 2) as isize);
+    element
+        .indexed_iter()
+        .filter(|&(_, &v)| v != 0)
+        .map(|((i, j), _)| (sign * (i as isize - y), sign * (j as isize - x)))
+        .collect()
+}
+
+/// Combines the pixels at `offsets` from each pixel with `pick`, starting from `initial`.
+fn extremum<A: Primitive>(
+    image: ArrayView3<'_, A>,
+    offsets: &[(isize, isize)],
+    initial: A,
+    pick: impl Fn(A, A) -> A,
+) -> Array3<A> {
+    let (height, width, _) = image.dim();
+    let mut out = Array3::zeros(image.dim());
+    for ((y, x, c), v) in out.indexed_iter_mut() {
+        *v = offsets
+            .iter()
+            .filter_map(|&(dy, dx)| {
+                let y = (y as isize + dy) as usize;
+                let x = (x as isize + dx) as usize;
+                // Offsets before the first pixel wrap around to large indices.
+                if y < height && x < width {
+                    Some(image[[y, x, c]])
+                } else {
+                    None
+                }
+            })
+            .fold(initial, &pick);
+    }
+    out
+}
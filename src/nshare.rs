@@ -0,0 +1,150 @@
+//! Conversion traits with the names and layouts of the `nshare` crate.
+//!
+//! Like `nshare`, 3d arrays made from images are in `(channels, height, width)` layout. These
+//! are views with permuted axes, so none of the conversions copy. The [`IntoNdGray`] and
+//! [`IntoNdColor`] traits at the crate root give the `(height, width, channels)` layout instead.
+//!
+//! [`IntoNdGray`]: crate::IntoNdGray
+//! [`IntoNdColor`]: crate::IntoNdColor
+
+#[allow(deprecated)]
+use crate::NdImage;
+use crate::{NdColor, NdColorMut, NdGray, NdGrayMut};
+use image::{ImageBuffer, Luma, Pixel, Primitive};
+use ndarray::{Array2, Array3, ArrayView3, ArrayViewMut3};
+use std::ops::{Deref, DerefMut};
+
+/// Turns an owned value into a 2d array.
+pub trait ToNdarray2 {
+    type Out;
+
+    fn into_ndarray2(self) -> Self::Out;
+}
+
+/// Turns an owned value into a 3d array.
+pub trait ToNdarray3 {
+    type Out;
+
+    fn into_ndarray3(self) -> Self::Out;
+}
+
+/// Borrows a value as a 2d array view.
+pub trait RefNdarray2 {
+    type Out;
+
+    fn ref_ndarray2(self) -> Self::Out;
+}
+
+/// Borrows a value as a 3d array view.
+pub trait RefNdarray3 {
+    type Out;
+
+    fn ref_ndarray3(self) -> Self::Out;
+}
+
+/// Mutably borrows a value as a 2d array view.
+pub trait MutNdarray2 {
+    type Out;
+
+    fn mut_ndarray2(self) -> Self::Out;
+}
+
+/// Mutably borrows a value as a 3d array view.
+pub trait MutNdarray3 {
+    type Out;
+
+    fn mut_ndarray3(self) -> Self::Out;
+}
+
+/// Turns an owned value into a gray image.
+pub trait ToImageLuma {
+    type Out;
+
+    fn into_image_luma(self) -> Self::Out;
+}
+
+#[allow(deprecated)]
+impl<A: Primitive + 'static> ToNdarray2 for ImageBuffer<Luma<A>, Vec<A>> {
+    type Out = Array2<A>;
+
+    fn into_ndarray2(self) -> Self::Out {
+        NdImage(self).into()
+    }
+}
+
+#[allow(deprecated)]
+impl<P, A> ToNdarray3 for ImageBuffer<P, Vec<A>>
+where
+    P: Pixel<Subpixel = A> + 'static,
+    A: Primitive + 'static,
+{
+    type Out = Array3<A>;
+
+    fn into_ndarray3(self) -> Self::Out {
+        Array3::from(NdImage(self)).permuted_axes([2, 0, 1])
+    }
+}
+
+#[allow(deprecated)]
+impl<'a, C, A> RefNdarray2 for &'a ImageBuffer<Luma<A>, C>
+where
+    A: Primitive + 'static,
+    C: Deref<Target = [A]> + AsRef<[A]>,
+{
+    type Out = NdGray<'a, A>;
+
+    fn ref_ndarray2(self) -> Self::Out {
+        NdImage(self).into()
+    }
+}
+
+#[allow(deprecated)]
+impl<'a, C, P, A> RefNdarray3 for &'a ImageBuffer<P, C>
+where
+    P: Pixel<Subpixel = A> + 'static,
+    A: Primitive + 'static,
+    C: Deref<Target = [A]> + AsRef<[A]>,
+{
+    type Out = ArrayView3<'a, A>;
+
+    fn ref_ndarray3(self) -> Self::Out {
+        NdColor::from(NdImage(self)).permuted_axes([2, 0, 1])
+    }
+}
+
+#[allow(deprecated)]
+impl<'a, C, A> MutNdarray2 for &'a mut ImageBuffer<Luma<A>, C>
+where
+    A: Primitive + 'static,
+    C: Deref<Target = [A]> + DerefMut + AsRef<[A]>,
+{
+    type Out = NdGrayMut<'a, A>;
+
+    fn mut_ndarray2(self) -> Self::Out {
+        NdImage(self).into()
+    }
+}
+
+#[allow(deprecated)]
+impl<'a, C, P, A> MutNdarray3 for &'a mut ImageBuffer<P, C>
+where
+    P: Pixel<Subpixel = A> + 'static,
+    A: Primitive + 'static,
+    C: Deref<Target = [A]> + DerefMut + AsRef<[A]>,
+{
+    type Out = ArrayViewMut3<'a, A>;
+
+    fn mut_ndarray3(self) -> Self::Out {
+        NdColorMut::from(NdImage(self)).permuted_axes([2, 0, 1])
+    }
+}
+
+/// This reuses the allocation of the array when it is in standard layout and copies otherwise.
+#[allow(deprecated)]
+impl<A: Primitive + 'static> ToImageLuma for Array2<A> {
+    type Out = Option<ImageBuffer<Luma<A>, Vec<A>>>;
+
+    fn into_image_luma(self) -> Self::Out {
+        Some(NdImage(self).into())
+    }
+}
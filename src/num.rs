@@ -0,0 +1,26 @@
+use image::Primitive;
+
+/// Whether `A` is an integer type, which truncates a half to zero when cast.
+pub(crate) fn is_integer<A: Primitive>() -> bool {
+    A::from(0.5).unwrap() == A::zero()
+}
+
+/// Converts a value to `A`, rounding it for integer types and saturating at the bounds of `A`.
+///
+/// NaN becomes zero for integer types. Bounds are checked by the cast itself rather than by
+/// clamping, because the largest 64-bit integers round up when they are converted to `f64`.
+pub(crate) fn saturate<A: Primitive>(v: f64) -> A {
+    if !is_integer::<A>() {
+        let min = A::min_value().to_f64().unwrap();
+        let max = A::max_value().to_f64().unwrap();
+        return A::from(v.clamp(min, max)).unwrap();
+    }
+    if v.is_nan() {
+        return A::zero();
+    }
+    A::from(v.round()).unwrap_or(if v > 0.0 {
+        A::max_value()
+    } else {
+        A::min_value()
+    })
+}
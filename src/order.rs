@@ -0,0 +1,116 @@
+use crate::{Colors, ConversionError, NdColor, ToImageBuffer};
+use image::{Bgr, Bgra, ImageBuffer, Luma, LumaA, Pixel, Primitive, Rgb, Rgba};
+use ndarray::Array3;
+
+/// The order of the red, green, and blue channels of color pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelOrder {
+    Rgb,
+    Bgr,
+}
+
+impl Colors {
+    /// The order of the color channels, which gray and YCbCr images do not have.
+    pub fn channel_order(self) -> Option<ChannelOrder> {
+        match self {
+            Colors::Luma | Colors::LumaA | Colors::YCbCr => None,
+            Colors::Rgb | Colors::Rgba => Some(ChannelOrder::Rgb),
+            Colors::Bgr | Colors::Bgra => Some(ChannelOrder::Bgr),
+        }
+    }
+}
+
+/// The pixel types of the `image` crate tagged with the colors they hold.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait ColorPixel: Pixel + private::Sealed {
+    /// The colors of the pixel type.
+    const COLORS: Colors;
+}
+
+/// Views an array holding `colors` as an image with the pixel type `P`.
+///
+/// Unlike the `NdImage` conversions, this fails with
+/// [`ConversionError::ChannelOrderMismatch`] instead of reinterpreting RGB channels as BGR or
+/// the other way around. It also fails if the array is not contiguous or its channel count does
+/// not match `P`.
+pub fn view_image_as<P>(
+    image: NdColor<'_, P::Subpixel>,
+    colors: Colors,
+) -> Result<ImageBuffer<P, &[P::Subpixel]>, ConversionError>
+where
+    P: ColorPixel + 'static,
+    P::Subpixel: 'static,
+{
+    check_order::<P>(colors)?;
+    image.into_image_buffer()
+}
+
+/// Turns an array holding `colors` into an image with the pixel type `P`.
+///
+/// RGB channels are swapped into BGR order in place, or the other way around, when `colors`
+/// and `P` disagree. Fails if the channel count does not match `P`.
+pub fn into_image_as<P, A>(
+    image: Array3<A>,
+    colors: Colors,
+) -> Result<ImageBuffer<P, Vec<A>>, ConversionError>
+where
+    P: ColorPixel<Subpixel = A> + 'static,
+    A: Primitive + 'static,
+{
+    let mut image: ImageBuffer<P, Vec<A>> = image.into_image_buffer()?;
+    // Only color pixels have an order, so there are at least 3 channels to swap.
+    if check_order::<P>(colors).is_err() {
+        for pixel in image.pixels_mut() {
+            pixel.channels_mut().swap(0, 2);
+        }
+    }
+    Ok(image)
+}
+
+/// Checks that `colors` is in the channel order of `P`.
+fn check_order<P: ColorPixel>(colors: Colors) -> Result<(), ConversionError> {
+    match (colors.channel_order(), P::COLORS.channel_order()) {
+        (Some(got), Some(expected)) if got != expected => {
+            Err(ConversionError::ChannelOrderMismatch { expected, got })
+        }
+        _ => Ok(()),
+    }
+}
+
+impl<T: Primitive + 'static> ColorPixel for Luma<T> {
+    const COLORS: Colors = Colors::Luma;
+}
+
+impl<T: Primitive + 'static> ColorPixel for LumaA<T> {
+    const COLORS: Colors = Colors::LumaA;
+}
+
+impl<T: Primitive + 'static> ColorPixel for Rgb<T> {
+    const COLORS: Colors = Colors::Rgb;
+}
+
+impl<T: Primitive + 'static> ColorPixel for Rgba<T> {
+    const COLORS: Colors = Colors::Rgba;
+}
+
+impl<T: Primitive + 'static> ColorPixel for Bgr<T> {
+    const COLORS: Colors = Colors::Bgr;
+}
+
+impl<T: Primitive + 'static> ColorPixel for Bgra<T> {
+    const COLORS: Colors = Colors::Bgra;
+}
+
+mod private {
+    use image::{Bgr, Bgra, Luma, LumaA, Primitive, Rgb, Rgba};
+
+    pub trait Sealed {}
+
+    impl<T: Primitive> Sealed for Luma<T> {}
+    impl<T: Primitive> Sealed for LumaA<T> {}
+    impl<T: Primitive> Sealed for Rgb<T> {}
+    impl<T: Primitive> Sealed for Rgba<T> {}
+    impl<T: Primitive> Sealed for Bgr<T> {}
+    impl<T: Primitive> Sealed for Bgra<T> {}
+}
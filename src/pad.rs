@@ -0,0 +1,64 @@
+use crate::NdColor;
+use ndarray::Array3;
+
+/// How the pixels past the borders of an array are filled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorderMode<A> {
+    /// Fills every channel with the same value.
+    Constant(A),
+    /// Mirrors the array at its edge pixels without repeating them, so `abcd` is padded as
+    /// `cb|abcd|cb`.
+    Reflect,
+    /// Repeats the edge pixels.
+    Replicate,
+    /// Continues with the pixels from the opposite side, as if the array was tiled.
+    Wrap,
+}
+
+impl<A> BorderMode<A> {
+    /// Gets the index of the pixel along an axis of length `len` that fills the position `index`,
+    /// or `None` for a constant border.
+    pub(crate) fn source(&self, index: isize, len: usize) -> Option<usize> {
+        let len = len as isize;
+        if (0..len).contains(&index) {
+            return Some(index as usize);
+        }
+        let index = match self {
+            BorderMode::Constant(_) => return None,
+            _ if len == 0 => return None,
+            BorderMode::Reflect if len == 1 => 0,
+            BorderMode::Reflect => {
+                // Mirroring back and forth repeats every `2 * (len - 1)` pixels.
+                let period = 2 * (len - 1);
+                let index = index.rem_euclid(period);
+                index.min(period - index)
+            }
+            BorderMode::Replicate => index.clamp(0, len - 1),
+            BorderMode::Wrap => index.rem_euclid(len),
+        };
+        Some(index as usize)
+    }
+}
+
+/// Copies a 3d array into a larger one, adding `(top, bottom)` rows and `(left, right)` columns
+/// of pixels that are filled in according to `mode`.
+///
+/// Borders wider than the array are filled in by repeating the mode, such as reflecting back and
+/// forth. Panics if the array is empty while pixels have to be taken from it.
+pub fn pad<A: Clone>(
+    image: NdColor<'_, A>,
+    [(top, bottom), (left, right)]: [(usize, usize); 2],
+    mode: BorderMode<A>,
+) -> Array3<A> {
+    let (height, width, channels) = image.dim();
+    let shape = (top + height + bottom, left + width + right, channels);
+    Array3::from_shape_fn(shape, |(y, x, c)| {
+        let y = mode.source(y as isize - top as isize, height);
+        let x = mode.source(x as isize - left as isize, width);
+        match (y, x, &mode) {
+            (Some(y), Some(x), _) => image[[y, x, c]].clone(),
+            (_, _, BorderMode::Constant(value)) => value.clone(),
+            _ => panic!("cannot take border pixels from an empty array"),
+        }
+    })
+}
@@ -0,0 +1,50 @@
+use crate::{open_image, save_image, Colors, NdColor, NdImageResult};
+use ndarray::Array3;
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use std::path::Path;
+
+/// Opens images in parallel on the global `rayon` thread pool, returning the result of each
+/// image in the same order as `paths`.
+///
+/// At most one image is decoded per thread at a time, so memory use is bounded by the size of the
+/// thread pool and the loaded arrays.
+pub fn open_images_parallel<P>(paths: &[P], colors: Colors) -> Vec<NdImageResult<Array3<u8>>>
+where
+    P: AsRef<Path> + Sync,
+{
+    paths
+        .par_iter()
+        .map(|path| open_image(path, colors))
+        .collect()
+}
+
+/// Saves images in parallel on the global `rayon` thread pool, returning the result of each
+/// image in the same order as `images`.
+///
+/// If `max_in_flight` is set, the images are instead saved on a dedicated pool of that many
+/// threads, so at most that many images are encoded at once, which bounds the memory used by the
+/// encoders.
+pub fn save_images_parallel<P>(
+    images: &[(P, NdColor<'_, u8>)],
+    colors: Colors,
+    max_in_flight: Option<usize>,
+) -> Vec<NdImageResult<()>>
+where
+    P: AsRef<Path> + Sync,
+{
+    let save = |(path, image): &(P, NdColor<'_, u8>)| save_image(path, image.view(), colors);
+    match max_in_flight {
+        Some(max_in_flight) => {
+            match ThreadPoolBuilder::new()
+                .num_threads(max_in_flight.max(1))
+                .build()
+            {
+                Ok(pool) => pool.install(|| images.par_iter().map(save).collect()),
+                // Saving one image at a time still stays within the bound.
+                Err(_) => images.iter().map(save).collect(),
+            }
+        }
+        None => images.par_iter().map(save).collect(),
+    }
+}
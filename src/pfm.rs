@@ -0,0 +1,139 @@
+use crate::float::convert_colors;
+use crate::{Colors, DecodeLimits, NdColor, NdGray, NdImageResult};
+use image::error::{DecodingError, ImageFormatHint};
+use image::ImageError;
+use ndarray::{s, Array2, Array3, ArrayView3, Axis};
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Opens a Portable FloatMap (PFM) image and loads it into a 3d array of `f32`.
+///
+/// The values are kept as they are stored, without any scaling.
+pub fn open_pfm(path: impl AsRef<Path>, colors: Colors) -> NdImageResult<Array3<f32>> {
+    let file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let pixel_bytes = 4 * colors.channel_count();
+    let (image, native) = read_pfm(BufReader::new(file), len, pixel_bytes)?;
+    Ok(convert_colors(image, native, colors))
+}
+
+/// Opens a Portable FloatMap (PFM) image and loads it into a 2d array of gray `f32` values.
+pub fn open_gray_pfm(path: impl AsRef<Path>) -> NdImageResult<Array2<f32>> {
+    let image = open_pfm(path, Colors::Luma)?;
+    Ok(image.index_axis_move(Axis(2), 0))
+}
+
+/// Saves a 3d array of `f32` as a Portable FloatMap (PFM) image.
+///
+/// Gray images are saved as gray and all others as RGB, so alpha channels are dropped.
+pub fn save_pfm(
+    path: impl AsRef<Path>,
+    image: NdColor<'_, f32>,
+    colors: Colors,
+) -> NdImageResult<()> {
+    let stored = match colors {
+        Colors::Luma | Colors::LumaA => Colors::Luma,
+        _ => Colors::Rgb,
+    };
+    let image = convert_colors(image.to_owned(), colors, stored);
+    let file = BufWriter::new(File::create(path)?);
+    write_pfm(file, image.view())
+}
+
+/// Saves a 2d array of gray `f32` values as a Portable FloatMap (PFM) image.
+pub fn save_gray_pfm(path: impl AsRef<Path>, image: NdGray<'_, f32>) -> NdImageResult<()> {
+    let file = BufWriter::new(File::create(path)?);
+    write_pfm(file, image.insert_axis(Axis(2)))
+}
+
+/// Decodes a PFM image of `len` bytes along with its native colors.
+///
+/// The image is checked against the global limits before its values are read, counting the
+/// `pixel_bytes` per pixel of the array it is loaded into.
+fn read_pfm(
+    mut reader: impl BufRead,
+    len: u64,
+    pixel_bytes: usize,
+) -> NdImageResult<(Array3<f32>, Colors)> {
+    let colors = match read_token(&mut reader)?.as_str() {
+        "Pf" => Colors::Luma,
+        "PF" => Colors::Rgb,
+        _ => return Err(pfm_error("missing PFM magic number")),
+    };
+    let width: usize = parse_token(&mut reader)?;
+    let height: usize = parse_token(&mut reader)?;
+    // The sign of the scale gives the byte order, negative meaning little endian.
+    let scale: f32 = parse_token(&mut reader)?;
+
+    let channels = colors.channel_count();
+    let size = width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(channels * 4))
+        .ok_or_else(|| pfm_error("invalid PFM header"))?;
+    // The header makes up part of the file too, so this only rules out sizes that cannot fit.
+    if size as u64 > len {
+        return Err(pfm_error("PFM image is truncated"));
+    }
+    let to_u32 = |v| u32::try_from(v).unwrap_or(u32::MAX);
+    // The values are read as bytes and then parsed into an array.
+    let stored_bytes = 2 * 4 * channels + pixel_bytes;
+    DecodeLimits::global().check(to_u32(width), to_u32(height), stored_bytes)?;
+    let mut raw = vec![0; size];
+    reader.read_exact(&mut raw)?;
+    let values = raw.chunks_exact(4).map(|bytes| {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if scale < 0.0 {
+            f32::from_le_bytes(bytes)
+        } else {
+            f32::from_be_bytes(bytes)
+        }
+    });
+    let image = Array3::from_shape_vec((height, width, channels), values.collect())
+        .map_err(|_| pfm_error("invalid PFM header"))?;
+    // Rows are stored from the bottom of the image to the top.
+    Ok((image.slice_move(s![..;-1, .., ..]), colors))
+}
+
+/// Encodes a gray or RGB array as a little endian PFM image.
+fn write_pfm(mut writer: impl Write, image: ArrayView3<'_, f32>) -> NdImageResult<()> {
+    let (height, width, channels) = image.dim();
+    let magic = if channels == 1 { "Pf" } else { "PF" };
+    write!(writer, "{}\n{} {}\n-1.0\n", magic, width, height)?;
+    for &value in image.slice(s![..;-1, .., ..]).iter() {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads a token of the PFM header along with the single whitespace byte that ends it.
+fn read_token(reader: &mut impl BufRead) -> NdImageResult<String> {
+    let mut token = Vec::new();
+    let mut byte = [0];
+    loop {
+        reader.read_exact(&mut byte)?;
+        if !byte[0].is_ascii_whitespace() {
+            token.push(byte[0]);
+        } else if !token.is_empty() {
+            break;
+        }
+    }
+    String::from_utf8(token).map_err(|_| pfm_error("PFM header is not ASCII"))
+}
+
+/// Reads a token of the PFM header and parses it.
+fn parse_token<T: std::str::FromStr>(reader: &mut impl BufRead) -> NdImageResult<T> {
+    read_token(reader)?
+        .parse()
+        .map_err(|_| pfm_error("invalid PFM header"))
+}
+
+fn pfm_error(message: &'static str) -> crate::NdImageError {
+    ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Name("PFM".to_owned()),
+        message,
+    ))
+    .into()
+}
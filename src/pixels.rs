@@ -0,0 +1,103 @@
+use crate::{ConversionError, IntoNdColor, NdColor};
+use image::{Bgr, Bgra, ImageBuffer, Luma, LumaA, Primitive, Rgb, Rgba};
+use std::convert::TryFrom;
+use std::ops::Deref;
+
+/// A contiguous 3d array view whose channel count `C` is part of its type.
+///
+/// The view is always in standard layout and its dimensions fit in an image, so it converts
+/// into an image view of any pixel type with `C` channels without failing or copying.
+#[derive(Debug, Clone, Copy)]
+pub struct NdPixels<'a, A, const C: usize> {
+    view: NdColor<'a, A>,
+}
+
+impl<'a, A, const C: usize> NdPixels<'a, A, C> {
+    /// Wraps a view, failing if it does not have `C` channels or is not contiguous.
+    pub fn new(view: NdColor<'a, A>) -> Result<Self, ConversionError> {
+        let (height, width, channels) = view.dim();
+        if channels != C {
+            return Err(ConversionError::WrongChannelCount {
+                expected: C,
+                got: channels,
+            });
+        }
+        if !view.is_standard_layout() {
+            return Err(ConversionError::NonContiguous);
+        }
+        if u32::try_from(height).is_err() || u32::try_from(width).is_err() {
+            return Err(ConversionError::BadShape {
+                shape: view.shape().to_vec(),
+            });
+        }
+        Ok(NdPixels { view })
+    }
+
+    /// Gets the view of the pixels.
+    pub fn view(&self) -> NdColor<'a, A> {
+        self.view
+    }
+
+    /// Gets the contiguous samples of the pixels.
+    pub fn as_slice(&self) -> &'a [A] {
+        self.view.to_slice().unwrap()
+    }
+
+    /// Gets the `(width, height)` of the pixels as an image.
+    fn dimensions(&self) -> (u32, u32) {
+        let (height, width, _) = self.view.dim();
+        (width as u32, height as u32)
+    }
+}
+
+impl<'a, A, const C: usize> Deref for NdPixels<'a, A, C> {
+    type Target = NdColor<'a, A>;
+
+    fn deref(&self) -> &NdColor<'a, A> {
+        &self.view
+    }
+}
+
+impl<'a, A, const C: usize> TryFrom<NdColor<'a, A>> for NdPixels<'a, A, C> {
+    type Error = ConversionError;
+
+    fn try_from(view: NdColor<'a, A>) -> Result<Self, Self::Error> {
+        NdPixels::new(view)
+    }
+}
+
+/// Implements the conversions between the views of an image with `$pixel` pixels and
+/// `NdPixels` with `$channels` channels.
+macro_rules! pixel_conversions {
+    ($pixel:ident, $channels:literal) => {
+        impl<'a, A: Primitive + 'static> From<NdPixels<'a, A, $channels>>
+            for ImageBuffer<$pixel<A>, &'a [A]>
+        {
+            fn from(pixels: NdPixels<'a, A, $channels>) -> Self {
+                let (width, height) = pixels.dimensions();
+                // The length was checked when the view was wrapped.
+                ImageBuffer::from_raw(width, height, pixels.as_slice()).unwrap()
+            }
+        }
+
+        impl<'a, A, Container> From<&'a ImageBuffer<$pixel<A>, Container>>
+            for NdPixels<'a, A, $channels>
+        where
+            A: Primitive + 'static,
+            Container: Deref<Target = [A]> + AsRef<[A]>,
+        {
+            fn from(image: &'a ImageBuffer<$pixel<A>, Container>) -> Self {
+                NdPixels {
+                    view: image.into_nd_color(),
+                }
+            }
+        }
+    };
+}
+
+pixel_conversions!(Luma, 1);
+pixel_conversions!(LumaA, 2);
+pixel_conversions!(Rgb, 3);
+pixel_conversions!(Rgba, 4);
+pixel_conversions!(Bgr, 3);
+pixel_conversions!(Bgra, 4);
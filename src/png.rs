@@ -0,0 +1,346 @@
+use crate::{NdImageResult, PngCompression, PngFilter, SaveOptions};
+use image::error::{ImageFormatHint, UnsupportedError, UnsupportedErrorKind};
+use image::{ColorType, ImageError};
+use miniz_oxide::deflate::core::{
+    compress_to_output, create_comp_flags_from_zip_params, CompressionStrategy, CompressorOxide,
+    TDEFLFlush, TDEFLStatus,
+};
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// The number of bytes of pixels compressed at a time.
+const CHUNK_BYTES: usize = 1 << 20;
+
+/// The modulus of the Adler-32 checksum.
+const ADLER_BASE: u64 = 65521;
+
+/// The `(x, y)` start and the `(x, y)` step between the pixels of each Adam7 pass.
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+/// Encodes a PNG image in independent chunks of rows.
+///
+/// Each chunk is filtered and deflated on its own, then the compressed chunks are joined into a
+/// single zlib stream. With the `rayon` feature the chunks are encoded in parallel. Interlaced
+/// images are split into their Adam7 passes, whose rows are chunked one pass after another.
+pub(crate) fn write_png_chunked(
+    mut writer: impl Write,
+    bytes: &[u8],
+    width: u32,
+    height: u32,
+    color: ColorType,
+    options: &SaveOptions,
+) -> NdImageResult<()> {
+    let (color_code, sample_bytes) = match color {
+        ColorType::L8 => (0, 1),
+        ColorType::La8 => (4, 1),
+        ColorType::Rgb8 => (2, 1),
+        ColorType::Rgba8 => (6, 1),
+        ColorType::L16 => (0, 2),
+        ColorType::La16 => (4, 2),
+        ColorType::Rgb16 => (2, 2),
+        ColorType::Rgba16 => (6, 2),
+        color => {
+            return Err(
+                ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+                    ImageFormatHint::Exact(image::ImageFormat::Png),
+                    UnsupportedErrorKind::Color(color.into()),
+                ))
+                .into(),
+            )
+        }
+    };
+    let pixel_bytes = color.bytes_per_pixel() as usize;
+    let (width, height) = (width as usize, height as usize);
+    let passes: Vec<(Cow<'_, [u8]>, usize, usize)> = if options.png_interlace {
+        ADAM7
+            .iter()
+            .map(|&pass| adam7_pass(bytes, width, height, pixel_bytes, pass))
+            .collect()
+    } else {
+        vec![(Cow::Borrowed(bytes), width, height)]
+    };
+    let encoders: Vec<ChunkEncoder<'_>> = passes
+        .iter()
+        // Passes without pixels have no rows at all, not even their filter types.
+        .filter(|(_, width, height)| *width > 0 && *height > 0)
+        .map(|(bytes, width, height)| ChunkEncoder {
+            bytes,
+            stride: width * pixel_bytes,
+            rows: *height,
+            pixel_bytes,
+            sixteen_bit: sample_bytes == 2,
+            filter: options.png_filter,
+            flags: compression_flags(options.png_compression),
+        })
+        .collect();
+    let chunks: Vec<(&ChunkEncoder<'_>, Range<usize>)> = encoders
+        .iter()
+        .flat_map(|encoder| {
+            let rows_per_chunk = (CHUNK_BYTES / encoder.stride.max(1)).max(1);
+            (0..encoder.rows)
+                .step_by(rows_per_chunk)
+                .map(move |start| (encoder, start..(start + rows_per_chunk).min(encoder.rows)))
+        })
+        .collect();
+    let last = chunks.len().saturating_sub(1);
+
+    #[cfg(feature = "rayon")]
+    let encoded: Vec<(Vec<u8>, u32, usize)> = {
+        use rayon::prelude::*;
+
+        chunks
+            .into_par_iter()
+            .enumerate()
+            .map(|(i, (encoder, rows))| encoder.encode(rows, i == last))
+            .collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let encoded: Vec<(Vec<u8>, u32, usize)> = chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, (encoder, rows))| encoder.encode(rows, i == last))
+        .collect();
+
+    writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    let interlace = u8::from(options.png_interlace);
+    header.extend_from_slice(&[8 * sample_bytes, color_code, 0, 0, interlace]);
+    write_chunk(&mut writer, b"IHDR", &[&header])?;
+    // The zlib header uses the default compression level, which is only informative.
+    write_chunk(&mut writer, b"IDAT", &[&[0x78, 0x9c]])?;
+    let mut adler = 1;
+    for (compressed, chunk_adler, len) in &encoded {
+        write_chunk(&mut writer, b"IDAT", &[compressed])?;
+        adler = adler32_combine(adler, *chunk_adler, *len);
+    }
+    // An image without rows still needs a final deflate block.
+    if encoded.is_empty() {
+        write_chunk(&mut writer, b"IDAT", &[&[0x03, 0x00]])?;
+    }
+    write_chunk(&mut writer, b"IDAT", &[&adler.to_be_bytes()])?;
+    write_chunk(&mut writer, b"IEND", &[])?;
+    Ok(())
+}
+
+/// Gathers the pixels of an Adam7 pass into a reduced image, returning it with its width and
+/// height.
+fn adam7_pass(
+    bytes: &[u8],
+    width: usize,
+    height: usize,
+    pixel_bytes: usize,
+    (x0, y0, dx, dy): (usize, usize, usize, usize),
+) -> (Cow<'static, [u8]>, usize, usize) {
+    let pass_width = (width + dx - 1).saturating_sub(x0) / dx;
+    let pass_height = (height + dy - 1).saturating_sub(y0) / dy;
+    let stride = width * pixel_bytes;
+    let mut pass = Vec::with_capacity(pass_width * pass_height * pixel_bytes);
+    for y in (y0..height).step_by(dy) {
+        for x in (x0..width).step_by(dx) {
+            pass.extend_from_slice(&bytes[y * stride + x * pixel_bytes..][..pixel_bytes]);
+        }
+    }
+    (Cow::Owned(pass), pass_width, pass_height)
+}
+
+/// The parameters shared by every chunk of an image or of an Adam7 pass.
+struct ChunkEncoder<'a> {
+    bytes: &'a [u8],
+    stride: usize,
+    rows: usize,
+    pixel_bytes: usize,
+    sixteen_bit: bool,
+    filter: PngFilter,
+    flags: u32,
+}
+
+impl ChunkEncoder<'_> {
+    /// Filters and deflates `rows`, returning the compressed bytes and the Adler-32 checksum and
+    /// length of the filtered bytes.
+    ///
+    /// Only the `last` chunk of the image ends the deflate stream.
+    fn encode(&self, rows: Range<usize>, last: bool) -> (Vec<u8>, u32, usize) {
+        // The row before the chunk is needed to filter its first row.
+        let first = rows.start.saturating_sub(1);
+        let raw = &self.bytes[first * self.stride..rows.end * self.stride];
+        let swapped;
+        let raw = if self.sixteen_bit {
+            // PNG stores 16-bit samples in big endian order.
+            swapped = raw
+                .chunks_exact(2)
+                .flat_map(|sample| u16::from_ne_bytes([sample[0], sample[1]]).to_be_bytes())
+                .collect::<Vec<u8>>();
+            &swapped[..]
+        } else {
+            raw
+        };
+
+        let mut filtered = Vec::with_capacity(rows.len() * (self.stride + 1));
+        for y in rows.clone() {
+            let row = &raw[(y - first) * self.stride..][..self.stride];
+            let previous = if y == 0 {
+                None
+            } else {
+                Some(&raw[(y - 1 - first) * self.stride..][..self.stride])
+            };
+            filter_row(self.filter, row, previous, self.pixel_bytes, &mut filtered);
+        }
+
+        let mut compressor = CompressorOxide::new(self.flags);
+        let mut compressed = Vec::with_capacity(filtered.len() / 2);
+        let flush = if last {
+            TDEFLFlush::Finish
+        } else {
+            // A sync flush ends the chunk on a byte boundary without ending the stream.
+            TDEFLFlush::Sync
+        };
+        let mut position = 0;
+        loop {
+            let (status, consumed) =
+                compress_to_output(&mut compressor, &filtered[position..], flush, |output| {
+                    compressed.extend_from_slice(output);
+                    true
+                });
+            position += consumed;
+            match status {
+                TDEFLStatus::Done => break,
+                TDEFLStatus::Okay if position == filtered.len() => break,
+                TDEFLStatus::Okay => {}
+                // Writing into a `Vec` cannot fail, so the compressor can only fail from a bug.
+                status => panic!("deflate failed with {:?}", status),
+            }
+        }
+        (compressed, adler32(&filtered), filtered.len())
+    }
+}
+
+/// The flags of a raw deflate compressor for a compression level.
+fn compression_flags(compression: PngCompression) -> u32 {
+    let (level, strategy) = match compression {
+        PngCompression::Default => (6, CompressionStrategy::Default),
+        PngCompression::Fast => (1, CompressionStrategy::Default),
+        PngCompression::Best => (9, CompressionStrategy::Default),
+        PngCompression::Huffman => (6, CompressionStrategy::HuffmanOnly),
+        PngCompression::Rle => (6, CompressionStrategy::RLE),
+    };
+    // Negative window bits produce a raw deflate stream without a zlib header.
+    create_comp_flags_from_zip_params(level, -15, strategy as i32)
+}
+
+/// Appends the filter type and the filtered bytes of a row to `out`.
+fn filter_row(
+    filter: PngFilter,
+    row: &[u8],
+    previous: Option<&[u8]>,
+    pixel_bytes: usize,
+    out: &mut Vec<u8>,
+) {
+    let up = |i: usize| previous.map_or(0, |previous| previous[i]);
+    let left = |i: usize| {
+        if i < pixel_bytes {
+            0
+        } else {
+            row[i - pixel_bytes]
+        }
+    };
+    let up_left = |i: usize| {
+        if i < pixel_bytes {
+            0
+        } else {
+            previous.map_or(0, |previous| previous[i - pixel_bytes])
+        }
+    };
+    match filter {
+        PngFilter::NoFilter => {
+            out.push(0);
+            out.extend_from_slice(row);
+        }
+        PngFilter::Sub => {
+            out.push(1);
+            out.extend((0..row.len()).map(|i| row[i].wrapping_sub(left(i))));
+        }
+        PngFilter::Up => {
+            out.push(2);
+            out.extend((0..row.len()).map(|i| row[i].wrapping_sub(up(i))));
+        }
+        PngFilter::Avg => {
+            out.push(3);
+            out.extend((0..row.len()).map(|i| {
+                let average = (u16::from(left(i)) + u16::from(up(i))) / 2;
+                row[i].wrapping_sub(average as u8)
+            }));
+        }
+        PngFilter::Paeth => {
+            out.push(4);
+            out.extend(
+                (0..row.len()).map(|i| row[i].wrapping_sub(paeth(left(i), up(i), up_left(i)))),
+            );
+        }
+    }
+}
+
+/// Predicts a byte from its neighbours to the left, above, and above left.
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let (a16, b16, c16) = (i16::from(a), i16::from(b), i16::from(c));
+    let p = a16 + b16 - c16;
+    let (pa, pb, pc) = ((p - a16).abs(), (p - b16).abs(), (p - c16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Computes the Adler-32 checksum of `data`.
+fn adler32(data: &[u8]) -> u32 {
+    // The sums can be reduced once per block of this many bytes without overflowing.
+    const BLOCK: usize = 5552;
+    let (mut a, mut b) = (1u64, 0u64);
+    for block in data.chunks(BLOCK) {
+        for &byte in block {
+            a += u64::from(byte);
+            b += a;
+        }
+        a %= ADLER_BASE;
+        b %= ADLER_BASE;
+    }
+    (b << 16 | a) as u32
+}
+
+/// Combines the Adler-32 checksums of two consecutive pieces of data, where the second is `len`
+/// bytes long.
+fn adler32_combine(first: u32, second: u32, len: usize) -> u32 {
+    let len = len as u64 % ADLER_BASE;
+    let (a1, b1) = (u64::from(first & 0xffff), u64::from(first >> 16));
+    let (a2, b2) = (u64::from(second & 0xffff), u64::from(second >> 16));
+    let a = (a1 + a2 + ADLER_BASE - 1) % ADLER_BASE;
+    let b = (b1 + b2 + len * a1 + ADLER_BASE - len) % ADLER_BASE;
+    (b << 16 | a) as u32
+}
+
+/// Writes a PNG chunk made of the concatenation of `parts`.
+fn write_chunk(writer: &mut impl Write, kind: &[u8; 4], parts: &[&[u8]]) -> io::Result<()> {
+    let len: usize = parts.iter().map(|part| part.len()).sum();
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    writer.write_all(&(len as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    for part in parts {
+        crc.update(part);
+        writer.write_all(part)?;
+    }
+    writer.write_all(&crc.finalize().to_be_bytes())
+}
@@ -0,0 +1,80 @@
+use ndarray::Array3;
+use std::sync::Mutex;
+
+/// A pool of byte buffers that are reused between decodes and encodes.
+///
+/// Buffers are kept in buckets by the power of two of their capacity, so a buffer can be reused
+/// for any image of a similar size. The pool can be shared between threads.
+#[derive(Debug)]
+pub struct ImageBufferPool {
+    buckets: Mutex<Vec<Vec<Vec<u8>>>>,
+    max_per_bucket: usize,
+}
+
+impl ImageBufferPool {
+    /// Creates an empty pool that keeps up to 4 buffers of each size.
+    pub fn new() -> Self {
+        Self::with_max_per_bucket(4)
+    }
+
+    /// Creates an empty pool that keeps up to `max_per_bucket` buffers of each size.
+    pub fn with_max_per_bucket(max_per_bucket: usize) -> Self {
+        ImageBufferPool {
+            buckets: Mutex::new(Vec::new()),
+            max_per_bucket,
+        }
+    }
+
+    /// Takes a buffer of `len` bytes from the pool, allocating one if none fit.
+    ///
+    /// The contents of a reused buffer are left over from its previous use.
+    pub fn take(&self, len: usize) -> Vec<u8> {
+        let bucket = len.next_power_of_two().trailing_zeros() as usize;
+        let reused = self
+            .buckets
+            .lock()
+            .unwrap()
+            .get_mut(bucket)
+            .and_then(|buffers| buffers.pop());
+        let mut buffer = reused.unwrap_or_else(|| Vec::with_capacity(len.next_power_of_two()));
+        buffer.resize(len, 0);
+        buffer
+    }
+
+    /// Takes an array of `shape` from the pool, allocating one if none fit.
+    ///
+    /// The contents of a reused array are left over from its previous use.
+    pub fn take_array(&self, shape: (usize, usize, usize)) -> Array3<u8> {
+        let (height, width, channels) = shape;
+        Array3::from_shape_vec(shape, self.take(height * width * channels)).unwrap()
+    }
+
+    /// Returns a buffer to the pool so that it can be reused.
+    ///
+    /// The buffer is dropped if its bucket is already full.
+    pub fn recycle(&self, buffer: Vec<u8>) {
+        if buffer.capacity() == 0 {
+            return;
+        }
+        // The bucket of a buffer is rounded down so that it fits every length in that bucket.
+        let bucket = (usize::BITS - 1 - buffer.capacity().leading_zeros()) as usize;
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() <= bucket {
+            buckets.resize_with(bucket + 1, Vec::new);
+        }
+        if buckets[bucket].len() < self.max_per_bucket {
+            buckets[bucket].push(buffer);
+        }
+    }
+
+    /// Returns the buffer of an array to the pool so that it can be reused.
+    pub fn recycle_array(&self, image: Array3<u8>) {
+        self.recycle(image.into_raw_vec());
+    }
+}
+
+impl Default for ImageBufferPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,112 @@
+use crate::limits::Target;
+use crate::{Colors, DecodeLimits, NdImageResult};
+use image::error::{ImageFormatHint, UnsupportedError};
+use image::io::Reader;
+use image::{ColorType, ImageError, ImageFormat};
+use ndarray::Array3;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// The properties of an image file that are known without decoding its pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    /// The color type the image is stored with.
+    pub color: ColorType,
+    /// The number of bits of each channel.
+    pub bit_depth: u8,
+    pub format: ImageFormat,
+}
+
+/// An image file whose properties have been read, but whose pixels are only decoded on demand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageHandle {
+    path: PathBuf,
+    info: ImageInfo,
+}
+
+impl ImageHandle {
+    /// Reads the properties of an image file.
+    pub fn open(path: impl AsRef<Path>) -> NdImageResult<Self> {
+        let path = path.as_ref();
+        Ok(ImageHandle {
+            path: path.to_owned(),
+            info: probe_image(path)?,
+        })
+    }
+
+    /// Gets the properties of the image.
+    pub fn info(&self) -> ImageInfo {
+        self.info
+    }
+
+    /// Gets the path of the image.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Decodes the image into a 3d array.
+    /// This performs a copy.
+    pub fn decode(&self, colors: Colors) -> NdImageResult<Array3<u8>> {
+        let mut reader = Reader::open(&self.path)?;
+        reader.set_format(self.info.format);
+        let image = crate::limits::decode(reader, DecodeLimits::global(), Target::u8(colors))?;
+        Ok(crate::decode_u8(image, colors))
+    }
+}
+
+/// Reads the dimensions, color type, and format of an image file without decoding its pixels.
+///
+/// The format is guessed from the contents of the file. Only the headers of images are read,
+/// except for formats whose codecs are disabled, which are decoded to find their color type.
+pub fn probe_image(path: impl AsRef<Path>) -> NdImageResult<ImageInfo> {
+    let reader = Reader::open(path)?.with_guessed_format()?;
+    let format = match reader.format() {
+        Some(format) => format,
+        None => {
+            let hint = ImageFormatHint::Unknown;
+            return Err(ImageError::Unsupported(UnsupportedError::from(hint)).into());
+        }
+    };
+    let (width, height, color) = read_header(reader.into_inner(), format)?;
+    Ok(ImageInfo {
+        width,
+        height,
+        color,
+        bit_depth: (color.bits_per_pixel() / u16::from(color.channel_count())) as u8,
+        format,
+    })
+}
+
+/// Reads the dimensions and color type from the header of an image.
+fn read_header(file: BufReader<File>, format: ImageFormat) -> NdImageResult<(u32, u32, ColorType)> {
+    // The header is unused when every codec is disabled.
+    #[allow(dead_code)]
+    fn header<'a>(decoder: impl image::ImageDecoder<'a>) -> (u32, u32, ColorType) {
+        let (width, height) = decoder.dimensions();
+        (width, height, decoder.color_type())
+    }
+    Ok(match format {
+        #[cfg(feature = "gif")]
+        ImageFormat::Gif => header(image::codecs::gif::GifDecoder::new(file)?),
+        #[cfg(feature = "hdr")]
+        ImageFormat::Hdr => header(image::codecs::hdr::HdrAdapter::new(file)?),
+        #[cfg(feature = "jpeg")]
+        ImageFormat::Jpeg => header(image::codecs::jpeg::JpegDecoder::new(file)?),
+        #[cfg(feature = "png")]
+        ImageFormat::Png => header(image::codecs::png::PngDecoder::new(file)?),
+        #[cfg(feature = "pnm")]
+        ImageFormat::Pnm => header(image::codecs::pnm::PnmDecoder::new(file)?),
+        #[cfg(feature = "tiff")]
+        ImageFormat::Tiff => header(image::codecs::tiff::TiffDecoder::new(file)?),
+        #[cfg(feature = "webp")]
+        ImageFormat::WebP => header(image::codecs::webp::WebPDecoder::new(file)?),
+        format => {
+            let image = Reader::with_format(file, format).decode()?;
+            let (width, height) = image::GenericImageView::dimensions(&image);
+            (width, height, image.color())
+        }
+    })
+}
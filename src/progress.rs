@@ -0,0 +1,92 @@
+use crate::limits::Target;
+use crate::{Colors, DecodeLimits, NdImageError, NdImageResult};
+use image::io::Reader;
+use ndarray::Array3;
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// How much of an image file has been decoded so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeProgress {
+    /// The number of bytes of the file read by the decoder.
+    pub bytes_read: u64,
+    /// The size of the file in bytes.
+    pub total_bytes: u64,
+}
+
+impl DecodeProgress {
+    /// The fraction of the file read so far, from `0.0` to `1.0`.
+    pub fn fraction(&self) -> f64 {
+        if self.total_bytes == 0 {
+            1.0
+        } else {
+            self.bytes_read as f64 / self.total_bytes as f64
+        }
+    }
+}
+
+/// Opens a color image using the `image` crate and loads it into a 3d array, reporting the
+/// progress of decoding to `progress`.
+///
+/// Progress is measured in bytes of the file consumed by the decoder, as the `image` crate does
+/// not report decoded rows. Returning `false` from `progress` aborts decoding with
+/// [`NdImageError::Aborted`].
+/// This performs a copy.
+pub fn open_image_with_progress(
+    path: impl AsRef<Path>,
+    colors: Colors,
+    mut progress: impl FnMut(DecodeProgress) -> bool,
+) -> NdImageResult<Array3<u8>> {
+    let file = File::open(path)?;
+    let total_bytes = file.metadata()?.len();
+    let aborted = Cell::new(false);
+    let reader = ProgressReader {
+        inner: file,
+        progress: DecodeProgress {
+            bytes_read: 0,
+            total_bytes,
+        },
+        callback: &mut progress,
+        aborted: &aborted,
+    };
+    let image = Reader::new(BufReader::new(reader))
+        .with_guessed_format()
+        .map_err(NdImageError::from)
+        .and_then(|reader| {
+            crate::limits::decode(reader, DecodeLimits::global(), Target::u8(colors))
+        });
+    match image {
+        _ if aborted.get() => Err(NdImageError::Aborted),
+        image => Ok(crate::decode_u8(image?, colors)),
+    }
+}
+
+/// A reader that reports its position to a progress callback.
+struct ProgressReader<'a, R> {
+    inner: R,
+    progress: DecodeProgress,
+    callback: &'a mut dyn FnMut(DecodeProgress) -> bool,
+    aborted: &'a Cell<bool>,
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.progress.bytes_read += read as u64;
+        if !(self.callback)(self.progress) {
+            self.aborted.set(true);
+            return Err(io::Error::other("decoding was aborted"));
+        }
+        Ok(read)
+    }
+}
+
+impl<R: Seek> Seek for ProgressReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = self.inner.seek(pos)?;
+        self.progress.bytes_read = position;
+        Ok(position)
+    }
+}
@@ -0,0 +1,119 @@
+use crate::NdColor;
+use ndarray::Array3;
+
+/// The filter used to smooth each level of a pyramid before it is downsampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PyramidFilter {
+    /// Averages each 2x2 block of pixels.
+    Box,
+    /// Blurs with the 5x5 binomial approximation of a Gaussian, like OpenCV's `pyrDown`.
+    Gaussian,
+}
+
+impl PyramidFilter {
+    /// The offset of the first tap from each sampled pixel and the weights of the separable taps.
+    ///
+    /// The weights sum to a power of two so that the sums can be normalized with a shift.
+    fn kernel(self) -> (isize, &'static [u32]) {
+        match self {
+            PyramidFilter::Box => (0, &[1, 1]),
+            PyramidFilter::Gaussian => (-2, &[1, 4, 6, 4, 1]),
+        }
+    }
+}
+
+/// Builds an image pyramid of up to `levels` arrays, each half the size of the one before it.
+///
+/// The first level is a copy of `image`. Odd dimensions are rounded up, pixels past the borders
+/// are clamped to the edge, and the pyramid stops early once a level has at most one pixel. Each
+/// level is filtered and downsampled from the previous one in a single pass.
+pub fn build_pyramid(
+    image: NdColor<'_, u8>,
+    levels: usize,
+    filter: PyramidFilter,
+) -> Vec<Array3<u8>> {
+    let mut pyramid: Vec<Array3<u8>> = Vec::with_capacity(levels);
+    if levels == 0 {
+        return pyramid;
+    }
+    pyramid.push(image.as_standard_layout().into_owned());
+    while pyramid.len() < levels {
+        let last = pyramid.last().unwrap();
+        let (height, width, channels) = last.dim();
+        if height * width <= 1 || channels == 0 {
+            break;
+        }
+        let next = downsample(last, filter);
+        pyramid.push(next);
+    }
+    pyramid
+}
+
+/// Filters and halves a contiguous `(height, width, channels)` array.
+///
+/// Each output row is filtered vertically from the input rows before being filtered
+/// horizontally, so only one row of intermediate sums is kept.
+fn downsample(image: &Array3<u8>, filter: PyramidFilter) -> Array3<u8> {
+    let (height, width, channels) = image.dim();
+    let (out_height, out_width) = (height.div_ceil(2), width.div_ceil(2));
+    let kernel = filter.kernel();
+    let (first, weights) = kernel;
+    let src = image.as_slice().unwrap();
+    let src_len = width * channels;
+    let out_len = out_width * channels;
+    let mut out = vec![0u8; out_height * out_len];
+    let mut sums = vec![0u32; src_len];
+    for (y, dst) in out.chunks_exact_mut(out_len).enumerate() {
+        sums.iter_mut().for_each(|sum| *sum = 0);
+        for (offset, &weight) in (first..).zip(weights) {
+            let y = clamp_index(2 * y as isize + offset, height);
+            for (sum, &value) in sums.iter_mut().zip(&src[y * src_len..][..src_len]) {
+                *sum += weight * u32::from(value);
+            }
+        }
+        // The common channel counts are matched so that the loop is compiled for each of them.
+        match channels {
+            1 => filter_row(&sums, dst, 1, kernel),
+            3 => filter_row(&sums, dst, 3, kernel),
+            4 => filter_row(&sums, dst, 4, kernel),
+            _ => filter_row(&sums, dst, channels, kernel),
+        }
+    }
+    Array3::from_shape_vec((out_height, out_width, channels), out).unwrap()
+}
+
+/// Filters a row of vertical sums horizontally into every other pixel of `dst`.
+#[inline(always)]
+fn filter_row(sums: &[u32], dst: &mut [u8], channels: usize, (first, weights): (isize, &[u32])) {
+    let width = sums.len() / channels;
+    // Both passes are normalized at once, by the square of the sum of the weights.
+    let shift = 2 * weights.iter().sum::<u32>().trailing_zeros();
+    let half = (1 << shift) >> 1;
+    for (x, pixel) in dst.chunks_exact_mut(channels).enumerate() {
+        let start = 2 * x as isize + first;
+        if start >= 0 && start as usize + weights.len() <= width {
+            let window = &sums[start as usize * channels..][..weights.len() * channels];
+            for (channel, value) in pixel.iter_mut().enumerate() {
+                let mut sum = 0;
+                for (tap, &weight) in weights.iter().enumerate() {
+                    sum += weight * window[tap * channels + channel];
+                }
+                *value = ((sum + half) >> shift) as u8;
+            }
+        } else {
+            // Pixels near the borders clamp each tap to the edge.
+            for (channel, value) in pixel.iter_mut().enumerate() {
+                let mut sum = 0;
+                for (offset, &weight) in (start..).zip(weights) {
+                    sum += weight * sums[clamp_index(offset, width) * channels + channel];
+                }
+                *value = ((sum + half) >> shift) as u8;
+            }
+        }
+    }
+}
+
+/// Clamps a possibly out of bounds index to the edge of an axis of length `len`.
+fn clamp_index(index: isize, len: usize) -> usize {
+    index.clamp(0, len as isize - 1) as usize
+}
@@ -0,0 +1,175 @@
+use crate::float::read_float;
+use crate::limits::Target;
+use crate::linear::Transfer;
+use crate::resize::{decode_resized, resize_to_fit};
+use crate::{exif, Colors, DecodeLimits, NdImageResult};
+use image::io::Reader;
+use image::{ColorType, DynamicImage};
+use ndarray::{Array2, Array3};
+use std::io::Cursor;
+use std::path::Path;
+
+/// A builder that opens an image with a combination of loading options.
+///
+/// Options are chained after [`NdImageReader::open`], then the image is loaded with one of the
+/// `decode` methods, such as `NdImageReader::open(path)?.auto_orient(true).decode3()?`.
+#[derive(Debug, Clone)]
+pub struct NdImageReader {
+    data: Vec<u8>,
+    colors: Colors,
+    limits: DecodeLimits,
+    max_size: Option<(u32, u32)>,
+    auto_orient: bool,
+    linear: bool,
+}
+
+impl NdImageReader {
+    /// Reads the image file at `path` into memory to be decoded.
+    ///
+    /// By default images are loaded as [`Colors::Rgb`] within the
+    /// [global limits](DecodeLimits::global), without scaling or orientation.
+    pub fn open(path: impl AsRef<Path>) -> NdImageResult<Self> {
+        Ok(Self::from_memory(std::fs::read(path)?))
+    }
+
+    /// Decodes an image that is already in memory.
+    pub fn from_memory(data: Vec<u8>) -> Self {
+        NdImageReader {
+            data,
+            colors: Colors::Rgb,
+            limits: DecodeLimits::global(),
+            max_size: None,
+            auto_orient: false,
+            linear: false,
+        }
+    }
+
+    /// Sets the colors of 3d arrays.
+    pub fn with_colors(mut self, colors: Colors) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Rejects images that exceed `limits` before decoding them, instead of the global limits.
+    pub fn with_limits(mut self, limits: DecodeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Scales images down to fit within `max_width` and `max_height`, keeping their aspect ratio.
+    ///
+    /// Like [`open_image_resized`](crate::open_image_resized), JPEG images are scaled while they
+    /// are decoded and images are never scaled up. The size applies after any orientation.
+    pub fn with_max_size(mut self, max_width: u32, max_height: u32) -> Self {
+        self.max_size = Some((max_width, max_height));
+        self
+    }
+
+    /// Sets whether images are rotated and flipped upright according to their EXIF orientation.
+    pub fn auto_orient(mut self, auto_orient: bool) -> Self {
+        self.auto_orient = auto_orient;
+        self
+    }
+
+    /// Sets whether `f32` arrays are decoded to linear light instead of keeping the encoded values.
+    ///
+    /// PNG images with a gamma are decoded with it, while all other images are decoded with the
+    /// sRGB transfer function. Radiance HDR and floating point TIFF images are already linear.
+    /// Alpha channels are always linear and are left as they are.
+    pub fn linear(mut self, linear: bool) -> Self {
+        self.linear = linear;
+        self
+    }
+
+    /// Decodes the image into a `(height, width, channels)` array of `u8`.
+    pub fn decode3(&self) -> NdImageResult<Array3<u8>> {
+        let image = self.decode(Target::u8(self.colors))?;
+        Ok(crate::decode_u8(image, self.colors))
+    }
+
+    /// Decodes the image into a `(height, width, channels)` array of `u16`.
+    pub fn decode3_u16(&self) -> NdImageResult<Array3<u16>> {
+        let image = self.decode(Target::u16(self.colors))?;
+        Ok(crate::decode_u16(image, self.colors))
+    }
+
+    /// Decodes the image into a `(height, width, channels)` array of `f32` in `0.0..=1.0`.
+    ///
+    /// Radiance HDR and floating point TIFF images keep their original values, which are already
+    /// linear light, like [`open_image_f32`](crate::open_image_f32).
+    pub fn decode3_f32(&self) -> NdImageResult<Array3<f32>> {
+        if let Some((image, native)) = self.decode_float()? {
+            return Ok(crate::float::convert_colors(image, native, self.colors));
+        }
+        // Other images are loaded into `u16` before they are converted to `f32`.
+        let image = self.decode(Target::Bytes(6 * self.colors.channel_count()))?;
+        // YCbCr is computed from the linear RGB values rather than decoded itself.
+        let decoded = match self.colors {
+            Colors::YCbCr => Colors::Rgb,
+            colors => colors,
+        };
+        let mut image =
+            crate::decode_u16(image, decoded).mapv(|v| f32::from(v) / f32::from(u16::MAX));
+        if self.linear {
+            Transfer::read(&self.data).decode(image.view_mut());
+        }
+        Ok(crate::float::convert_colors(image, decoded, self.colors))
+    }
+
+    /// Decodes the image into a `(height, width)` gray array of `u8`, ignoring the colors.
+    pub fn decode2(&self) -> NdImageResult<Array2<u8>> {
+        let image = self.decode(Target::Color(ColorType::L8))?;
+        Ok(crate::decode_gray_u8(image))
+    }
+
+    /// Decodes the image with every option applied, to load it into `target`.
+    fn decode(&self, target: Target) -> NdImageResult<DynamicImage> {
+        let reader = Reader::new(Cursor::new(&self.data)).with_guessed_format()?;
+        let orientation = self.orientation();
+        let image = match self.max_size {
+            // Orientations 5 to 8 rotate the image, which swaps its width and height.
+            Some((max_width, max_height)) => {
+                let (max_width, max_height) = match orientation {
+                    Some(5..=8) => (max_height, max_width),
+                    _ => (max_width, max_height),
+                };
+                decode_resized(reader, max_width, max_height, self.limits, target)?
+            }
+            None => crate::limits::decode(reader, self.limits, target)?,
+        };
+        Ok(match orientation {
+            Some(orientation) => exif::apply_orientation(image, orientation),
+            None => image,
+        })
+    }
+
+    /// Decodes a Radiance HDR or floating point TIFF image with every option applied, or gets
+    /// `None` for other images.
+    fn decode_float(&self) -> NdImageResult<Option<(Array3<f32>, Colors)>> {
+        let format = image::guess_format(&self.data).ok();
+        let target = Target::Bytes(4 * self.colors.channel_count());
+        let mut reader = Cursor::new(&self.data);
+        let (image, native) = match read_float(&mut reader, format, self.limits, target)? {
+            Some(float) => float,
+            None => return Ok(None),
+        };
+        let image = match self.orientation() {
+            Some(orientation) => exif::orient_array(image, orientation),
+            None => image,
+        };
+        let image = match self.max_size {
+            Some((max_width, max_height)) => resize_to_fit(image, max_width, max_height),
+            None => image,
+        };
+        Ok(Some((image, native)))
+    }
+
+    /// Gets the EXIF orientation of the image if it is oriented automatically.
+    fn orientation(&self) -> Option<u16> {
+        if self.auto_orient {
+            exif::read_exif(&self.data).and_then(|exif| exif.orientation)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use crate::limits::Target;
+use crate::{Colors, DecodeLimits, NdImageResult};
+use image::error::{ParameterError, ParameterErrorKind};
+use image::io::Reader;
+use image::{GenericImageView, ImageError};
+use ndarray::Array3;
+use std::path::Path;
+
+/// Opens the region of a color image starting at `x` and `y` with the given dimensions and loads
+/// it into a 3d array.
+///
+/// TIFF images are decoded one strip at a time, so only the requested rows are ever held in
+/// memory. Other formats are fully decoded before the region is cropped from them.
+/// This performs a copy.
+///
+/// Tiled TIFF images and TIFF images that store each channel in its own plane are not supported
+/// and give an unsupported error, as do TIFF images with other than 8 or 16 bits per sample.
+/// Strips that hold fewer rows than the image claims give a decoding error.
+pub fn open_image_region(
+    path: impl AsRef<Path>,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    colors: Colors,
+) -> NdImageResult<Array3<u8>> {
+    let reader = Reader::open(path)?.with_guessed_format()?;
+    let image = match reader.format() {
+        #[cfg(feature = "tiff")]
+        Some(image::ImageFormat::Tiff) => {
+            read_tiff_region(reader.into_inner(), x, y, width, height)?
+        }
+        _ => {
+            let image = crate::limits::decode(reader, DecodeLimits::global(), Target::Decoded)?;
+            check_region(image.dimensions(), x, y, width, height)?;
+            image.crop_imm(x, y, width, height)
+        }
+    };
+    Ok(crate::decode_u8(image, colors))
+}
+
+/// Checks that a region lies within an image with the given dimensions.
+fn check_region(
+    dimensions: (u32, u32),
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> NdImageResult<()> {
+    let fits =
+        |start: u32, len: u32, max: u32| start.checked_add(len).is_some_and(|end| end <= max);
+    if fits(x, width, dimensions.0) && fits(y, height, dimensions.1) {
+        Ok(())
+    } else {
+        Err(ImageError::Parameter(ParameterError::from_kind(
+            ParameterErrorKind::DimensionMismatch,
+        ))
+        .into())
+    }
+}
+
+/// Decodes a region of a TIFF image, keeping only the rows of each strip that overlap it.
+#[cfg(feature = "tiff")]
+fn read_tiff_region(
+    reader: impl std::io::Read + std::io::Seek,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> NdImageResult<image::DynamicImage> {
+    use crate::float::tiff_decoding_error;
+    use tiff::decoder::{Decoder, DecodingResult};
+    use tiff::tags::Tag;
+    use tiff::ColorType;
+
+    let mut decoder = Decoder::new(reader).map_err(tiff_decoding_error)?;
+    let dimensions = decoder.dimensions().map_err(tiff_decoding_error)?;
+    check_region(dimensions, x, y, width, height)?;
+    let limits = DecodeLimits::global();
+    limits.check_dimensions(dimensions.0, dimensions.1)?;
+    // Tiles would have to be decoded by hand, as the tiff crate only reads strips.
+    if decoder
+        .find_tag(Tag::TileWidth)
+        .map_err(tiff_decoding_error)?
+        .is_some()
+    {
+        return Err(unsupported_tiff("tiled images"));
+    }
+    let color = decoder.colortype().map_err(tiff_decoding_error)?;
+    let rows_per_strip = decoder
+        .get_tag_u32(Tag::RowsPerStrip)
+        .unwrap_or(dimensions.1)
+        .max(1) as usize;
+    let (channels, bits) = match color {
+        ColorType::Gray(bits) => (1, bits),
+        ColorType::GrayA(bits) => (2, bits),
+        ColorType::RGB(bits) => (3, bits),
+        ColorType::RGBA(bits) => (4, bits),
+        color => return Err(crate::tiff_stack::unsupported_color(color)),
+    };
+    let planar = decoder
+        .find_tag_unsigned::<u16>(Tag::PlanarConfiguration)
+        .map_err(tiff_decoding_error)?;
+    if channels > 1 && planar == Some(2) {
+        return Err(unsupported_tiff("planar samples"));
+    }
+    // Other depths pack several samples into a byte or decode to other types, so their strips
+    // cannot be sliced into rows of samples.
+    let mut region = match bits {
+        8 => DecodingResult::U8(Vec::new()),
+        16 => DecodingResult::U16(Vec::new()),
+        _ => return Err(crate::tiff_stack::unsupported_color(color)),
+    };
+    // Only the region is kept, along with the `u8` array 16-bit samples are converted to.
+    let sample_bytes = usize::from(bits / 8);
+    limits.check(
+        width,
+        height,
+        channels * (sample_bytes + usize::from(bits > 8)),
+    )?;
+    let (x, y, width, height) = (x as usize, y as usize, width as usize, height as usize);
+    let row_len = dimensions.0 as usize * channels;
+    let columns = (x * channels, (x + width) * channels);
+
+    let mut strip_start = 0;
+    while strip_start < y + height {
+        let strip = decoder.read_strip().map_err(tiff_decoding_error)?;
+        let rows = (
+            y.max(strip_start),
+            (y + height).min(strip_start + rows_per_strip),
+        );
+        if rows.0 < rows.1 {
+            match (&mut region, strip) {
+                (DecodingResult::U8(region), DecodingResult::U8(strip)) => {
+                    copy_rows(&strip, region, rows, strip_start, columns, row_len)?
+                }
+                (DecodingResult::U16(region), DecodingResult::U16(strip)) => {
+                    copy_rows(&strip, region, rows, strip_start, columns, row_len)?
+                }
+                _ => return Err(crate::tiff_stack::unsupported_color(color)),
+            }
+        }
+        strip_start += rows_per_strip;
+    }
+    crate::tiff_stack::samples_to_image(color, width as u32, height as u32, region)
+}
+
+/// Copies the samples of a region from the rows of a strip that overlap it.
+///
+/// Fails if the strip is too short to hold the rows.
+#[cfg(feature = "tiff")]
+fn copy_rows<T: Copy>(
+    strip: &[T],
+    region: &mut Vec<T>,
+    rows: (usize, usize),
+    strip_start: usize,
+    columns: (usize, usize),
+    row_len: usize,
+) -> NdImageResult<()> {
+    use image::error::DecodingError;
+
+    for row in rows.0..rows.1 {
+        let start = (row - strip_start) * row_len;
+        let samples = strip
+            .get(start + columns.0..start + columns.1)
+            .ok_or_else(|| {
+                ImageError::Decoding(DecodingError::new(
+                    image::ImageFormat::Tiff.into(),
+                    "strip is shorter than its rows",
+                ))
+            })?;
+        region.extend_from_slice(samples);
+    }
+    Ok(())
+}
+
+/// Gets the error for TIFF images with a layout that cannot be read a strip at a time.
+#[cfg(feature = "tiff")]
+fn unsupported_tiff(feature: &str) -> crate::NdImageError {
+    use image::error::{ImageFormatHint, UnsupportedError, UnsupportedErrorKind};
+
+    ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+        ImageFormatHint::Exact(image::ImageFormat::Tiff),
+        UnsupportedErrorKind::GenericFeature(format!("{} in regions", feature)),
+    ))
+    .into()
+}
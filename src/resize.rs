@@ -0,0 +1,210 @@
+use crate::limits::Target;
+use crate::num::saturate;
+use crate::{Colors, DecodeLimits, NdColor, NdImageResult};
+use image::imageops::FilterType;
+use image::io::Reader;
+use image::{DynamicImage, GenericImageView, Primitive};
+use ndarray::Array3;
+use std::io::{BufRead, Seek};
+use std::path::Path;
+
+/// The filter used to sample the pixels of a resized array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeFilter {
+    /// Takes the pixel closest to the center of each output pixel.
+    Nearest,
+    /// Interpolates linearly between neighbouring pixels.
+    Bilinear,
+    /// Windowed sinc with three lobes, which is sharper but can overshoot at edges.
+    Lanczos3,
+}
+
+impl ResizeFilter {
+    /// The radius of the filter in input pixels when scaling up.
+    fn support(self) -> f32 {
+        match self {
+            ResizeFilter::Nearest => 0.0,
+            ResizeFilter::Bilinear => 1.0,
+            ResizeFilter::Lanczos3 => 3.0,
+        }
+    }
+
+    /// The weight of a pixel at distance `x` from the sampled position.
+    fn weight(self, x: f32) -> f32 {
+        let sinc = |x: f32| {
+            if x == 0.0 {
+                1.0
+            } else {
+                let x = x * std::f32::consts::PI;
+                x.sin() / x
+            }
+        };
+        match self {
+            ResizeFilter::Nearest => 1.0,
+            ResizeFilter::Bilinear => (1.0 - x.abs()).max(0.0),
+            ResizeFilter::Lanczos3 if x.abs() < 3.0 => sinc(x) * sinc(x / 3.0),
+            ResizeFilter::Lanczos3 => 0.0,
+        }
+    }
+}
+
+/// Resizes a 3d array to `(height, width)` pixels, filtering every channel the same way.
+///
+/// This works on arrays of any layout without converting them to an image first. When scaling
+/// down, the filter is widened to cover all input pixels so that the result does not alias.
+/// Integer values are rounded and saturate at the bounds of `A`. Channels are filtered
+/// independently, so alpha should be premultiplied first to avoid dark fringes. Resizing an empty
+/// array gives zeros.
+pub fn resize<A: Primitive>(
+    image: NdColor<'_, A>,
+    (height, width): (usize, usize),
+    filter: ResizeFilter,
+) -> Array3<A> {
+    let (in_height, in_width, channels) = image.dim();
+    if in_height == 0 || in_width == 0 {
+        return Array3::zeros((height, width, channels));
+    }
+    let columns = taps(in_width, width, filter);
+    let mut rows = Array3::<f32>::zeros((in_height, width, channels));
+    for (src, mut dst) in image.outer_iter().zip(rows.outer_iter_mut()) {
+        for (x, (start, weights)) in columns.iter().enumerate() {
+            for c in 0..channels {
+                dst[[x, c]] = weights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, w)| w * src[[start + i, c]].to_f32().unwrap())
+                    .sum();
+            }
+        }
+    }
+    let mut out = Array3::zeros((height, width, channels));
+    for (mut dst, (start, weights)) in out.outer_iter_mut().zip(taps(in_height, height, filter)) {
+        for ((x, c), v) in dst.indexed_iter_mut() {
+            let sum: f32 = weights
+                .iter()
+                .enumerate()
+                .map(|(i, w)| w * rows[[start + i, x, c]])
+                .sum();
+            *v = saturate(f64::from(sum));
+        }
+    }
+    out
+}
+
+/// Gets the first input pixel and the normalized weights of the input pixels that make up each
+/// output pixel along an axis.
+fn taps(in_len: usize, out_len: usize, filter: ResizeFilter) -> Vec<(usize, Vec<f32>)> {
+    let scale = in_len as f32 / out_len as f32;
+    let filter_scale = scale.max(1.0);
+    let support = filter.support() * filter_scale;
+    (0..out_len)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * scale;
+            let nearest = (center as usize).min(in_len - 1);
+            if filter == ResizeFilter::Nearest {
+                return (nearest, vec![1.0]);
+            }
+            let start = (center - support).floor().max(0.0) as usize;
+            let end = ((center + support).ceil() as usize).min(in_len);
+            let mut weights: Vec<f32> = (start..end)
+                .map(|j| filter.weight((j as f32 + 0.5 - center) / filter_scale))
+                .collect();
+            let total: f32 = weights.iter().sum();
+            if total == 0.0 {
+                return (nearest, vec![1.0]);
+            }
+            weights.iter_mut().for_each(|w| *w /= total);
+            (start, weights)
+        })
+        .collect()
+}
+
+/// Opens a color image using the `image` crate and loads it into a 3d array, scaling it down to
+/// fit within `max_width` and `max_height` while keeping its aspect ratio.
+///
+/// JPEG images are scaled down while they are decoded, so large photos never have to be fully
+/// loaded into memory. Images that already fit are not scaled up.
+pub fn open_image_resized(
+    path: impl AsRef<Path>,
+    max_width: u32,
+    max_height: u32,
+    colors: Colors,
+) -> NdImageResult<Array3<u8>> {
+    let reader = Reader::open(path)?.with_guessed_format()?;
+    let limits = DecodeLimits::global();
+    let image = decode_resized(reader, max_width, max_height, limits, Target::u8(colors))?;
+    Ok(crate::decode_u8(image, colors))
+}
+
+/// Decodes an image within `limits` to load it into `target`, scaling it down to fit within
+/// `max_width` and `max_height`.
+///
+/// The dimensions the image is stored with are checked against `limits`, while the bytes are
+/// counted for JPEG images at the scale they are decoded with.
+pub(crate) fn decode_resized<R: BufRead + Seek>(
+    reader: Reader<R>,
+    max_width: u32,
+    max_height: u32,
+    limits: DecodeLimits,
+    target: Target,
+) -> NdImageResult<DynamicImage> {
+    let image = match reader.format() {
+        #[cfg(feature = "jpeg")]
+        Some(image::ImageFormat::Jpeg) => {
+            use image::codecs::jpeg::JpegDecoder;
+            use image::ImageDecoder;
+            use std::convert::TryFrom;
+
+            let mut decoder = JpegDecoder::new(reader.into_inner())?;
+            let (width, height) = decoder.dimensions();
+            limits.check_dimensions(width, height)?;
+            let (width, height) = fit_dimensions(width, height, max_width, max_height);
+            let to_u16 = |v| u16::try_from(v).unwrap_or(u16::MAX);
+            // The decoder picks the smallest scale that is at least as large as requested.
+            decoder.scale(to_u16(width), to_u16(height))?;
+            limits.check_decoder(&decoder, target)?;
+            DynamicImage::from_decoder(decoder)?
+        }
+        _ => crate::limits::decode(reader, limits, target)?,
+    };
+    Ok(
+        if image.width() > max_width || image.height() > max_height {
+            image.resize(max_width, max_height, FilterType::Triangle)
+        } else {
+            image
+        },
+    )
+}
+
+/// Scales a 3d array down to fit within `max_width` and `max_height` like [`decode_resized`].
+pub(crate) fn resize_to_fit<A: Primitive>(
+    image: Array3<A>,
+    max_width: u32,
+    max_height: u32,
+) -> Array3<A> {
+    let (height, width, _) = image.dim();
+    let (fit_width, fit_height) =
+        fit_dimensions(width as u32, height as u32, max_width, max_height);
+    if (fit_width as usize, fit_height as usize) == (width, height) {
+        return image;
+    }
+    resize(
+        image.view(),
+        (fit_height as usize, fit_width as usize),
+        ResizeFilter::Bilinear,
+    )
+}
+
+/// Gets the largest dimensions with the same aspect ratio that fit within the maximum dimensions,
+/// without scaling up.
+fn fit_dimensions(width: u32, height: u32, max_width: u32, max_height: u32) -> (u32, u32) {
+    if width <= max_width && height <= max_height {
+        return (width, height);
+    }
+    let scale = f64::min(
+        f64::from(max_width) / f64::from(width),
+        f64::from(max_height) / f64::from(height),
+    );
+    let scaled = |v: u32| ((f64::from(v) * scale).round() as u32).max(1);
+    (scaled(width), scaled(height))
+}
@@ -0,0 +1,125 @@
+use crate::limits::Target;
+use crate::{Colors, DecodeLimits, ImageBufferPool, NdImageResult};
+use image::io::Reader;
+use ndarray::Array3;
+use std::path::Path;
+
+/// Opens a color image using the `image` crate and loads it into an existing 3d array, which is
+/// only reallocated if its shape does not match the image.
+///
+/// When the image is stored with the requested colors, it is decoded directly into the array
+/// without any other allocation. This is the case with RGB JPEG, PNG, PNM, and TIFF images
+/// opened as [`Colors::Rgb`], for example. If decoding fails, the contents of the array are
+/// unspecified.
+pub fn open_image_into(
+    path: impl AsRef<Path>,
+    image: &mut Array3<u8>,
+    colors: Colors,
+) -> NdImageResult<()> {
+    open_into(path.as_ref(), image, colors, None)
+}
+
+/// Opens a color image using the `image` crate and loads it into an array taken from `pool`.
+///
+/// Like [`open_image_into`], the image is decoded directly into the array when it is stored with
+/// the requested colors. Return the array with [`ImageBufferPool::recycle_array`] once it is no
+/// longer needed so that the next image can reuse it.
+pub fn open_image_with_pool(
+    path: impl AsRef<Path>,
+    colors: Colors,
+    pool: &ImageBufferPool,
+) -> NdImageResult<Array3<u8>> {
+    let mut image = Array3::zeros((0, 0, colors.channel_count()));
+    open_into(path.as_ref(), &mut image, colors, Some(pool))?;
+    Ok(image)
+}
+
+/// Decodes an image into an existing array, reallocating it from `pool` if one is given.
+// The pool is unused when every codec with a direct decoder is disabled.
+#[allow(unused_variables)]
+fn open_into(
+    path: &Path,
+    image: &mut Array3<u8>,
+    colors: Colors,
+    pool: Option<&ImageBufferPool>,
+) -> NdImageResult<()> {
+    let reader = Reader::open(path)?.with_guessed_format()?;
+    let format = reader.format();
+    // The file is unused in the arms of disabled codecs.
+    #[allow(unused_variables)]
+    let file = reader.into_inner();
+    match format {
+        #[cfg(feature = "jpeg")]
+        Some(image::ImageFormat::Jpeg) => read_into(
+            image::codecs::jpeg::JpegDecoder::new(file)?,
+            image,
+            colors,
+            pool,
+        ),
+        #[cfg(feature = "png")]
+        Some(image::ImageFormat::Png) => read_into(
+            image::codecs::png::PngDecoder::new(file)?,
+            image,
+            colors,
+            pool,
+        ),
+        #[cfg(feature = "pnm")]
+        Some(image::ImageFormat::Pnm) => read_into(
+            image::codecs::pnm::PnmDecoder::new(file)?,
+            image,
+            colors,
+            pool,
+        ),
+        #[cfg(feature = "tiff")]
+        Some(image::ImageFormat::Tiff) => read_into(
+            image::codecs::tiff::TiffDecoder::new(file)?,
+            image,
+            colors,
+            pool,
+        ),
+        _ => {
+            let decoded = crate::limits::read(file, Target::u8(colors))?;
+            *image = crate::decode_u8(decoded, colors);
+            Ok(())
+        }
+    }
+}
+
+/// Decodes an image into an existing array, directly if the decoder produces the requested
+/// colors.
+// Unused when every codec with a direct decoder is disabled.
+#[allow(dead_code)]
+fn read_into<'a>(
+    decoder: impl image::ImageDecoder<'a>,
+    image: &mut Array3<u8>,
+    colors: Colors,
+    pool: Option<&ImageBufferPool>,
+) -> NdImageResult<()> {
+    use image::ColorType;
+
+    DecodeLimits::global().check_decoder(&decoder, Target::u8(colors))?;
+    let native = match decoder.color_type() {
+        ColorType::L8 => Some(Colors::Luma),
+        ColorType::La8 => Some(Colors::LumaA),
+        ColorType::Rgb8 => Some(Colors::Rgb),
+        ColorType::Rgba8 => Some(Colors::Rgba),
+        ColorType::Bgr8 => Some(Colors::Bgr),
+        ColorType::Bgra8 => Some(Colors::Bgra),
+        _ => None,
+    };
+    if native != Some(colors) {
+        let decoded = image::DynamicImage::from_decoder(decoder)?;
+        *image = crate::decode_u8(decoded, colors);
+        return Ok(());
+    }
+    let (width, height) = decoder.dimensions();
+    let shape = (height as usize, width as usize, colors.channel_count());
+    if image.dim() != shape || !image.is_standard_layout() {
+        *image = match pool {
+            Some(pool) => pool.take_array(shape),
+            None => Array3::zeros(shape),
+        };
+    }
+    decoder.read_image(image.as_slice_mut().unwrap())?;
+    Ok(())
+}
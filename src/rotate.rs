@@ -0,0 +1,45 @@
+use ndarray::{ArrayBase, Axis, Dimension, RawData};
+
+/// Rotates an image array a quarter turn clockwise without copying.
+///
+/// The first two axes of the array are its height and width. Like the other rotations and flips,
+/// this only changes the strides, so it works the same for views, mutable views, and owned arrays.
+/// Use `as_standard_layout` on the result to get a contiguous array. Panics if the array has
+/// fewer than two axes.
+pub fn rotate90<S: RawData, D: Dimension>(mut image: ArrayBase<S, D>) -> ArrayBase<S, D> {
+    image.swap_axes(0, 1);
+    image.invert_axis(Axis(1));
+    image
+}
+
+/// Rotates an image array half a turn without copying.
+///
+/// Panics if the array has fewer than two axes.
+pub fn rotate180<S: RawData, D: Dimension>(mut image: ArrayBase<S, D>) -> ArrayBase<S, D> {
+    image.invert_axis(Axis(0));
+    image.invert_axis(Axis(1));
+    image
+}
+
+/// Rotates an image array a quarter turn counterclockwise without copying.
+///
+/// Panics if the array has fewer than two axes.
+pub fn rotate270<S: RawData, D: Dimension>(mut image: ArrayBase<S, D>) -> ArrayBase<S, D> {
+    image.swap_axes(0, 1);
+    image.invert_axis(Axis(0));
+    image
+}
+
+/// Mirrors an image array left to right without copying.
+///
+/// Panics if the array has fewer than two axes.
+pub fn flip_h<S: RawData, D: Dimension>(mut image: ArrayBase<S, D>) -> ArrayBase<S, D> {
+    image.invert_axis(Axis(1));
+    image
+}
+
+/// Mirrors an image array top to bottom without copying.
+pub fn flip_v<S: RawData, D: Dimension>(mut image: ArrayBase<S, D>) -> ArrayBase<S, D> {
+    image.invert_axis(Axis(0));
+    image
+}
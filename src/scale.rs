@@ -0,0 +1,94 @@
+use crate::float::{convert_colors, open_float, Opened};
+use crate::limits::Target;
+use crate::num::saturate;
+use crate::{Colors, NdImageResult};
+use image::{DynamicImage, Primitive};
+use ndarray::{Array3, ArrayView3};
+use std::any::{Any, TypeId};
+use std::path::Path;
+
+/// How the samples of an image are mapped to values of the subpixel type of an array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    /// Scales the full range of the samples to `0..=1`.
+    ZeroToOne,
+    /// Scales the full range of the samples to `0..=255`.
+    ZeroTo255,
+    /// Casts the samples without scaling them, so 8-bit images are in `0..=255` and 16-bit images
+    /// in `0..=65535`.
+    Raw,
+}
+
+/// Opens an image using the `image` crate and loads it into a 3d array of any subpixel type,
+/// mapping its samples with `scale`.
+///
+/// Values are rounded when `A` is an integer type and saturate at its bounds. This does not copy
+/// if the image is stored as `colors` in the bit depth of `A` and `scale` keeps its samples as
+/// they are. When `A` is `f32`, Radiance HDR and floating point TIFF images are read without
+/// quantizing them, taking their values to be in `0.0..=1.0` for [`Scale::ZeroTo255`] and
+/// keeping them as they are otherwise.
+pub fn open_image_as<A: Primitive + 'static>(
+    path: impl AsRef<Path>,
+    colors: Colors,
+    scale: Scale,
+) -> NdImageResult<Array3<A>> {
+    let target = Target::Bytes(colors.channel_count() * std::mem::size_of::<A>());
+    if TypeId::of::<A>() != TypeId::of::<f32>() {
+        return Ok(decode_as(crate::limits::open(path, target)?, colors, scale));
+    }
+    match open_float(path, target)? {
+        Opened::Float(image, native) => {
+            let mut image = convert_colors(image, native, colors);
+            if scale == Scale::ZeroTo255 {
+                image.mapv_inplace(|v| v * 255.0);
+            }
+            let image: Box<dyn Any> = Box::new(image);
+            Ok(*image.downcast().unwrap())
+        }
+        Opened::Image(image) => Ok(decode_as(image, colors, scale)),
+    }
+}
+
+/// Converts a decoded image into a 3d array of `A` with scaled samples.
+pub(crate) fn decode_as<A: Primitive + 'static>(
+    image: DynamicImage,
+    colors: Colors,
+    scale: Scale,
+) -> Array3<A> {
+    let color = image.color();
+    let bit_depth = 8 * color.bytes_per_pixel() / color.channel_count();
+    if bit_depth > 8 {
+        convert(crate::decode_u16(image, colors), u16::MAX, scale)
+    } else {
+        convert(crate::decode_u8(image, colors), u8::MAX, scale)
+    }
+}
+
+/// Maps the samples of an array with the largest sample `max` to another subpixel type.
+fn convert<S, A>(image: Array3<S>, max: S, scale: Scale) -> Array3<A>
+where
+    S: Primitive + 'static,
+    A: Primitive + 'static,
+{
+    let max = max.to_f64().unwrap();
+    let target = match scale {
+        Scale::ZeroToOne => 1.0,
+        Scale::ZeroTo255 => 255.0,
+        Scale::Raw => max,
+    };
+    // Arrays that already have the right type and range are moved as they are.
+    if target == max && TypeId::of::<S>() == TypeId::of::<A>() {
+        let image: Box<dyn Any> = Box::new(image);
+        return *image.downcast().unwrap();
+    }
+    convert_values(image.view(), target / max)
+}
+
+/// Multiplies each sample by `factor`, rounding and saturating for integer types.
+pub(crate) fn convert_values<S, A>(image: ArrayView3<'_, S>, factor: f64) -> Array3<A>
+where
+    S: Primitive,
+    A: Primitive,
+{
+    image.mapv(|v| saturate(v.to_f64().unwrap() * factor))
+}
@@ -0,0 +1,142 @@
+use crate::{ConversionError, NdColor, NdColorMut, NdImageResult};
+use ndarray::{s, Array3, Zip};
+use std::any::Any;
+use std::convert::TryFrom;
+
+/// Swaps the red and blue channels of an RGB(A) or BGR(A) image in place.
+///
+/// This converts between RGB and BGR orders, such as for OpenCV. Contiguous arrays are shuffled
+/// 16 bytes at a time with SSSE3 when the CPU supports it, strided arrays fall back to swapping
+/// one pixel at a time.
+pub fn swap_rb(mut image: NdColorMut<'_, u8>) -> NdImageResult<()> {
+    let channels = image.dim().2;
+    if channels != 3 && channels != 4 {
+        return Err(ConversionError::WrongChannelCount {
+            expected: 3,
+            got: channels,
+        }
+        .into());
+    }
+    match image.as_slice_mut() {
+        Some(data) => {
+            let done = swap_rb_simd(data, channels);
+            let rest = &mut data[done..];
+            if channels == 3 {
+                rest.chunks_exact_mut(3).for_each(|pixel| pixel.swap(0, 2));
+            } else {
+                swap_rb4(rest);
+            }
+        }
+        None => {
+            let (red, blue) = image.multi_slice_mut((s![.., .., 0], s![.., .., 2]));
+            Zip::from(red).and(blue).for_each(std::mem::swap);
+        }
+    }
+    Ok(())
+}
+
+/// Copies an array into standard layout with its red and blue channels swapped.
+///
+/// This is how BGR(A) images are opened and saved, since the `image` crate only stores colors in
+/// RGB order. Fails if the array does not have `channels` channels.
+pub(crate) fn swapped_rb<A: Copy + 'static>(
+    image: NdColor<'_, A>,
+    channels: usize,
+) -> Result<Array3<A>, ConversionError> {
+    if image.dim().2 != channels {
+        return Err(ConversionError::WrongChannelCount {
+            expected: channels,
+            got: image.dim().2,
+        });
+    }
+    let mut image = image.as_standard_layout().into_owned();
+    swap_rb_owned(&mut image);
+    Ok(image)
+}
+
+/// Swaps the red and blue channels of an RGB(A) or BGR(A) array in standard layout in place.
+pub(crate) fn swap_rb_owned<A: 'static>(image: &mut Array3<A>) {
+    if let Some(image) = (image as &mut dyn Any).downcast_mut::<Array3<u8>>() {
+        swap_rb(image.view_mut()).unwrap();
+        return;
+    }
+    let channels = image.dim().2;
+    let data = image.as_slice_mut().unwrap();
+    data.chunks_exact_mut(channels)
+        .for_each(|pixel| pixel.swap(0, 2));
+}
+
+/// Swaps the channels of as many whole pixels as possible with SIMD shuffles, returning the
+/// number of bytes that were processed.
+#[cfg(target_arch = "x86_64")]
+fn swap_rb_simd(data: &mut [u8], channels: usize) -> usize {
+    if is_x86_feature_detected!("ssse3") {
+        // SAFETY: the CPU was just checked for SSSE3.
+        unsafe { swap_rb_ssse3(data, channels) }
+    } else {
+        0
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn swap_rb_simd(_data: &mut [u8], _channels: usize) -> usize {
+    0
+}
+
+/// Shuffles 16 pixels at a time with 3 channels or 4 pixels at a time with 4 channels.
+///
+/// Pixels with 3 channels cross the 16 byte lanes, so each output lane combines shuffles of the
+/// neighbouring input lanes, with the other bytes zeroed by a set high mask bit.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+unsafe fn swap_rb_ssse3(data: &mut [u8], channels: usize) -> usize {
+    use std::arch::x86_64::{
+        __m128i, _mm_loadu_si128, _mm_or_si128, _mm_setr_epi8, _mm_shuffle_epi8, _mm_storeu_si128,
+    };
+
+    const Z: i8 = -128;
+    let ptr = data.as_mut_ptr() as *mut __m128i;
+    if channels == 3 {
+        let a_a = _mm_setr_epi8(2, 1, 0, 5, 4, 3, 8, 7, 6, 11, 10, 9, 14, 13, 12, Z);
+        let a_b = _mm_setr_epi8(Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, 1);
+        let b_a = _mm_setr_epi8(Z, 15, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z);
+        let b_b = _mm_setr_epi8(0, Z, 4, 3, 2, 7, 6, 5, 10, 9, 8, 13, 12, 11, Z, 15);
+        let b_c = _mm_setr_epi8(Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, 0, Z);
+        let c_b = _mm_setr_epi8(14, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z, Z);
+        let c_c = _mm_setr_epi8(Z, 3, 2, 1, 6, 5, 4, 9, 8, 7, 12, 11, 10, 15, 14, 13);
+        let blocks = data.len() / 48;
+        for block in 0..blocks {
+            let ptr = ptr.add(block * 3);
+            let a = _mm_loadu_si128(ptr);
+            let b = _mm_loadu_si128(ptr.add(1));
+            let c = _mm_loadu_si128(ptr.add(2));
+            let out_a = _mm_or_si128(_mm_shuffle_epi8(a, a_a), _mm_shuffle_epi8(b, a_b));
+            let out_b = _mm_or_si128(
+                _mm_or_si128(_mm_shuffle_epi8(a, b_a), _mm_shuffle_epi8(b, b_b)),
+                _mm_shuffle_epi8(c, b_c),
+            );
+            let out_c = _mm_or_si128(_mm_shuffle_epi8(b, c_b), _mm_shuffle_epi8(c, c_c));
+            _mm_storeu_si128(ptr, out_a);
+            _mm_storeu_si128(ptr.add(1), out_b);
+            _mm_storeu_si128(ptr.add(2), out_c);
+        }
+        blocks * 48
+    } else {
+        let mask = _mm_setr_epi8(2, 1, 0, 3, 6, 5, 4, 7, 10, 9, 8, 11, 14, 13, 12, 15);
+        let blocks = data.len() / 16;
+        for block in 0..blocks {
+            let ptr = ptr.add(block);
+            _mm_storeu_si128(ptr, _mm_shuffle_epi8(_mm_loadu_si128(ptr), mask));
+        }
+        blocks * 16
+    }
+}
+
+/// Swaps the first and third channel of packed 4 channel pixels by masking each pixel as a word.
+fn swap_rb4(data: &mut [u8]) {
+    for pixel in data.chunks_exact_mut(4) {
+        let p = u32::from_le_bytes(<[u8; 4]>::try_from(&*pixel).unwrap());
+        let p = (p & 0xff00_ff00) | ((p >> 16) & 0xff) | ((p & 0xff) << 16);
+        pixel.copy_from_slice(&p.to_le_bytes());
+    }
+}
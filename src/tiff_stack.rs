@@ -0,0 +1,181 @@
+use crate::{Colors, DecodeLimits, NdImageError, NdImageResult};
+use image::error::{DecodingError, ImageFormatHint, UnsupportedError, UnsupportedErrorKind};
+use image::{DynamicImage, ImageBuffer, ImageError, ImageFormat};
+use ndarray::{Array3, Array4, ArrayView4};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::encoder::{colortype, TiffEncoder};
+use tiff::ColorType;
+
+/// Opens every page of a multi-page TIFF image and stacks them into a
+/// `(pages, height, width, channels)` array.
+///
+/// Every page must have the same dimensions.
+pub fn open_tiff_stack(path: impl AsRef<Path>, colors: Colors) -> NdImageResult<Array4<u8>> {
+    open_stack(path.as_ref(), colors, crate::decode_u8)
+}
+
+/// Opens every page of a multi-page TIFF image and stacks them into a
+/// `(pages, height, width, channels)` array of `u16`.
+///
+/// Every page must have the same dimensions.
+pub fn open_tiff_stack_u16(path: impl AsRef<Path>, colors: Colors) -> NdImageResult<Array4<u16>> {
+    open_stack(path.as_ref(), colors, crate::decode_u16)
+}
+
+/// Saves every image of a `(pages, height, width, channels)` array as a page of one TIFF image.
+pub fn save_tiff_stack(
+    path: impl AsRef<Path>,
+    stack: ArrayView4<'_, u8>,
+    colors: Colors,
+) -> NdImageResult<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = TiffEncoder::new(file).map_err(crate::float::tiff_encoding_error)?;
+    for page in stack.outer_iter() {
+        let page = crate::encode::to_dynamic_image(page, colors)?;
+        let (width, height) = image::GenericImageView::dimensions(&page);
+        match page {
+            DynamicImage::ImageLuma8(page) => {
+                encoder.write_image::<colortype::Gray8>(width, height, &page)
+            }
+            DynamicImage::ImageRgb8(page) => {
+                encoder.write_image::<colortype::RGB8>(width, height, &page)
+            }
+            // TIFF has no gray image with alpha, so it is stored as RGBA.
+            page => encoder.write_image::<colortype::RGBA8>(width, height, &page.into_rgba8()),
+        }
+        .map_err(crate::float::tiff_encoding_error)?;
+    }
+    Ok(())
+}
+
+/// Decodes every page of a TIFF image and stacks them after converting each page with `decode`.
+fn open_stack<A>(
+    path: &Path,
+    colors: Colors,
+    decode: fn(DynamicImage, Colors) -> Array3<A>,
+) -> NdImageResult<Array4<A>>
+where
+    A: image::Primitive,
+{
+    let file = BufReader::new(File::open(path)?);
+    let mut decoder = Decoder::new(file).map_err(crate::float::tiff_decoding_error)?;
+    let limits = DecodeLimits::global();
+    // Each page is converted into an array and then copied into the stack.
+    let pixel_bytes = 2 * colors.channel_count() * std::mem::size_of::<A>();
+    let mut loaded = 0;
+    let mut pages = Vec::new();
+    loop {
+        let page = read_page(&mut decoder, limits, &mut loaded, pixel_bytes)?;
+        pages.push(decode(page, colors));
+        if !decoder.more_images() {
+            break;
+        }
+        decoder
+            .next_image()
+            .map_err(crate::float::tiff_decoding_error)?;
+    }
+
+    let (height, width, channels) = pages[0].dim();
+    let mut stack = Array4::zeros((pages.len(), height, width, channels));
+    for (mut slot, page) in stack.outer_iter_mut().zip(&pages) {
+        let (page_height, page_width, _) = page.dim();
+        if (page_height, page_width) != (height, width) {
+            return Err(NdImageError::DimensionMismatch {
+                path: path.to_owned(),
+                expected: (height, width),
+                got: (page_height, page_width),
+            });
+        }
+        slot.assign(page);
+    }
+    Ok(stack)
+}
+
+/// Decodes the current page of a TIFF image into a `DynamicImage`.
+///
+/// The page is checked against `limits` first, counting the bytes already `loaded` and the
+/// `pixel_bytes` per pixel of the arrays it is loaded into, which are then added to `loaded`.
+fn read_page(
+    decoder: &mut Decoder<BufReader<File>>,
+    limits: DecodeLimits,
+    loaded: &mut u64,
+    pixel_bytes: usize,
+) -> NdImageResult<DynamicImage> {
+    let (width, height) = decoder
+        .dimensions()
+        .map_err(crate::float::tiff_decoding_error)?;
+    let color = decoder
+        .colortype()
+        .map_err(crate::float::tiff_decoding_error)?;
+    limits.check_dimensions(width, height)?;
+    let (channels, bits) = match color {
+        ColorType::Gray(bits) => (1, bits),
+        ColorType::GrayA(bits) => (2, bits),
+        ColorType::RGB(bits) => (3, bits),
+        ColorType::RGBA(bits) => (4, bits),
+        color => return Err(unsupported_color(color)),
+    };
+    let pixels = u64::from(width) * u64::from(height);
+    let decoded = pixels.saturating_mul((channels * u64::from(bits)).div_ceil(8));
+    *loaded = loaded.saturating_add(pixels.saturating_mul(pixel_bytes as u64));
+    limits.check_bytes(Some(loaded.saturating_add(decoded)))?;
+    let raw = decoder
+        .read_image()
+        .map_err(crate::float::tiff_decoding_error)?;
+    samples_to_image(color, width, height, raw)
+}
+
+/// Gets the error for TIFF images with colors that cannot be loaded.
+pub(crate) fn unsupported_color(color: ColorType) -> NdImageError {
+    ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+        ImageFormatHint::Exact(ImageFormat::Tiff),
+        UnsupportedErrorKind::GenericFeature(format!("{:?} pages", color)),
+    ))
+    .into()
+}
+
+/// Puts the decoded samples of a TIFF image into a `DynamicImage`.
+pub(crate) fn samples_to_image(
+    color: ColorType,
+    width: u32,
+    height: u32,
+    raw: DecodingResult,
+) -> NdImageResult<DynamicImage> {
+    let image = match (color, raw) {
+        (ColorType::Gray(8), DecodingResult::U8(raw)) => {
+            ImageBuffer::from_raw(width, height, raw).map(DynamicImage::ImageLuma8)
+        }
+        (ColorType::GrayA(8), DecodingResult::U8(raw)) => {
+            ImageBuffer::from_raw(width, height, raw).map(DynamicImage::ImageLumaA8)
+        }
+        (ColorType::RGB(8), DecodingResult::U8(raw)) => {
+            ImageBuffer::from_raw(width, height, raw).map(DynamicImage::ImageRgb8)
+        }
+        (ColorType::RGBA(8), DecodingResult::U8(raw)) => {
+            ImageBuffer::from_raw(width, height, raw).map(DynamicImage::ImageRgba8)
+        }
+        (ColorType::Gray(16), DecodingResult::U16(raw)) => {
+            ImageBuffer::from_raw(width, height, raw).map(DynamicImage::ImageLuma16)
+        }
+        (ColorType::GrayA(16), DecodingResult::U16(raw)) => {
+            ImageBuffer::from_raw(width, height, raw).map(DynamicImage::ImageLumaA16)
+        }
+        (ColorType::RGB(16), DecodingResult::U16(raw)) => {
+            ImageBuffer::from_raw(width, height, raw).map(DynamicImage::ImageRgb16)
+        }
+        (ColorType::RGBA(16), DecodingResult::U16(raw)) => {
+            ImageBuffer::from_raw(width, height, raw).map(DynamicImage::ImageRgba16)
+        }
+        (color, _) => return Err(unsupported_color(color)),
+    };
+    image.ok_or_else(|| {
+        ImageError::Decoding(DecodingError::new(
+            ImageFormat::Tiff.into(),
+            "page is smaller than its dimensions",
+        ))
+        .into()
+    })
+}
@@ -0,0 +1,151 @@
+use crate::NdColor;
+use ndarray::{s, Array2, Array3, Axis};
+
+/// An iterator over overlapping tiles of a `(height, width, channels)` array.
+///
+/// Tiles are yielded in row-major order along with the `(y, x)` position of their top left corner.
+/// Every tile has the chosen size unless the image is smaller than it. The last row and column of
+/// tiles are moved back to end at the image border, so they may overlap their neighbours by more
+/// than requested.
+#[derive(Debug, Clone)]
+pub struct Tiles<'a, A> {
+    image: NdColor<'a, A>,
+    size: (usize, usize),
+    ys: Vec<usize>,
+    xs: Vec<usize>,
+    next: usize,
+}
+
+impl<'a, A> Tiles<'a, A> {
+    /// Splits `image` into tiles of `(height, width)` that overlap their neighbours by `overlap`
+    /// pixels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `overlap` is not smaller than both dimensions of the tiles.
+    pub fn new(image: NdColor<'a, A>, (height, width): (usize, usize), overlap: usize) -> Self {
+        assert!(
+            overlap < height && overlap < width,
+            "tile overlap must be smaller than the tiles"
+        );
+        let (image_height, image_width, _) = image.dim();
+        Tiles {
+            ys: tile_starts(image_height, height, overlap),
+            xs: tile_starts(image_width, width, overlap),
+            size: (height.min(image_height), width.min(image_width)),
+            image,
+            next: 0,
+        }
+    }
+}
+
+impl<'a, A> Iterator for Tiles<'a, A> {
+    type Item = (NdColor<'a, A>, (usize, usize));
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.ys.len() * self.xs.len() {
+            return None;
+        }
+        let y = self.ys[self.next / self.xs.len()];
+        let x = self.xs[self.next % self.xs.len()];
+        self.next += 1;
+        let (height, width) = self.size;
+        let tile = self.image.slice_move(s![y..y + height, x..x + width, ..]);
+        Some((tile, (y, x)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.ys.len() * self.xs.len() - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, A> ExactSizeIterator for Tiles<'a, A> {}
+
+/// The start of each tile along an axis of length `len`.
+fn tile_starts(len: usize, tile: usize, overlap: usize) -> Vec<usize> {
+    if len == 0 {
+        return Vec::new();
+    }
+    if len <= tile {
+        return vec![0];
+    }
+    let mut starts: Vec<usize> = (0..len - tile).step_by(tile - overlap).collect();
+    starts.push(len - tile);
+    starts
+}
+
+/// Stitches processed tiles back into a single `(height, width, channels)` array.
+///
+/// Where tiles overlap, their pixels are blended with weights that fall off linearly over the
+/// `overlap` pixels at the edges of each tile, which hides the seams between them.
+#[derive(Debug, Clone)]
+pub struct TileStitcher {
+    sums: Array3<f32>,
+    weights: Array2<f32>,
+    overlap: usize,
+}
+
+impl TileStitcher {
+    /// Creates a stitcher for an image of `(height, width, channels)` made of tiles that overlap
+    /// by `overlap` pixels.
+    pub fn new((height, width, channels): (usize, usize, usize), overlap: usize) -> Self {
+        TileStitcher {
+            sums: Array3::zeros((height, width, channels)),
+            weights: Array2::zeros((height, width)),
+            overlap,
+        }
+    }
+
+    /// Adds a tile with its top left corner at `(y, x)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile does not fit in the image or has the wrong number of channels.
+    pub fn add(&mut self, tile: NdColor<'_, f32>, (y, x): (usize, usize)) {
+        let (height, width, _) = tile.dim();
+        let mut sums = self.sums.slice_mut(s![y..y + height, x..x + width, ..]);
+        let mut weights = self.weights.slice_mut(s![y..y + height, x..x + width]);
+        for (row, ((mut sums, mut weights), tile)) in sums
+            .outer_iter_mut()
+            .zip(weights.outer_iter_mut())
+            .zip(tile.outer_iter())
+            .enumerate()
+        {
+            let row_weight = ramp(row, height, self.overlap);
+            for (column, ((mut sums, weight), tile)) in sums
+                .outer_iter_mut()
+                .zip(weights.iter_mut())
+                .zip(tile.outer_iter())
+                .enumerate()
+            {
+                let pixel_weight = row_weight * ramp(column, width, self.overlap);
+                sums.scaled_add(pixel_weight, &tile);
+                *weight += pixel_weight;
+            }
+        }
+    }
+
+    /// Blends the added tiles into the stitched image.
+    ///
+    /// Pixels that no tile covered are zero.
+    pub fn finish(self) -> Array3<f32> {
+        let mut image = self.sums;
+        for (mut pixel, &weight) in image
+            .lanes_mut(Axis(2))
+            .into_iter()
+            .zip(self.weights.iter())
+        {
+            if weight > 0.0 {
+                pixel.mapv_inplace(|value| value / weight);
+            }
+        }
+        image
+    }
+}
+
+/// The blending weight of pixel `index` along a tile axis of length `len`.
+fn ramp(index: usize, len: usize, overlap: usize) -> f32 {
+    let distance = (index + 1).min(len - index).min(overlap + 1);
+    distance as f32 / (overlap + 1) as f32
+}
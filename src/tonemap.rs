@@ -0,0 +1,57 @@
+use crate::linear::encode_srgb;
+use crate::NdColor;
+use ndarray::{Array3, Axis, Zip};
+
+/// The curve that compresses linear light of any brightness into `0.0..=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToneMapOperator {
+    /// Maps each value `x` to `x / (1 + x)`.
+    Reinhard,
+    /// The fit of the ACES filmic curve by Krzysztof Narkowicz.
+    Aces,
+    /// The filmic curve by John Hable from Uncharted 2, with a white point of `11.2`.
+    Filmic,
+}
+
+impl ToneMapOperator {
+    fn apply(self, x: f32) -> f32 {
+        match self {
+            ToneMapOperator::Reinhard => x / (1.0 + x),
+            ToneMapOperator::Aces => (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14),
+            ToneMapOperator::Filmic => hable(2.0 * x) / hable(11.2),
+        }
+    }
+}
+
+/// Tone maps a 3d array of linear light `f32` values, such as a loaded HDR image, into a 3d array
+/// of `u8` sRGB values.
+///
+/// The values are scaled by `2.0.powf(exposure)` before the curve is applied, so `exposure` is
+/// in stops. The last channel of arrays with 2 or 4 channels is treated as alpha and only
+/// clamped.
+pub fn tonemap(image: NdColor<'_, f32>, operator: ToneMapOperator, exposure: f32) -> Array3<u8> {
+    let scale = exposure.exp2();
+    let channels = image.dim().2;
+    let alpha = matches!(channels, 2 | 4);
+    let mut out = Array3::zeros(image.dim());
+    Zip::from(out.lanes_mut(Axis(2)))
+        .and(image.lanes(Axis(2)))
+        .for_each(|mut out, pixel| {
+            for (c, (out, &v)) in out.iter_mut().zip(pixel).enumerate() {
+                let v = if alpha && c == channels - 1 {
+                    v
+                } else {
+                    // Values are capped at the largest half float, so every curve is finite.
+                    encode_srgb(operator.apply((v * scale).clamp(0.0, 65504.0)))
+                };
+                *out = (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        });
+    out
+}
+
+/// The curve of [`ToneMapOperator::Filmic`] before it is normalized by its white point.
+fn hable(x: f32) -> f32 {
+    let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+    ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f
+}
@@ -0,0 +1,156 @@
+use crate::{
+    Colors, ConversionError, IntoNdColor, NdColor, NdColorMut, NdImageResult, ToImageBuffer,
+};
+use image::buffer::ConvertBuffer;
+use image::{ImageBuffer, LumaA, Pixel, Primitive, Rgb, Rgba};
+use ndarray::Array3;
+use std::convert::TryFrom;
+use std::ops::Deref;
+use std::path::Path;
+
+/// Defines an owned array newtype that always holds `(height, width, channels)` pixels of one
+/// pixel type.
+macro_rules! typed_image {
+    ($name:ident, $pixel:ident, $colors:expr, $description:literal) => {
+        #[doc = concat!("An owned `(height, width, channels)` array of ", $description, " pixels.")]
+        ///
+        /// The array always has the right number of channels and dimensions that fit in an
+        /// image. It dereferences to the array, but can only be mutated through a view so that
+        /// its shape cannot change.
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct $name<A = u8>(Array3<A>);
+
+        impl<A: Primitive + 'static> $name<A> {
+            /// Wraps an array, failing if its shape does not fit the pixel type.
+            pub fn new(image: Array3<A>) -> Result<Self, ConversionError> {
+                check_shape(&image, $colors)?;
+                Ok($name(image))
+            }
+
+            /// Unwraps the array.
+            pub fn into_inner(self) -> Array3<A> {
+                self.0
+            }
+
+            /// Views the pixels as a 3d array.
+            pub fn view(&self) -> NdColor<'_, A> {
+                self.0.view()
+            }
+
+            /// Mutably views the pixels as a 3d array.
+            pub fn view_mut(&mut self) -> NdColorMut<'_, A> {
+                self.0.view_mut()
+            }
+
+            /// Views the pixels as an image without copying.
+            ///
+            /// Fails if the array is not contiguous.
+            pub fn to_image(&self) -> Result<ImageBuffer<$pixel<A>, &[A]>, ConversionError> {
+                self.0.view().into_image_buffer()
+            }
+        }
+
+        impl $name<u8> {
+            /// Opens an image using the `image` crate, converting it to these colors.
+            pub fn open(path: impl AsRef<Path>) -> NdImageResult<Self> {
+                Ok($name(crate::open_image(path, $colors)?))
+            }
+
+            /// Saves the image using the `image` crate.
+            ///
+            /// The format is guessed from the extension of the path.
+            pub fn save(&self, path: impl AsRef<Path>) -> NdImageResult<()> {
+                crate::save_image(path, self.view(), $colors)
+            }
+        }
+
+        impl<A> Deref for $name<A> {
+            type Target = Array3<A>;
+
+            fn deref(&self) -> &Array3<A> {
+                &self.0
+            }
+        }
+
+        impl<A: Primitive + 'static> TryFrom<Array3<A>> for $name<A> {
+            type Error = ConversionError;
+
+            fn try_from(image: Array3<A>) -> Result<Self, Self::Error> {
+                $name::new(image)
+            }
+        }
+
+        /// This reuses the allocation of the image buffer and does not copy.
+        impl<A: Primitive + 'static> From<ImageBuffer<$pixel<A>, Vec<A>>> for $name<A> {
+            fn from(image: ImageBuffer<$pixel<A>, Vec<A>>) -> Self {
+                $name(image.into_nd_color())
+            }
+        }
+
+        /// This only copies if the array is not in standard layout.
+        impl<A: Primitive + 'static> From<$name<A>> for ImageBuffer<$pixel<A>, Vec<A>> {
+            fn from(image: $name<A>) -> Self {
+                // The shape was checked when the array was wrapped.
+                image.0.into_image_buffer().unwrap()
+            }
+        }
+    };
+}
+
+typed_image!(NdRgb, Rgb, Colors::Rgb, "RGB");
+typed_image!(NdRgba, Rgba, Colors::Rgba, "RGBA");
+typed_image!(NdGrayAlpha, LumaA, Colors::LumaA, "gray and alpha");
+
+/// Converts between the pixel types of two newtypes with the conversions of the `image` crate.
+macro_rules! typed_conversion {
+    ($from:ident, $from_pixel:ident, $to:ident, $to_pixel:ident) => {
+        /// Added alpha channels are opaque at the maximum value of `A`, like in the `image` crate.
+        impl<A: Primitive + 'static> From<$from<A>> for $to<A>
+        where
+            for<'a> ImageBuffer<$from_pixel<A>, &'a [A]>:
+                ConvertBuffer<ImageBuffer<$to_pixel<A>, Vec<A>>>,
+        {
+            fn from(image: $from<A>) -> Self {
+                $to::from(convert::<$from_pixel<A>, $to_pixel<A>, A>(image.0))
+            }
+        }
+    };
+}
+
+typed_conversion!(NdRgb, Rgb, NdRgba, Rgba);
+typed_conversion!(NdRgb, Rgb, NdGrayAlpha, LumaA);
+typed_conversion!(NdRgba, Rgba, NdRgb, Rgb);
+typed_conversion!(NdRgba, Rgba, NdGrayAlpha, LumaA);
+typed_conversion!(NdGrayAlpha, LumaA, NdRgb, Rgb);
+typed_conversion!(NdGrayAlpha, LumaA, NdRgba, Rgba);
+
+/// Converts the pixels of a checked array to another pixel type.
+fn convert<P, Q, A>(image: Array3<A>) -> ImageBuffer<Q, Vec<A>>
+where
+    P: Pixel<Subpixel = A> + 'static,
+    Q: Pixel<Subpixel = A> + 'static,
+    A: Primitive + 'static,
+    for<'a> ImageBuffer<P, &'a [A]>: ConvertBuffer<ImageBuffer<Q, Vec<A>>>,
+{
+    let image = image.as_standard_layout();
+    // The shape was checked when the array was wrapped and the layout is now standard.
+    let image: ImageBuffer<P, &[A]> = image.view().into_image_buffer().unwrap();
+    image.convert()
+}
+
+/// Checks that an array has the channels of `colors` and dimensions that fit in an image.
+fn check_shape<A>(image: &Array3<A>, colors: Colors) -> Result<(), ConversionError> {
+    let (height, width, channels) = image.dim();
+    if channels != colors.channel_count() {
+        return Err(ConversionError::WrongChannelCount {
+            expected: colors.channel_count(),
+            got: channels,
+        });
+    }
+    if u32::try_from(height).is_err() || u32::try_from(width).is_err() {
+        return Err(ConversionError::BadShape {
+            shape: image.shape().to_vec(),
+        });
+    }
+    Ok(())
+}
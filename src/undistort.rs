@@ -0,0 +1,77 @@
+use crate::{remap, BorderMode, Interpolation, NdColor};
+use image::Primitive;
+use ndarray::{Array2, Array3};
+
+/// The pinhole parameters of a camera in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraIntrinsics {
+    /// The horizontal focal length.
+    pub fx: f32,
+    /// The vertical focal length.
+    pub fy: f32,
+    /// The column of the principal point.
+    pub cx: f32,
+    /// The row of the principal point.
+    pub cy: f32,
+}
+
+/// The radial `k` and tangential `p` lens distortion coefficients in the order used by OpenCV.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DistortionCoeffs {
+    pub k1: f32,
+    pub k2: f32,
+    pub p1: f32,
+    pub p2: f32,
+    pub k3: f32,
+}
+
+/// Computes the `(map_x, map_y)` maps for [`remap`] that undistort images of `(height, width)`
+/// pixels taken by a camera.
+///
+/// Each position of the maps is where the lens projected the pixel at that position of an ideal
+/// pinhole camera with the same intrinsics, like OpenCV's `initUndistortRectifyMap` without a
+/// rectification. The maps only depend on the camera, so they can be computed once and reused for
+/// every frame.
+pub fn undistort_maps(
+    intrinsics: &CameraIntrinsics,
+    distortion: &DistortionCoeffs,
+    (height, width): (usize, usize),
+) -> (Array2<f32>, Array2<f32>) {
+    let CameraIntrinsics { fx, fy, cx, cy } = *intrinsics;
+    let DistortionCoeffs { k1, k2, p1, p2, k3 } = *distortion;
+    let mut map_x = Array2::zeros((height, width));
+    let mut map_y = Array2::zeros((height, width));
+    for (((row, column), mx), my) in map_x.indexed_iter_mut().zip(map_y.iter_mut()) {
+        let x = (column as f32 - cx) / fx;
+        let y = (row as f32 - cy) / fy;
+        let r2 = x * x + y * y;
+        let radial = 1.0 + r2 * (k1 + r2 * (k2 + r2 * k3));
+        let xd = x * radial + 2.0 * p1 * x * y + p2 * (r2 + 2.0 * x * x);
+        let yd = y * radial + p1 * (r2 + 2.0 * y * y) + 2.0 * p2 * x * y;
+        *mx = fx * xd + cx;
+        *my = fy * yd + cy;
+    }
+    (map_x, map_y)
+}
+
+/// Undistorts a 3d array taken by a camera with lens distortion.
+///
+/// The array is bilinearly sampled with the maps from [`undistort_maps`], and pixels that were
+/// not seen by the camera are zero. Compute the maps once and use [`remap`] instead when
+/// undistorting many frames.
+pub fn undistort<A: Primitive>(
+    image: NdColor<'_, A>,
+    intrinsics: &CameraIntrinsics,
+    distortion: &DistortionCoeffs,
+) -> Array3<A> {
+    let (height, width, _) = image.dim();
+    let (map_x, map_y) = undistort_maps(intrinsics, distortion, (height, width));
+    remap(
+        image,
+        map_x.view(),
+        map_y.view(),
+        Interpolation::Bilinear,
+        BorderMode::Constant(A::zero()),
+    )
+    .unwrap()
+}
@@ -0,0 +1,174 @@
+use crate::num::saturate;
+use crate::{BorderMode, ConversionError, NdColor, NdGray};
+use image::Primitive;
+use ndarray::Array3;
+
+/// How values between pixels are sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Takes the closest pixel.
+    Nearest,
+    /// Interpolates linearly between the four closest pixels.
+    Bilinear,
+}
+
+/// Warps a 3d array with an affine transformation into a new array of `(height, width)` pixels.
+///
+/// The `[[a, b, c], [d, e, f]]` matrix maps the `(x, y)` column and row of each input pixel to
+/// `(a * x + b * y + c, d * x + e * y + f)` in the output, like OpenCV's `warpAffine`. Each output
+/// pixel is sampled at the inverse of the transformation, and positions outside the input are
+/// filled in according to `border`. Integer values are rounded and saturate at the bounds of `A`.
+/// Panics if the matrix cannot be inverted, or if the input is empty and the border is not
+/// constant.
+pub fn warp_affine<A: Primitive>(
+    image: NdColor<'_, A>,
+    matrix: &[[f32; 3]; 2],
+    (height, width): (usize, usize),
+    interpolation: Interpolation,
+    border: BorderMode<A>,
+) -> Array3<A> {
+    let [[a, b, c], [d, e, f]] = *matrix;
+    let det = a * e - b * d;
+    assert!(
+        det != 0.0 && det.is_finite(),
+        "affine matrix {:?} cannot be inverted",
+        matrix
+    );
+    let inverse = [
+        [e / det, -b / det, (b * f - c * e) / det],
+        [-d / det, a / det, (c * d - a * f) / det],
+    ];
+    warp(image, (height, width), interpolation, border, |x, y| {
+        let (x, y) = (x as f32, y as f32);
+        let sx = inverse[0][0] * x + inverse[0][1] * y + inverse[0][2];
+        let sy = inverse[1][0] * x + inverse[1][1] * y + inverse[1][2];
+        (sx, sy)
+    })
+}
+
+/// Warps a 3d array with a perspective transformation into a new array of `(height, width)`
+/// pixels.
+///
+/// The 3x3 homography maps the `(x, y, 1)` column and row of each input pixel to homogeneous
+/// output coordinates, like OpenCV's `warpPerspective`. Output pixels are sampled like in
+/// [`warp_affine`], and those whose inverse lies on the line at infinity are filled with the
+/// constant border, or zero for other borders. Panics if the homography cannot be inverted, or if
+/// the input is empty and the border is not constant.
+pub fn warp_perspective<A: Primitive>(
+    image: NdColor<'_, A>,
+    matrix: &[[f32; 3]; 3],
+    (height, width): (usize, usize),
+    interpolation: Interpolation,
+    border: BorderMode<A>,
+) -> Array3<A> {
+    let m = matrix;
+    // The adjugate is the inverse up to a scale, which the homogeneous division cancels out.
+    let cofactor =
+        |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    let inverse = [
+        [
+            cofactor(1, 2, 1, 2),
+            -cofactor(0, 2, 1, 2),
+            cofactor(0, 1, 1, 2),
+        ],
+        [
+            -cofactor(1, 2, 0, 2),
+            cofactor(0, 2, 0, 2),
+            -cofactor(0, 1, 0, 2),
+        ],
+        [
+            cofactor(1, 2, 0, 1),
+            -cofactor(0, 2, 0, 1),
+            cofactor(0, 1, 0, 1),
+        ],
+    ];
+    let det = m[0][0] * inverse[0][0] + m[0][1] * inverse[1][0] + m[0][2] * inverse[2][0];
+    assert!(
+        det != 0.0 && det.is_finite(),
+        "homography {:?} cannot be inverted",
+        matrix
+    );
+    warp(image, (height, width), interpolation, border, |x, y| {
+        let (x, y) = (x as f32, y as f32);
+        let row = |r: [f32; 3]| r[0] * x + r[1] * y + r[2];
+        let w = row(inverse[2]);
+        (row(inverse[0]) / w, row(inverse[1]) / w)
+    })
+}
+
+/// Samples a 3d array at arbitrary positions, like OpenCV's `remap`.
+///
+/// Each output pixel is sampled at the `(map_x, map_y)` column and row of the input at the same
+/// index of the maps, so the output has the dimensions of the maps. Positions are interpolated
+/// like in [`warp_affine`], and positions that are not finite are filled with the constant border,
+/// or zero for other borders. Fails if the maps do not have the same dimensions. Panics if the
+/// input is empty and the border is not constant.
+pub fn remap<A: Primitive>(
+    image: NdColor<'_, A>,
+    map_x: NdGray<'_, f32>,
+    map_y: NdGray<'_, f32>,
+    interpolation: Interpolation,
+    border: BorderMode<A>,
+) -> Result<Array3<A>, ConversionError> {
+    if map_x.dim() != map_y.dim() {
+        return Err(ConversionError::BadShape {
+            shape: map_y.shape().to_vec(),
+        });
+    }
+    Ok(warp(image, map_x.dim(), interpolation, border, |x, y| {
+        (map_x[[y, x]], map_y[[y, x]])
+    }))
+}
+
+/// Fills a new array of `(height, width)` pixels by sampling the input at the `(x, y)` position
+/// that `source` maps the column and row of each output pixel to.
+///
+/// Positions that are not finite are filled with the constant border, or zero for other borders.
+pub(crate) fn warp<A: Primitive>(
+    image: NdColor<'_, A>,
+    (height, width): (usize, usize),
+    interpolation: Interpolation,
+    border: BorderMode<A>,
+    source: impl Fn(usize, usize) -> (f32, f32),
+) -> Array3<A> {
+    let (in_height, in_width, channels) = image.dim();
+    let outside = match border {
+        BorderMode::Constant(value) => value.to_f32().unwrap(),
+        _ => 0.0,
+    };
+    let value = |y: isize, x: isize, c: usize| -> f32 {
+        let y = border.source(y, in_height);
+        let x = border.source(x, in_width);
+        match (y, x, &border) {
+            (Some(y), Some(x), _) => image[[y, x, c]].to_f32().unwrap(),
+            (_, _, BorderMode::Constant(value)) => value.to_f32().unwrap(),
+            _ => panic!("cannot take border pixels from an empty array"),
+        }
+    };
+    let sample = |sx: f32, sy: f32, c: usize| -> f32 {
+        if !sx.is_finite() || !sy.is_finite() {
+            return outside;
+        }
+        match interpolation {
+            Interpolation::Nearest => value(sy.round() as isize, sx.round() as isize, c),
+            Interpolation::Bilinear => {
+                let (x0, y0) = (sx.floor(), sy.floor());
+                let (fx, fy) = (sx - x0, sy - y0);
+                let (x0, y0) = (x0 as isize, y0 as isize);
+                let top = value(y0, x0, c) * (1.0 - fx) + value(y0, x0 + 1, c) * fx;
+                let bottom = value(y0 + 1, x0, c) * (1.0 - fx) + value(y0 + 1, x0 + 1, c) * fx;
+                top * (1.0 - fy) + bottom * fy
+            }
+        }
+    };
+    let mut out = Array3::zeros((height, width, channels));
+    for (y, mut row) in out.outer_iter_mut().enumerate() {
+        for (x, mut pixel) in row.outer_iter_mut().enumerate() {
+            let (sx, sy) = source(x, y);
+            for (c, v) in pixel.iter_mut().enumerate() {
+                *v = saturate(f64::from(sample(sx, sy, c)));
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,106 @@
+use crate::hsv::map_pixels;
+use crate::{ConversionError, NdColor};
+use image::Primitive;
+use ndarray::{Array3, ArrayViewMut3, Axis};
+
+/// Converts a 3d array of `u8` RGB pixels to full range YCbCr, as used by JPEG.
+///
+/// Fails if the array does not have 3 channels.
+pub fn rgb_to_ycbcr(image: NdColor<'_, u8>) -> Result<Array3<u8>, ConversionError> {
+    map_pixels(image, |pixel| {
+        quantize(rgb_to_ycbcr_pixel(widen(pixel), 128.0))
+    })
+}
+
+/// Converts a 3d array of full range `u8` YCbCr pixels to RGB.
+///
+/// Fails if the array does not have 3 channels.
+pub fn ycbcr_to_rgb(image: NdColor<'_, u8>) -> Result<Array3<u8>, ConversionError> {
+    map_pixels(image, |pixel| {
+        quantize(ycbcr_to_rgb_pixel(widen(pixel), 128.0))
+    })
+}
+
+/// Converts a 3d array of `f32` RGB pixels in `0.0..=1.0` to YCbCr, with the chroma centered on
+/// `0.5`.
+///
+/// Fails if the array does not have 3 channels.
+pub fn rgb_to_ycbcr_f32(image: NdColor<'_, f32>) -> Result<Array3<f32>, ConversionError> {
+    map_pixels(image, |pixel| rgb_to_ycbcr_pixel(pixel, 0.5))
+}
+
+/// Converts a 3d array of `f32` YCbCr pixels, with the chroma centered on `0.5`, to RGB.
+///
+/// The values are not clamped, so colors outside of RGB can fall outside `0.0..=1.0`. Fails if
+/// the array does not have 3 channels.
+pub fn ycbcr_to_rgb_f32(image: NdColor<'_, f32>) -> Result<Array3<f32>, ConversionError> {
+    map_pixels(image, |pixel| ycbcr_to_rgb_pixel(pixel, 0.5))
+}
+
+/// Copies a 3d array of integer YCbCr pixels into a new array of RGB pixels.
+///
+/// Fails if the array does not have 3 channels.
+pub(crate) fn rgb_from_ycbcr<A: Primitive>(
+    image: NdColor<'_, A>,
+) -> Result<Array3<A>, ConversionError> {
+    let channels = image.dim().2;
+    if channels != 3 {
+        return Err(ConversionError::WrongChannelCount {
+            expected: 3,
+            got: channels,
+        });
+    }
+    let mut image = image.to_owned();
+    ycbcr_to_rgb_in_place(image.view_mut());
+    Ok(image)
+}
+
+/// Converts the RGB pixels of a 3 channel array of integers to YCbCr in place.
+pub(crate) fn rgb_to_ycbcr_in_place<A: Primitive>(image: ArrayViewMut3<'_, A>) {
+    map_in_place(image, rgb_to_ycbcr_pixel);
+}
+
+/// Converts the YCbCr pixels of a 3 channel array of integers to RGB in place.
+pub(crate) fn ycbcr_to_rgb_in_place<A: Primitive>(image: ArrayViewMut3<'_, A>) {
+    map_in_place(image, ycbcr_to_rgb_pixel);
+}
+
+/// Maps each pixel of an array of integers through a conversion with the chroma centered on half
+/// of the range of `A`, rounding and clamping the results.
+fn map_in_place<A: Primitive>(mut image: ArrayViewMut3<'_, A>, f: fn([f32; 3], f32) -> [f32; 3]) {
+    let max = A::max_value().to_f32().unwrap();
+    let mid = (max / 2.0).ceil();
+    for mut pixel in image.lanes_mut(Axis(2)) {
+        let src = [0, 1, 2].map(|c| pixel[c].to_f32().unwrap());
+        for (dst, v) in pixel.iter_mut().zip(f(src, mid)) {
+            *dst = A::from(v.round().clamp(0.0, max)).unwrap();
+        }
+    }
+}
+
+fn widen(pixel: [u8; 3]) -> [f32; 3] {
+    pixel.map(f32::from)
+}
+
+fn quantize(pixel: [f32; 3]) -> [u8; 3] {
+    pixel.map(|v| v.round().clamp(0.0, 255.0) as u8)
+}
+
+/// Converts an RGB pixel to YCbCr with the BT.601 coefficients, centering the chroma on `mid`.
+pub(crate) fn rgb_to_ycbcr_pixel([r, g, b]: [f32; 3], mid: f32) -> [f32; 3] {
+    [
+        0.299 * r + 0.587 * g + 0.114 * b,
+        mid - 0.168_736 * r - 0.331_264 * g + 0.5 * b,
+        mid + 0.5 * r - 0.418_688 * g - 0.081_312 * b,
+    ]
+}
+
+/// Converts a YCbCr pixel with the chroma centered on `mid` to RGB with the BT.601 coefficients.
+pub(crate) fn ycbcr_to_rgb_pixel([y, cb, cr]: [f32; 3], mid: f32) -> [f32; 3] {
+    let (cb, cr) = (cb - mid, cr - mid);
+    [
+        y + 1.402 * cr,
+        y - 0.344_136 * cb - 0.714_136 * cr,
+        y + 1.772 * cb,
+    ]
+}
@@ -0,0 +1,170 @@
+use crate::{ConversionError, NdColor};
+use ndarray::{Array2, Array3};
+
+/// The layout of the planes of a frame with YUV 4:2:0 chroma subsampling.
+///
+/// Both start with a full resolution luma plane followed by chroma planes of half the width and
+/// height, rounded up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Yuv420Format {
+    /// The U and V samples are interleaved in a single plane.
+    Nv12,
+    /// The U plane is followed by the V plane.
+    I420,
+}
+
+/// The matrix between RGB and YUV, which video stores in limited range with luma in `16..=235`
+/// and chroma in `16..=240`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YuvMatrix {
+    /// The standard definition matrix of BT.601.
+    Bt601,
+    /// The high definition matrix of BT.709.
+    Bt709,
+}
+
+impl YuvMatrix {
+    /// The red and blue luma coefficients.
+    fn coefficients(self) -> (f32, f32) {
+        match self {
+            YuvMatrix::Bt601 => (0.299, 0.114),
+            YuvMatrix::Bt709 => (0.2126, 0.0722),
+        }
+    }
+}
+
+/// Converts a YUV 4:2:0 frame of `width` by `height` pixels into a `(height, width, 3)` array of
+/// RGB colors.
+///
+/// The chroma is upsampled bilinearly from samples sited horizontally with the even columns and
+/// vertically between each pair of rows, as in MPEG-2 and H.264. Fails if `data` is shorter than
+/// the frame.
+pub fn yuv420_to_rgb(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    format: Yuv420Format,
+    matrix: YuvMatrix,
+) -> Result<Array3<u8>, ConversionError> {
+    let (chroma_width, chroma_height) = chroma_dim(width, height);
+    let luma_len = width * height;
+    if data.len() < luma_len + 2 * chroma_width * chroma_height {
+        return Err(ConversionError::BufferTooShort {
+            shape: vec![height, width, 3],
+            len: data.len(),
+        });
+    }
+    let (luma, chroma) = data.split_at(luma_len);
+    let plane = |channel: usize| {
+        Array2::from_shape_fn((chroma_height, chroma_width), |(y, x)| {
+            let index = match format {
+                Yuv420Format::Nv12 => 2 * (y * chroma_width + x) + channel,
+                Yuv420Format::I420 => channel * chroma_width * chroma_height + y * chroma_width + x,
+            };
+            f32::from(chroma[index])
+        })
+    };
+    let (u, v) = (plane(0), plane(1));
+    let (kr, kb) = matrix.coefficients();
+    let kg = 1.0 - kr - kb;
+    let mut rgb = Array3::zeros((height, width, 3));
+    for y in 0..height {
+        // Chroma rows are sited halfway between each pair of luma rows.
+        let cy = ((y as f32 - 0.5) / 2.0).clamp(0.0, (chroma_height - 1) as f32);
+        for x in 0..width {
+            let cx = x as f32 / 2.0;
+            let ey = (f32::from(luma[y * width + x]) - 16.0) * 255.0 / 219.0;
+            let pb = (bilinear(&u, cy, cx) - 128.0) * 255.0 / 224.0;
+            let pr = (bilinear(&v, cy, cx) - 128.0) * 255.0 / 224.0;
+            let r = ey + 2.0 * (1.0 - kr) * pr;
+            let b = ey + 2.0 * (1.0 - kb) * pb;
+            let g = (ey - kr * r - kb * b) / kg;
+            for (c, value) in [r, g, b].iter().enumerate() {
+                rgb[[y, x, c]] = value.round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+    Ok(rgb)
+}
+
+/// Converts a `(height, width, 3)` array of RGB colors into a YUV 4:2:0 frame.
+///
+/// The chroma is filtered down to the same siting that [`yuv420_to_rgb`] expects. Fails if the
+/// array does not have 3 channels.
+pub fn rgb_to_yuv420(
+    image: NdColor<'_, u8>,
+    format: Yuv420Format,
+    matrix: YuvMatrix,
+) -> Result<Vec<u8>, ConversionError> {
+    let (height, width, channels) = image.dim();
+    if channels != 3 {
+        return Err(ConversionError::WrongChannelCount {
+            expected: 3,
+            got: channels,
+        });
+    }
+    let (kr, kb) = matrix.coefficients();
+    let kg = 1.0 - kr - kb;
+    let mut pb = Array2::zeros((height, width));
+    let mut pr = Array2::zeros((height, width));
+    let (chroma_width, chroma_height) = chroma_dim(width, height);
+    let mut data = Vec::with_capacity(width * height + 2 * chroma_width * chroma_height);
+    for y in 0..height {
+        for x in 0..width {
+            let [r, g, b] = [0, 1, 2].map(|c| f32::from(image[[y, x, c]]));
+            let ey = kr * r + kg * g + kb * b;
+            data.push((16.0 + ey * 219.0 / 255.0).round().clamp(0.0, 255.0) as u8);
+            pb[[y, x]] = (b - ey) / (2.0 * (1.0 - kb));
+            pr[[y, x]] = (r - ey) / (2.0 * (1.0 - kr));
+        }
+    }
+    let (u, v) = (downsample(&pb), downsample(&pr));
+    let quantize = |c: f32| (128.0 + c * 224.0 / 255.0).round().clamp(0.0, 255.0) as u8;
+    match format {
+        Yuv420Format::Nv12 => {
+            for (&u, &v) in u.iter().zip(&v) {
+                data.extend_from_slice(&[quantize(u), quantize(v)]);
+            }
+        }
+        Yuv420Format::I420 => {
+            data.extend(u.iter().map(|&u| quantize(u)));
+            data.extend(v.iter().map(|&v| quantize(v)));
+        }
+    }
+    Ok(data)
+}
+
+/// The dimensions of the chroma planes of a frame.
+fn chroma_dim(width: usize, height: usize) -> (usize, usize) {
+    (width.div_ceil(2), height.div_ceil(2))
+}
+
+/// Samples a plane at a fractional position, clamping to its edges.
+fn bilinear(plane: &Array2<f32>, y: f32, x: f32) -> f32 {
+    let (height, width) = plane.dim();
+    let (y0, x0) = (y.floor() as usize, x.floor() as usize);
+    let (y1, x1) = ((y0 + 1).min(height - 1), (x0 + 1).min(width - 1));
+    let (ty, tx) = (y - y0 as f32, x - x0 as f32);
+    let top = plane[[y0, x0]] * (1.0 - tx) + plane[[y0, x1]] * tx;
+    let bottom = plane[[y1, x0]] * (1.0 - tx) + plane[[y1, x1]] * tx;
+    top * (1.0 - ty) + bottom * ty
+}
+
+/// Halves a full resolution chroma plane, averaging each pair of rows and filtering each even
+/// column with its neighbors.
+fn downsample(plane: &Array2<f32>) -> Array2<f32> {
+    let (height, width) = plane.dim();
+    let (chroma_width, chroma_height) = chroma_dim(width, height);
+    Array2::from_shape_fn((chroma_height, chroma_width), |(cy, cx)| {
+        let rows = [2 * cy, (2 * cy + 1).min(height - 1)];
+        let x = 2 * cx;
+        let columns = [x.saturating_sub(1), x, (x + 1).min(width - 1)];
+        let mut sum = 0.0;
+        for &y in &rows {
+            for (&x, weight) in columns.iter().zip([1.0, 2.0, 1.0]) {
+                sum += weight * plane[[y, x]];
+            }
+        }
+        sum / 8.0
+    })
+}
@@ -0,0 +1,46 @@
+use ndarray::{arr3, Array3};
+use ndarray_image::{premultiply_alpha, unpremultiply_alpha, Colors, ConversionError};
+
+#[test]
+fn premultiply_rounds_u8() {
+    let mut image = arr3(&[[[255u8, 128, 0, 128], [200, 100, 50, 255], [90, 90, 90, 0]]]);
+    premultiply_alpha(image.view_mut(), Colors::Rgba).unwrap();
+    assert_eq!(
+        image,
+        arr3(&[[[128, 64, 0, 128], [200, 100, 50, 255], [0, 0, 0, 0]]])
+    );
+    unpremultiply_alpha(image.view_mut(), Colors::Rgba).unwrap();
+    assert_eq!(
+        image,
+        arr3(&[[[255, 128, 0, 128], [200, 100, 50, 255], [0, 0, 0, 0]]])
+    );
+}
+
+#[test]
+fn unpremultiply_saturates_and_keeps_floats() {
+    let mut image = arr3(&[[[100u8, 60], [10, 0]]]);
+    unpremultiply_alpha(image.view_mut(), Colors::LumaA).unwrap();
+    assert_eq!(image, arr3(&[[[255, 60], [0, 0]]]));
+    let mut image = arr3(&[[[0.5f32, 0.25, 1.0, 0.5]]]);
+    premultiply_alpha(image.view_mut(), Colors::Bgra).unwrap();
+    assert_eq!(image, arr3(&[[[0.25, 0.125, 0.5, 0.5]]]));
+}
+
+#[test]
+fn colors_without_alpha_are_opaque() {
+    let mut image = Array3::from_elem((2, 2, 3), 77u8);
+    premultiply_alpha(image.view_mut(), Colors::Rgb).unwrap();
+    assert_eq!(image, Array3::from_elem((2, 2, 3), 77));
+}
+
+#[test]
+fn wrong_channel_count_reports_the_colors() {
+    let mut image = Array3::<u8>::zeros((2, 2, 4));
+    assert_eq!(
+        premultiply_alpha(image.view_mut(), Colors::LumaA),
+        Err(ConversionError::WrongChannelCount {
+            expected: 2,
+            got: 4
+        })
+    );
+}
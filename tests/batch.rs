@@ -0,0 +1,34 @@
+mod common;
+
+use ndarray_image::{open_images_batch, Colors, Layout};
+use std::path::PathBuf;
+
+#[test]
+fn empty_batches_follow_the_layout() {
+    let none: [PathBuf; 0] = [];
+    let batch = open_images_batch(&none, Colors::Rgb, Layout::Nhwc).unwrap();
+    assert_eq!(batch.dim(), (0, 0, 0, 3));
+    let batch = open_images_batch(&none, Colors::Rgba, Layout::Nchw).unwrap();
+    assert_eq!(batch.dim(), (0, 4, 0, 0));
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn nchw_batches_round_trip() {
+    use common::temp_path;
+    use ndarray::Array4;
+    use ndarray_image::save_images_batch;
+
+    let paths: Vec<PathBuf> = (0..2)
+        .map(|i| temp_path(&format!("batch-{}.png", i)))
+        .collect();
+    let batch = Array4::from_shape_fn((2, 3, 4, 5), |(n, c, y, x)| {
+        (n * 60 + c * 20 + y * 5 + x) as u8
+    });
+    save_images_batch(&paths, batch.view(), Colors::Rgb, Layout::Nchw).unwrap();
+    let opened = open_images_batch(&paths, Colors::Rgb, Layout::Nchw).unwrap();
+    assert_eq!(opened, batch);
+    for path in paths {
+        std::fs::remove_file(path).unwrap();
+    }
+}
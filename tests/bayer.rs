@@ -0,0 +1,131 @@
+mod common;
+
+#[cfg(feature = "png")]
+use common::temp_path;
+use ndarray::{s, Array2, Array3};
+use ndarray_image::{demosaic, CfaPattern, DemosaicAlgorithm};
+
+const PATTERNS: [CfaPattern; 4] = [
+    CfaPattern::Rggb,
+    CfaPattern::Bggr,
+    CfaPattern::Grbg,
+    CfaPattern::Gbrg,
+];
+
+const ALGORITHMS: [DemosaicAlgorithm; 2] = [DemosaicAlgorithm::Bilinear, DemosaicAlgorithm::Malvar];
+
+/// The channel sampled at `(y, x)` for each pattern, read from the description of the pattern.
+fn channel(pattern: CfaPattern, y: usize, x: usize) -> usize {
+    let name = format!("{:?}", pattern).to_lowercase();
+    let letter = name.as_bytes()[(y % 2) * 2 + x % 2];
+    match letter {
+        b'r' => 0,
+        b'g' => 1,
+        _ => 2,
+    }
+}
+
+/// Samples an image through the color filter array of a pattern.
+fn mosaic(image: &Array3<u16>, pattern: CfaPattern) -> Array2<u16> {
+    let (height, width, _) = image.dim();
+    Array2::from_shape_fn((height, width), |(y, x)| {
+        image[[y, x, channel(pattern, y, x)]]
+    })
+}
+
+#[test]
+fn flat_colors_are_restored_exactly() {
+    let color = [40000, 1234, 65535];
+    // Odd sizes end on a different part of the pattern at each border.
+    for &(height, width) in &[(7, 9), (3, 5), (6, 4), (9, 3)] {
+        let image = Array3::from_shape_fn((height, width, 3), |(_, _, c)| color[c]);
+        for &pattern in &PATTERNS {
+            for &algorithm in &ALGORITHMS {
+                let demosaiced = demosaic(mosaic(&image, pattern).view(), pattern, algorithm);
+                assert_eq!(
+                    demosaiced, image,
+                    "{:?} {:?} {}x{}",
+                    pattern, algorithm, width, height
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn sampled_values_are_kept() {
+    let mosaic = Array2::from_shape_fn((7, 9), |(y, x)| ((y * 9 + x) * 7919 % 65536) as u16);
+    for &pattern in &PATTERNS {
+        for &algorithm in &ALGORITHMS {
+            let demosaiced = demosaic(mosaic.view(), pattern, algorithm);
+            assert_eq!(demosaiced.dim(), (7, 9, 3));
+            for ((y, x), &sample) in mosaic.indexed_iter() {
+                assert_eq!(demosaiced[[y, x, channel(pattern, y, x)]], sample);
+            }
+        }
+    }
+}
+
+#[test]
+fn ramps_are_restored_away_from_the_borders() {
+    // Both algorithms are exact for colors that change linearly, until the mirrored borders.
+    let image = Array3::from_shape_fn((8, 12, 3), |(y, x, c)| {
+        (y * 300 + x * 1000 + c * 100) as u16
+    });
+    for &pattern in &PATTERNS {
+        let mosaic = mosaic(&image, pattern);
+        for &algorithm in &ALGORITHMS {
+            let demosaiced = demosaic(mosaic.view(), pattern, algorithm);
+            let inner = s![2..6, 2..10, ..];
+            assert_eq!(
+                demosaiced.slice(inner),
+                image.slice(inner),
+                "{:?} {:?}",
+                pattern,
+                algorithm
+            );
+        }
+    }
+}
+
+#[test]
+fn tiny_mosaics_are_demosaiced() {
+    // Axes too short to mirror into are clamped instead.
+    for &(height, width) in &[(1, 1), (1, 2), (2, 1), (2, 2), (1, 5)] {
+        let mosaic = Array2::from_shape_fn((height, width), |(y, x)| (y * 2 + x + 1) as u16 * 100);
+        for &pattern in &PATTERNS {
+            for &algorithm in &ALGORITHMS {
+                let demosaiced = demosaic(mosaic.view(), pattern, algorithm);
+                assert_eq!(demosaiced.dim(), (height, width, 3));
+                for ((y, x), &sample) in mosaic.indexed_iter() {
+                    assert_eq!(demosaiced[[y, x, channel(pattern, y, x)]], sample);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn raw_mosaics_keep_their_values() {
+    use ndarray::Axis;
+    use ndarray_image::{open_raw_bayer, save_image, save_image_u16, Colors};
+
+    let mosaic = Array3::from_shape_fn((4, 6, 1), |(y, x, _)| (y * 6000 + x * 1000 + 3) as u16);
+    let path = temp_path("bayer-16.png");
+    save_image_u16(&path, mosaic.view(), Colors::Luma).unwrap();
+    let raw = open_raw_bayer(&path).unwrap();
+    assert_eq!(raw, mosaic.index_axis(Axis(2), 0));
+    // 8-bit samples are widened without scaling.
+    let path_8 = temp_path("bayer-8.png");
+    let mosaic = mosaic.mapv(|value| (value / 256) as u8);
+    save_image(&path_8, mosaic.view(), Colors::Luma).unwrap();
+    let raw = open_raw_bayer(&path_8).unwrap();
+    assert_eq!(raw, mosaic.index_axis(Axis(2), 0).mapv(u16::from));
+    // Images with colors are not mosaics.
+    let rgb = Array3::<u8>::zeros((2, 2, 3));
+    save_image(&path_8, rgb.view(), Colors::Rgb).unwrap();
+    assert!(open_raw_bayer(&path_8).is_err());
+    std::fs::remove_file(path).unwrap();
+    std::fs::remove_file(path_8).unwrap();
+}
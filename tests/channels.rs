@@ -0,0 +1,135 @@
+use ndarray::{s, Array2, Array3, Axis};
+use ndarray_image::{
+    channel_views, channel_views_mut, merge_channels, reorder_channels, split_channels,
+    ConversionError,
+};
+
+fn image(channels: usize) -> Array3<u16> {
+    Array3::from_shape_fn((3, 5, channels), |(y, x, c)| (c * 100 + y * 10 + x) as u16)
+}
+
+#[test]
+fn split_channels_merge_back() {
+    for channels in 1..=5 {
+        let image = image(channels);
+        let split = split_channels(image.view());
+        assert_eq!(split.len(), channels);
+        for (c, channel) in split.iter().enumerate() {
+            assert_eq!(channel, &image.slice(s![.., .., c]));
+        }
+        let views: Vec<_> = split.iter().map(Array2::view).collect();
+        let merged = merge_channels(&views).unwrap();
+        assert!(merged.is_standard_layout());
+        assert_eq!(merged, image);
+    }
+}
+
+#[test]
+fn channel_views_match_the_split_channels() {
+    // Strided images are viewed as well as contiguous ones.
+    let image = image(4);
+    let flipped = image.slice(s![..;-1, 1..;2, ..]);
+    let views = channel_views(flipped);
+    assert_eq!(views.len(), 4);
+    for (view, split) in views.iter().zip(split_channels(flipped)) {
+        assert_eq!(view, &split);
+    }
+    let merged = merge_channels(&views).unwrap();
+    assert_eq!(merged, flipped);
+}
+
+#[test]
+fn channel_views_mut_write_through() {
+    let mut image = image(3);
+    let mut expected = image.clone();
+    expected.slice_mut(s![.., .., 0]).fill(7);
+    expected.slice_mut(s![.., .., 2]).mapv_inplace(|v| v * 2);
+    let mut views = channel_views_mut(image.view_mut());
+    assert_eq!(views.len(), 3);
+    views[0].fill(7);
+    views[2].mapv_inplace(|v| v * 2);
+    assert_eq!(image, expected);
+    // An image without channels has no views.
+    let mut empty = Array3::<u8>::zeros((2, 2, 0));
+    assert!(channel_views_mut(empty.view_mut()).is_empty());
+    assert!(channel_views(empty.view()).is_empty());
+}
+
+#[test]
+fn mismatched_channels_are_rejected() {
+    assert_eq!(
+        merge_channels::<u8>(&[]),
+        Err(ConversionError::BadShape { shape: vec![0] })
+    );
+    let a = Array2::<u8>::zeros((3, 4));
+    let b = Array2::<u8>::zeros((4, 3));
+    assert_eq!(
+        merge_channels(&[a.view(), a.view(), b.view()]),
+        Err(ConversionError::BadShape { shape: vec![4, 3] })
+    );
+}
+
+/// Every permutation of `0..n`.
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
+    }
+    let mut all = Vec::new();
+    for rest in permutations(n - 1) {
+        for index in 0..n {
+            let mut order = rest.clone();
+            order.insert(index, n - 1);
+            all.push(order);
+        }
+    }
+    all
+}
+
+#[test]
+fn channels_are_reordered_in_place() {
+    let mut rgb = image(3);
+    let original = rgb.clone();
+    reorder_channels(rgb.view_mut(), &[2, 1, 0]).unwrap();
+    assert_eq!(rgb.slice(s![.., .., 0]), original.slice(s![.., .., 2]));
+    assert_eq!(rgb.slice(s![.., .., 2]), original.slice(s![.., .., 0]));
+    let mut rgba = image(4);
+    let original = rgba.clone();
+    reorder_channels(rgba.view_mut(), &[3, 0, 1, 2]).unwrap();
+    assert_eq!(rgba, original.select(Axis(2), &[3, 0, 1, 2]));
+}
+
+#[test]
+fn every_permutation_is_applied() {
+    for channels in 1..=5 {
+        let original = image(channels);
+        for order in permutations(channels) {
+            let mut reordered = original.clone();
+            // Strided views are reordered too.
+            reorder_channels(reordered.slice_mut(s![.., ..;-1, ..]), &order).unwrap();
+            let expected = original.select(Axis(2), &order);
+            assert_eq!(reordered, expected, "{:?}", order);
+        }
+    }
+}
+
+#[test]
+fn invalid_orders_are_rejected() {
+    let mut rgb = image(3);
+    let original = rgb.clone();
+    assert_eq!(
+        reorder_channels(rgb.view_mut(), &[0, 1]),
+        Err(ConversionError::WrongChannelCount {
+            expected: 3,
+            got: 2
+        })
+    );
+    for order in &[[0, 0, 1], [0, 1, 3]] {
+        assert_eq!(
+            reorder_channels(rgb.view_mut(), order),
+            Err(ConversionError::BadPermutation {
+                order: order.to_vec()
+            })
+        );
+    }
+    assert_eq!(rgb, original);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::row;
+#[cfg(any(feature = "png", feature = "tiff"))]
+use common::temp_path;
+use ndarray::{Array3, Axis};
+use ndarray_image::{cmyk_to_rgb, rgb_to_cmyk, ConversionError};
+
+fn rgb_grid() -> Array3<u8> {
+    Array3::from_shape_fn((16, 16, 3), |(y, x, c)| match c {
+        0 => (y * 17) as u8,
+        1 => (x * 17) as u8,
+        _ => ((y + x) * 8) as u8,
+    })
+}
+
+#[test]
+fn primaries_have_known_cmyk() {
+    let rgb = row(&[
+        [255, 0, 0],
+        [0, 255, 0],
+        [0, 0, 255],
+        [255, 255, 255],
+        [0, 0, 0],
+        [128, 128, 128],
+        [128, 0, 0],
+    ]);
+    let cmyk = row(&[
+        [0, 255, 255, 0],
+        [255, 0, 255, 0],
+        [255, 255, 0, 0],
+        [0, 0, 0, 0],
+        [0, 0, 0, 255],
+        [0, 0, 0, 127],
+        [0, 255, 255, 127],
+    ]);
+    assert_eq!(rgb_to_cmyk(rgb.view()).unwrap(), cmyk);
+    assert_eq!(cmyk_to_rgb(cmyk.view()).unwrap(), rgb);
+}
+
+#[test]
+fn cmyk_round_trips() {
+    let rgb = rgb_grid();
+    let cmyk = rgb_to_cmyk(rgb.view()).unwrap();
+    // The black channel takes all the ink it can, so one of the other channels is always empty.
+    assert!(cmyk
+        .lanes(Axis(2))
+        .into_iter()
+        .all(|pixel| pixel[3] == 255 || pixel.iter().take(3).any(|&c| c == 0)));
+    let back = cmyk_to_rgb(cmyk.view()).unwrap();
+    for (&a, &b) in back.iter().zip(&rgb) {
+        assert!((i16::from(a) - i16::from(b)).abs() <= 1, "{} != {}", a, b);
+    }
+}
+
+#[test]
+fn wrong_channel_counts_are_rejected() {
+    let image = Array3::<u8>::zeros((2, 2, 4));
+    assert_eq!(
+        rgb_to_cmyk(image.view()),
+        Err(ConversionError::WrongChannelCount {
+            expected: 3,
+            got: 4
+        })
+    );
+    let image = Array3::<u8>::zeros((2, 2, 3));
+    assert_eq!(
+        cmyk_to_rgb(image.view()),
+        Err(ConversionError::WrongChannelCount {
+            expected: 4,
+            got: 3
+        })
+    );
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn rgb_images_are_opened_as_cmyk() {
+    use ndarray_image::{open_image_cmyk, save_image, Colors};
+
+    let rgb = rgb_grid();
+    let path = temp_path("cmyk.png");
+    save_image(&path, rgb.view(), Colors::Rgb).unwrap();
+    let cmyk = open_image_cmyk(&path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(cmyk, rgb_to_cmyk(rgb.view()).unwrap());
+}
+
+#[cfg(feature = "tiff")]
+#[test]
+fn cmyk_tiff_images_keep_their_colors() {
+    use ndarray_image::{open_image_cmyk, save_tiff_cmyk, NdImageError};
+
+    // Colors that RGB cannot tell apart, such as rich and plain black, are kept.
+    let cmyk = Array3::from_shape_fn((5, 7, 4), |(y, x, c)| (y * 40 + x * 25 + c * 60) as u8);
+    let path = temp_path("cmyk.tiff");
+    save_tiff_cmyk(&path, cmyk.view()).unwrap();
+    assert_eq!(open_image_cmyk(&path).unwrap(), cmyk);
+    std::fs::remove_file(&path).unwrap();
+    let rgb = Array3::<u8>::zeros((2, 2, 3));
+    assert!(matches!(
+        save_tiff_cmyk(&path, rgb.view()),
+        Err(NdImageError::Conversion(
+            ConversionError::WrongChannelCount { .. }
+        ))
+    ));
+    assert!(!path.exists());
+}
@@ -0,0 +1,30 @@
+//! Helpers shared by the integration tests.
+
+// Each test only uses some of the helpers.
+#![allow(dead_code)]
+
+use ndarray::Array3;
+use std::path::PathBuf;
+
+/// A gradient that differs along every axis.
+pub fn gradient(dim: (usize, usize, usize)) -> Array3<u8> {
+    Array3::from_shape_fn(dim, |(y, x, c)| (y * 11 + x * 7 + c * 50) as u8)
+}
+
+/// A path in the temporary directory for the file `name`, which is unique to this test process.
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ndarray-image-{}-{}", std::process::id(), name))
+}
+
+/// A single row of pixels.
+pub fn row<A: Copy, const N: usize>(pixels: &[[A; N]]) -> Array3<A> {
+    Array3::from_shape_fn((1, pixels.len(), N), |(_, x, c)| pixels[x][c])
+}
+
+/// Asserts that every value of two arrays differs by at most `tolerance`.
+pub fn assert_close(a: &Array3<f32>, b: &Array3<f32>, tolerance: f32) {
+    assert_eq!(a.dim(), b.dim());
+    for ((index, &a), &b) in a.indexed_iter().zip(b) {
+        assert!((a - b).abs() <= tolerance, "{} != {} at {:?}", a, b, index);
+    }
+}
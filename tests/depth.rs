@@ -0,0 +1,73 @@
+use ndarray::Array3;
+use ndarray_image::{convert_depth, from_f32_unit, to_f32_unit};
+
+fn array<A: Clone>(values: &[A]) -> Array3<A> {
+    Array3::from_shape_vec((1, values.len(), 1), values.to_vec()).unwrap()
+}
+
+#[test]
+fn u8_to_u16_replicates_bits() {
+    let all: Vec<u8> = (0..=255).collect();
+    let wide = convert_depth::<u8, u16>(array(&all).view());
+    for (&narrow, &wide) in all.iter().zip(wide.iter()) {
+        assert_eq!(wide, u16::from(narrow) * 257);
+    }
+    assert_eq!(convert_depth::<u16, u8>(wide.view()), array(&all));
+}
+
+#[test]
+fn u16_to_u8_rounds_to_nearest() {
+    let narrow =
+        convert_depth::<u16, u8>(array(&[0, 128, 129, 385, 386, 65406, 65407, 65535]).view());
+    assert_eq!(narrow, array(&[0, 0, 1, 1, 2, 254, 255, 255]));
+}
+
+#[test]
+fn f32_unit_round_trips() {
+    let all: Vec<u8> = (0..=255).collect();
+    let unit = to_f32_unit(array(&all).view());
+    assert_eq!(unit[[0, 0, 0]], 0.0);
+    assert_eq!(unit[[0, 255, 0]], 1.0);
+    assert_eq!(from_f32_unit::<u8>(unit.view()), array(&all));
+
+    let all: Vec<u16> = (0..=65535).collect();
+    let unit = to_f32_unit(array(&all).view());
+    assert_eq!(from_f32_unit::<u16>(unit.view()), array(&all));
+}
+
+#[test]
+fn from_f32_unit_rounds_and_saturates() {
+    let values = from_f32_unit::<u8>(array(&[-0.5, 0.0, 0.5, 0.999, 1.0, 2.0]).view());
+    assert_eq!(values, array(&[0, 0, 128, 255, 255, 255]));
+}
+
+#[test]
+fn from_f32_unit_saturates_non_finite_values() {
+    let values = array(&[f32::NAN, f32::INFINITY, f32::NEG_INFINITY, 0.5]);
+    assert_eq!(from_f32_unit::<u8>(values.view()), array(&[0, 255, 0, 128]));
+    assert_eq!(
+        from_f32_unit::<u16>(values.view()),
+        array(&[0, 65535, 0, 32768])
+    );
+    assert_eq!(
+        from_f32_unit::<i64>(values.view()),
+        array(&[0, i64::MAX, i64::MIN, 1 << 62])
+    );
+}
+
+#[test]
+fn convert_depth_saturates_at_64_bit_bounds() {
+    // The largest 64-bit integers round up to a power of two as `f64`.
+    let narrow = array(&[0u8, 255]);
+    assert_eq!(
+        convert_depth::<u8, u64>(narrow.view()),
+        array(&[0, u64::MAX])
+    );
+    assert_eq!(
+        convert_depth::<u8, i64>(narrow.view()),
+        array(&[0, i64::MAX])
+    );
+    let wide = array(&[0u64, u64::MAX]);
+    assert_eq!(convert_depth::<u64, u8>(wide.view()), array(&[0, 255]));
+    assert_eq!(convert_depth::<u64, u16>(wide.view()), array(&[0, 65535]));
+}
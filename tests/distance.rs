@@ -0,0 +1,41 @@
+use ndarray::Array2;
+use ndarray_image::{distance_transform, DistanceMetric};
+
+#[test]
+fn distances_to_a_single_seed() {
+    let mut mask = Array2::from_elem((9, 12), 1u8);
+    mask[[3, 7]] = 0;
+    let euclidean = distance_transform(mask.view(), DistanceMetric::Euclidean);
+    let chebyshev = distance_transform(mask.view(), DistanceMetric::Chebyshev);
+    for ((y, x), &d) in euclidean.indexed_iter() {
+        let (dy, dx) = (y as f32 - 3.0, x as f32 - 7.0);
+        assert!((d - dx.hypot(dy)).abs() < 1e-5, "{:?}", (y, x));
+        assert_eq!(chebyshev[[y, x]], dx.abs().max(dy.abs()), "{:?}", (y, x));
+    }
+}
+
+#[test]
+fn distances_to_the_closest_seed() {
+    let mut mask = Array2::from_elem((7, 15), 255u8);
+    let seeds = [(0, 0), (6, 14), (3, 8)];
+    for &seed in &seeds {
+        mask[seed] = 0;
+    }
+    let euclidean = distance_transform(mask.view(), DistanceMetric::Euclidean);
+    for ((y, x), &d) in euclidean.indexed_iter() {
+        let closest = seeds
+            .iter()
+            .map(|&(sy, sx)| (y as f32 - sy as f32).hypot(x as f32 - sx as f32))
+            .fold(f32::INFINITY, f32::min);
+        assert!((d - closest).abs() < 1e-5, "{:?}", (y, x));
+    }
+}
+
+#[test]
+fn masks_without_zeros_are_infinitely_far() {
+    let mask = Array2::from_elem((3, 4), 1u8);
+    for &metric in &[DistanceMetric::Euclidean, DistanceMetric::Chebyshev] {
+        let distances = distance_transform(mask.view(), metric);
+        assert!(distances.iter().all(|d| d.is_infinite()), "{:?}", metric);
+    }
+}
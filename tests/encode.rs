@@ -0,0 +1,40 @@
+mod common;
+
+use common::temp_path;
+use image::{ImageError, ImageFormat};
+use ndarray::Array3;
+use ndarray_image::{encode_image, save_image_with_options, Colors, NdImageError, SaveOptions};
+
+#[test]
+fn webp_images_cannot_be_saved() {
+    let image = Array3::<u8>::zeros((2, 2, 3));
+    let encoded = encode_image(image.view(), Colors::Rgb, ImageFormat::WebP);
+    assert!(matches!(
+        encoded,
+        Err(NdImageError::Image(ImageError::Unsupported(_)))
+    ));
+    let path = temp_path("save.webp");
+    let saved = save_image_with_options(&path, image.view(), Colors::Rgb, SaveOptions::default());
+    assert!(matches!(
+        saved,
+        Err(NdImageError::Image(ImageError::Unsupported(_)))
+    ));
+    assert!(!path.exists());
+}
+
+#[cfg(feature = "jpeg")]
+#[test]
+fn jpeg_images_are_baseline_with_full_resolution_chroma() {
+    let image = Array3::from_shape_fn((8, 8, 3), |(y, x, c)| (y * 30 + x * 3 + c * 80) as u8);
+    let encoded = encode_image(image.view(), Colors::Rgb, ImageFormat::Jpeg).unwrap();
+    // A progressive image would have a SOF2 marker instead of SOF0.
+    let sof = encoded
+        .windows(2)
+        .position(|marker| marker == [0xFF, 0xC0])
+        .unwrap();
+    assert!(!encoded.windows(2).any(|marker| marker == [0xFF, 0xC2]));
+    // Each of the 3 components has a sampling factor of 1x1 after its id.
+    let components = &encoded[sof + 10..sof + 10 + 3 * 3];
+    assert_eq!(encoded[sof + 9], 3);
+    assert!(components.chunks(3).all(|component| component[1] == 0x11));
+}
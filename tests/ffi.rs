@@ -0,0 +1,127 @@
+#![cfg(all(feature = "ffi", feature = "png"))]
+
+mod common;
+
+use ndarray::{s, Array3, ArrayView3};
+use ndarray_image::{ndimage_free, ndimage_open, ndimage_save, NdImageBuffer, NdImageStatus};
+use std::ffi::CString;
+use std::ptr;
+
+/// The temporary path as a C string.
+fn temp_path(name: &str) -> CString {
+    CString::new(common::temp_path(name).to_str().unwrap()).unwrap()
+}
+
+fn empty_buffer() -> NdImageBuffer {
+    NdImageBuffer {
+        data: ptr::null_mut(),
+        shape: [0; 3],
+        strides: [0; 3],
+    }
+}
+
+#[test]
+fn buffers_round_trip_through_the_c_interface() {
+    let image = Array3::from_shape_fn((3, 5, 3), |(y, x, c)| (y * 50 + x * 10 + c) as u8);
+    let path = temp_path("ffi.png");
+    let mut data = image.clone();
+    let buffer = NdImageBuffer {
+        data: data.as_mut_ptr(),
+        shape: [3, 5, 3],
+        strides: [15, 3, 1],
+    };
+    unsafe {
+        assert_eq!(ndimage_save(path.as_ptr(), &buffer, 2), NdImageStatus::Ok);
+
+        let mut opened = empty_buffer();
+        assert_eq!(
+            ndimage_open(path.as_ptr(), 2, &mut opened),
+            NdImageStatus::Ok
+        );
+        assert_eq!(opened.shape, [3, 5, 3]);
+        assert_eq!(opened.strides, [15, 3, 1]);
+        let pixels = std::slice::from_raw_parts(opened.data, 45);
+        assert_eq!(pixels, image.as_slice().unwrap());
+
+        // Freeing a buffer nulls its data, so freeing it again does nothing.
+        ndimage_free(&mut opened);
+        assert!(opened.data.is_null());
+        ndimage_free(&mut opened);
+        assert!(opened.data.is_null());
+        ndimage_free(ptr::null_mut());
+    }
+    std::fs::remove_file(path.to_str().unwrap()).unwrap();
+}
+
+#[test]
+fn buffers_with_negative_strides_are_saved_flipped() {
+    let image = Array3::from_shape_fn((4, 6, 4), |(y, x, c)| (y * 60 + x * 9 + c) as u8);
+    let path = temp_path("ffi-flipped.png");
+    let mut data = image.clone();
+    // The buffer starts at the last pixel of the first row and goes down and to the left.
+    let buffer = NdImageBuffer {
+        data: unsafe { data.as_mut_ptr().add(5 * 4) },
+        shape: [4, 6, 4],
+        strides: [24, -4, 1],
+    };
+    unsafe {
+        assert_eq!(ndimage_save(path.as_ptr(), &buffer, 3), NdImageStatus::Ok);
+        let mut opened = empty_buffer();
+        assert_eq!(
+            ndimage_open(path.as_ptr(), 3, &mut opened),
+            NdImageStatus::Ok
+        );
+        let pixels = std::slice::from_raw_parts(opened.data, 96);
+        let opened_image = ArrayView3::from_shape((4, 6, 4), pixels).unwrap();
+        assert_eq!(opened_image, image.slice(s![.., ..;-1, ..]));
+        ndimage_free(&mut opened);
+
+        // Flipping both axes starts the buffer at the last pixel.
+        let buffer = NdImageBuffer {
+            data: data.as_mut_ptr().add(95 - 3),
+            shape: [4, 6, 4],
+            strides: [-24, -4, 1],
+        };
+        assert_eq!(ndimage_save(path.as_ptr(), &buffer, 3), NdImageStatus::Ok);
+        let mut opened = empty_buffer();
+        assert_eq!(
+            ndimage_open(path.as_ptr(), 3, &mut opened),
+            NdImageStatus::Ok
+        );
+        let pixels = std::slice::from_raw_parts(opened.data, 96);
+        let opened_image = ArrayView3::from_shape((4, 6, 4), pixels).unwrap();
+        assert_eq!(opened_image, image.slice(s![..;-1, ..;-1, ..]));
+        ndimage_free(&mut opened);
+    }
+    std::fs::remove_file(path.to_str().unwrap()).unwrap();
+}
+
+#[test]
+fn invalid_arguments_are_rejected() {
+    let path = temp_path("ffi-invalid.png");
+    let mut opened = empty_buffer();
+    unsafe {
+        assert_eq!(
+            ndimage_open(ptr::null(), 2, &mut opened),
+            NdImageStatus::InvalidArgument
+        );
+        assert_eq!(
+            ndimage_open(path.as_ptr(), 7, &mut opened),
+            NdImageStatus::InvalidArgument
+        );
+        assert_eq!(
+            ndimage_open(path.as_ptr(), 2, ptr::null_mut()),
+            NdImageStatus::InvalidArgument
+        );
+        assert_eq!(
+            ndimage_save(path.as_ptr(), &empty_buffer(), 2),
+            NdImageStatus::InvalidArgument
+        );
+        // The file does not exist.
+        assert_eq!(
+            ndimage_open(path.as_ptr(), 2, &mut opened),
+            NdImageStatus::Image
+        );
+    }
+    assert!(opened.data.is_null());
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::gradient;
+use ndarray::{arr2, Array2, Array3};
+use ndarray_image::{
+    convolve2d, gaussian_blur, gradient_magnitude, gradient_orientation, median_filter, scharr,
+    sobel, BorderMode,
+};
+
+#[test]
+fn delta_kernel_copies() {
+    let kernel = arr2(&[[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.0]]);
+    let image = gradient((5, 6, 3));
+    let convolved = convolve2d(image.view(), kernel.view(), BorderMode::Reflect);
+    assert_eq!(convolved, image);
+    let gray = Array2::from_shape_fn((4, 7), |(y, x)| (y * 13 + x) as u8);
+    let convolved = convolve2d(gray.view(), kernel.view(), BorderMode::Wrap);
+    assert_eq!(convolved, gray);
+}
+
+#[test]
+fn kernel_is_flipped() {
+    // A weight on the left of the center takes every pixel from its right neighbour.
+    let kernel = arr2(&[[1.0, 0.0, 0.0]]);
+    let gray = Array2::from_shape_fn((2, 4), |(y, x)| (y * 10 + x + 1) as u8);
+    let convolved = convolve2d(gray.view(), kernel.view(), BorderMode::Constant(99));
+    assert_eq!(convolved, arr2(&[[2, 3, 4, 99], [12, 13, 14, 99]]));
+}
+
+#[test]
+fn box_kernel_averages_and_saturates() {
+    let image = Array3::from_elem((4, 5, 2), 100u8);
+    let average = Array2::from_elem((3, 3), 1.0 / 9.0);
+    let convolved = convolve2d(image.view(), average.view(), BorderMode::Replicate);
+    assert_eq!(convolved, image);
+    // An even kernel covers each pixel and the ones after it, so the last row and column also
+    // sum the zero border.
+    let ones = Array2::from_elem((2, 2), 1.0);
+    let convolved = convolve2d(image.view(), ones.view(), BorderMode::Constant(0));
+    assert_eq!(convolved[[0, 0, 0]], 255);
+    assert_eq!(convolved[[3, 0, 1]], 200);
+    assert_eq!(convolved[[3, 4, 0]], 100);
+}
+
+#[test]
+fn gaussian_blur_keeps_constants() {
+    let image = Array3::from_elem((6, 7, 3), 123u8);
+    assert_eq!(gaussian_blur(image.view(), 1.5), image);
+    let gray = gradient((5, 5, 1));
+    assert_eq!(gaussian_blur(gray.view(), 0.0), gray);
+}
+
+#[test]
+fn gaussian_blur_spreads_an_impulse_symmetrically() {
+    let mut impulse = Array2::<f32>::zeros((21, 21));
+    impulse[[10, 10]] = 1.0;
+    let narrow = gaussian_blur(impulse.view(), 1.0);
+    let wide = gaussian_blur(impulse.view(), 2.0);
+    for blurred in &[&narrow, &wide] {
+        assert!((blurred.sum() - 1.0).abs() < 1e-5);
+        for ((y, x), &v) in blurred.indexed_iter() {
+            assert!((v - blurred[[x, y]]).abs() < 1e-7);
+            assert!((v - blurred[[20 - y, x]]).abs() < 1e-7);
+        }
+    }
+    assert!(wide[[10, 10]] < narrow[[10, 10]]);
+    assert!(wide[[10, 14]] > narrow[[10, 14]]);
+    // The weights fall off like a Gaussian of the given standard deviation.
+    let ratio = narrow[[10, 11]] / narrow[[10, 10]];
+    assert!((ratio - (-0.5f32).exp()).abs() < 1e-5);
+}
+
+#[test]
+fn gradients_of_a_ramp() {
+    let ramp = Array2::from_shape_fn((6, 8), |(y, x)| (3 * x + 2 * y) as u8);
+    for ((dx, dy), scale) in [(sobel(ramp.view()), 8.0), (scharr(ramp.view()), 32.0)] {
+        for y in 1..5 {
+            for x in 1..7 {
+                assert_eq!((dx[[y, x]], dy[[y, x]]), (3.0 * scale, 2.0 * scale));
+            }
+        }
+        // Reflected borders mirror the ramp, so it is flat across them.
+        assert_eq!((dx[[3, 0]], dy[[0, 3]]), (0.0, 0.0));
+    }
+}
+
+#[test]
+fn gradient_magnitude_and_orientation() {
+    let dx = arr2(&[[3.0, 0.0, -1.0]]);
+    let dy = arr2(&[[4.0, 2.0, 0.0]]);
+    assert_eq!(
+        gradient_magnitude(dx.view(), dy.view()),
+        arr2(&[[5.0, 2.0, 1.0]])
+    );
+    let orientation = gradient_orientation(dx.view(), dy.view());
+    let expected = [
+        (4.0f32).atan2(3.0),
+        std::f32::consts::FRAC_PI_2,
+        std::f32::consts::PI,
+    ];
+    for (&v, &expected) in orientation.iter().zip(&expected) {
+        assert!((v - expected).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn median_removes_salt_noise() {
+    let mut image = Array2::from_elem((9, 9), 40u8);
+    for &(y, x) in &[(0, 0), (2, 5), (4, 4), (8, 3), (6, 8)] {
+        image[[y, x]] = 255;
+    }
+    assert_eq!(
+        median_filter(image.view(), 1),
+        Array2::from_elem((9, 9), 40)
+    );
+}
+
+#[test]
+fn median_matches_sorting_each_window() {
+    let mut state = 7u32;
+    let image = Array3::from_shape_fn((7, 10, 2), |_| {
+        state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        (state >> 16) as u8
+    });
+    for radius in 1..4 {
+        let filtered = median_filter(image.view(), radius);
+        let r = radius as isize;
+        for ((y, x, c), &v) in filtered.indexed_iter() {
+            let mut window = Vec::new();
+            for dy in -r..=r {
+                for dx in -r..=r {
+                    // Borders repeat the edge pixels.
+                    let sy = (y as isize + dy).clamp(0, 6) as usize;
+                    let sx = (x as isize + dx).clamp(0, 9) as usize;
+                    window.push(image[[sy, sx, c]]);
+                }
+            }
+            window.sort_unstable();
+            assert_eq!(v, window[window.len() / 2], "{:?}", (radius, y, x, c));
+        }
+    }
+}
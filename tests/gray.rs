@@ -0,0 +1,60 @@
+mod common;
+
+use common::row;
+use ndarray::{arr2, Array3, Axis};
+use ndarray_image::{rgb_to_gray, ConversionError, GrayWeights};
+
+#[test]
+fn primaries_are_weighted() {
+    let rgb = row(&[[255u8, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255]]);
+    for &(weights, expected) in &[
+        (GrayWeights::Rec601, [76, 150, 29, 255]),
+        (GrayWeights::Rec709, [54, 182, 18, 255]),
+        (GrayWeights::Average, [85, 85, 85, 255]),
+    ] {
+        let gray = rgb_to_gray(rgb.view(), weights).unwrap();
+        assert_eq!(gray, arr2(&[expected]));
+    }
+    let float = row(&[[1.0f32, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+    let gray = rgb_to_gray(float.view(), GrayWeights::Rec709).unwrap();
+    assert_eq!(gray, arr2(&[[0.2126, 0.7152, 0.0722]]));
+}
+
+#[test]
+fn integer_results_are_rounded_and_saturated() {
+    let rgb = row(&[[65535u16, 0, 0], [65535, 65535, 65535], [0, 0, 0]]);
+    let gray = rgb_to_gray(rgb.view(), GrayWeights::Rec601).unwrap();
+    assert_eq!(gray, arr2(&[[19595, 65535, 0]]));
+    let rgb = row(&[[200u8, 200, 200], [100, 0, 0]]);
+    let gray = rgb_to_gray(rgb.view(), GrayWeights::Custom([1.0, 1.0, 1.0])).unwrap();
+    assert_eq!(gray, arr2(&[[255, 100]]));
+    let gray = rgb_to_gray(rgb.view(), GrayWeights::Custom([-1.0, 0.0, 0.0])).unwrap();
+    assert_eq!(gray, arr2(&[[0, 0]]));
+}
+
+#[test]
+fn alpha_is_dropped() {
+    let rgba = Array3::from_shape_fn((3, 4, 4), |(y, x, c)| (y * 40 + x * 20 + c * 30) as u8);
+    let mut opaque = rgba.clone();
+    opaque.index_axis_mut(Axis(2), 3).fill(255);
+    let gray = rgb_to_gray(rgba.view(), GrayWeights::Rec709).unwrap();
+    assert_eq!(gray.dim(), (3, 4));
+    assert_eq!(
+        gray,
+        rgb_to_gray(opaque.view(), GrayWeights::Rec709).unwrap()
+    );
+}
+
+#[test]
+fn fewer_than_three_channels_are_rejected() {
+    for channels in 1..3 {
+        let image = Array3::<u8>::zeros((2, 2, channels));
+        assert_eq!(
+            rgb_to_gray(image.view(), GrayWeights::Rec601),
+            Err(ConversionError::WrongChannelCount {
+                expected: 3,
+                got: channels
+            })
+        );
+    }
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::{assert_close, row};
+use ndarray::Array3;
+use ndarray_image::{
+    hsl_to_rgb, hsl_to_rgb_f32, hsv_to_rgb, hsv_to_rgb_f32, rgb_to_hsl, rgb_to_hsl_f32, rgb_to_hsv,
+    rgb_to_hsv_f32,
+};
+
+/// The primaries, the secondaries, white, gray, and black.
+const RGB: [[u8; 3]; 9] = [
+    [255, 0, 0],
+    [255, 255, 0],
+    [0, 255, 0],
+    [0, 255, 255],
+    [0, 0, 255],
+    [255, 0, 255],
+    [255, 255, 255],
+    [128, 128, 128],
+    [0, 0, 0],
+];
+
+/// Every color of a coarse grid of RGB values.
+fn rgb_grid() -> Array3<u8> {
+    Array3::from_shape_fn((16, 16, 3), |(y, x, c)| {
+        let index = y * 16 + x;
+        let value = [index % 7, index / 7 % 7, index / 49 % 7][c];
+        (value * 42) as u8
+    })
+}
+
+#[test]
+fn primaries_have_known_hsv() {
+    let hsv = rgb_to_hsv(row(&RGB).view()).unwrap();
+    let expected = [
+        [0, 255, 255],
+        [30, 255, 255],
+        [60, 255, 255],
+        [90, 255, 255],
+        [120, 255, 255],
+        [150, 255, 255],
+        [0, 0, 255],
+        [0, 0, 128],
+        [0, 0, 0],
+    ];
+    assert_eq!(hsv, row(&expected));
+    assert_eq!(hsv_to_rgb(hsv.view()).unwrap(), row(&RGB));
+
+    let rgb = row(&RGB).mapv(|v| f32::from(v) / 255.0);
+    let hsv = rgb_to_hsv_f32(rgb.view()).unwrap();
+    let hues = [0.0, 60.0, 120.0, 180.0, 240.0, 300.0, 0.0, 0.0, 0.0];
+    for (x, &hue) in hues.iter().enumerate() {
+        assert_eq!(hsv[[0, x, 0]], hue);
+    }
+    assert_close(&hsv_to_rgb_f32(hsv.view()).unwrap(), &rgb, 1e-6);
+}
+
+#[test]
+fn hsv_round_trips() {
+    let rgb = rgb_grid();
+    let unit = rgb.mapv(|v| f32::from(v) / 255.0);
+    let hsv = rgb_to_hsv_f32(unit.view()).unwrap();
+    assert_close(&hsv_to_rgb_f32(hsv.view()).unwrap(), &unit, 1e-5);
+    // Hues are stored in steps of two degrees, which moves the middle channel slightly.
+    let round_tripped = hsv_to_rgb(rgb_to_hsv(rgb.view()).unwrap().view()).unwrap();
+    for (&a, &b) in round_tripped.iter().zip(&rgb) {
+        assert!((i32::from(a) - i32::from(b)).abs() <= 3, "{} {}", a, b);
+    }
+}
+
+#[test]
+fn primaries_have_known_hsl() {
+    let hsl = rgb_to_hsl(row(&RGB).view()).unwrap();
+    // Full saturation is at half lightness, which rounds up to 128.
+    let expected = [
+        [0, 255, 128],
+        [30, 255, 128],
+        [60, 255, 128],
+        [90, 255, 128],
+        [120, 255, 128],
+        [150, 255, 128],
+        [0, 0, 255],
+        [0, 0, 128],
+        [0, 0, 0],
+    ];
+    assert_eq!(hsl, row(&expected));
+    // The rounded lightness lifts the empty channels of the colors by one.
+    let rgb = hsl_to_rgb(hsl.view()).unwrap();
+    for (&a, &b) in rgb.iter().zip(&row(&RGB)) {
+        assert!((i32::from(a) - i32::from(b)).abs() <= 1, "{} {}", a, b);
+    }
+
+    let rgb = row(&RGB).mapv(|v| f32::from(v) / 255.0);
+    let hsl = rgb_to_hsl_f32(rgb.view()).unwrap();
+    for x in 0..6 {
+        assert_eq!([1, 2].map(|c| hsl[[0, x, c]]), [1.0, 0.5]);
+    }
+    assert_eq!([1, 2].map(|c| hsl[[0, 6, c]]), [0.0, 1.0]);
+    assert_close(&hsl_to_rgb_f32(hsl.view()).unwrap(), &rgb, 1e-6);
+}
+
+#[test]
+fn hsl_round_trips() {
+    let rgb = rgb_grid();
+    let unit = rgb.mapv(|v| f32::from(v) / 255.0);
+    let hsl = rgb_to_hsl_f32(unit.view()).unwrap();
+    assert_close(&hsl_to_rgb_f32(hsl.view()).unwrap(), &unit, 1e-5);
+    let round_tripped = hsl_to_rgb(rgb_to_hsl(rgb.view()).unwrap().view()).unwrap();
+    for (&a, &b) in round_tripped.iter().zip(&rgb) {
+        assert!((i32::from(a) - i32::from(b)).abs() <= 3, "{} {}", a, b);
+    }
+}
+
+#[test]
+fn hues_wrap_around() {
+    let hsv = Array3::from_shape_vec((1, 2, 3), vec![360.0, 1.0, 1.0, -120.0, 1.0, 1.0]).unwrap();
+    let rgb = hsv_to_rgb_f32(hsv.view()).unwrap();
+    assert_close(&rgb, &row(&[[1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]), 1e-6);
+}
+
+#[test]
+fn other_channel_counts_are_rejected() {
+    assert!(rgb_to_hsv(Array3::zeros((2, 2, 4)).view()).is_err());
+    assert!(hsv_to_rgb_f32(Array3::zeros((2, 2, 1)).view()).is_err());
+    assert!(rgb_to_hsl_f32(Array3::zeros((2, 2, 2)).view()).is_err());
+    assert!(hsl_to_rgb(Array3::zeros((2, 2, 4)).view()).is_err());
+}
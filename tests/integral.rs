@@ -0,0 +1,50 @@
+use ndarray::{s, Array2};
+use ndarray_image::{integral_image, integral_image_squared, integral_sum};
+
+fn noise(dim: (usize, usize)) -> Array2<u16> {
+    let mut state = 11u32;
+    Array2::from_shape_fn(dim, |_| {
+        state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        (state >> 16) as u16
+    })
+}
+
+#[test]
+fn integral_sums_match_brute_force() {
+    let image = noise((6, 8));
+    let table = integral_image(image.view());
+    let squared = integral_image_squared(image.view());
+    assert_eq!(table.dim(), (7, 9));
+    assert!(table.row(0).iter().chain(table.column(0)).all(|&v| v == 0));
+    for top in 0..=6 {
+        for bottom in top..=6 {
+            for left in 0..=8 {
+                for right in left..=8 {
+                    let window = image.slice(s![top..bottom, left..right]);
+                    let sum: u64 = window.iter().map(|&v| u64::from(v)).sum();
+                    let sum_squared: u64 = window.iter().map(|&v| u64::from(v).pow(2)).sum();
+                    assert_eq!(integral_sum(table.view(), top..bottom, left..right), sum);
+                    assert_eq!(
+                        integral_sum(squared.view(), top..bottom, left..right),
+                        sum_squared
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn integral_of_u8_ones_counts_pixels() {
+    let table = integral_image(Array2::<u8>::ones((4, 5)).view());
+    assert_eq!(table[[4, 5]], 20);
+    assert_eq!(integral_sum(table.view(), 1..3, 2..5), 6);
+}
+
+#[test]
+#[should_panic(expected = "reversed")]
+fn reversed_window_panics() {
+    let table = integral_image(Array2::<u8>::ones((4, 5)).view());
+    let (top, bottom) = (3, 1);
+    integral_sum(table.view(), top..bottom, 0..2);
+}
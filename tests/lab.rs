@@ -0,0 +1,44 @@
+mod common;
+
+use common::{assert_close, row};
+use ndarray::Array3;
+use ndarray_image::{lab_to_rgb, rgb_to_lab};
+
+#[test]
+fn primaries_have_known_lab() {
+    let rgb = row(&[
+        [1.0, 1.0, 1.0],
+        [1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [0.5, 0.5, 0.5],
+        [0.0, 0.0, 0.0],
+    ]);
+    let lab = rgb_to_lab(rgb.view()).unwrap();
+    let expected = row(&[
+        [100.0, 0.0, 0.0],
+        [53.24, 80.09, 67.20],
+        [87.73, -86.18, 83.18],
+        [32.30, 79.19, -107.86],
+        [53.39, 0.0, 0.0],
+        [0.0, 0.0, 0.0],
+    ]);
+    assert_close(&lab, &expected, 0.01);
+    assert_close(&lab_to_rgb(lab.view()).unwrap(), &rgb, 1e-5);
+}
+
+#[test]
+fn lab_round_trips() {
+    // Dark values are on the linear part of both the sRGB and the Lab curves.
+    let rgb = Array3::from_shape_fn((9, 9, 3), |(y, x, c)| {
+        [y as f32 / 8.0, x as f32 / 8.0, (y * x) as f32 / 64.0][c]
+    });
+    let lab = rgb_to_lab(rgb.view()).unwrap();
+    assert_close(&lab_to_rgb(lab.view()).unwrap(), &rgb, 1e-5);
+}
+
+#[test]
+fn colors_outside_of_srgb_are_not_clamped() {
+    let rgb = lab_to_rgb(row(&[[50.0, 0.0, -120.0]]).view()).unwrap();
+    assert!(rgb[[0, 0, 0]] < 0.0 && rgb[[0, 0, 2]] > 1.0, "{:?}", rgb);
+}
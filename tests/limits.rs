@@ -0,0 +1,59 @@
+#![cfg(feature = "png")]
+
+mod common;
+
+use common::temp_path;
+use image::error::LimitErrorKind;
+use image::ImageError;
+use ndarray::Array3;
+use ndarray_image::{
+    open_gray_image, open_gray_image_with_limits, open_image, save_image_u16, Colors, DecodeLimits,
+    NdImageError, NdImageResult,
+};
+use std::fmt::Debug;
+
+fn assert_limited<T: Debug>(result: NdImageResult<T>, expected: LimitErrorKind) {
+    match result {
+        Err(NdImageError::Image(ImageError::Limits(err))) => assert_eq!(err.kind(), expected),
+        other => panic!("expected a limit error, got {:?}", other),
+    }
+}
+
+// The global limits are shared by every test in this file, so they are all checked in one test.
+#[test]
+fn every_loader_applies_the_limits() {
+    let path = temp_path("limits.png");
+    // A 4x4 RGBA image with 16-bit samples decodes to 128 bytes, and to 16 more as gray `u8`.
+    let image = Array3::from_shape_fn((4, 4, 4), |(y, x, c)| (y * 4000 + x * 1000 + c) as u16);
+    save_image_u16(&path, image.view(), Colors::Rgba).unwrap();
+
+    let limits = |max_width, max_bytes| DecodeLimits {
+        max_width,
+        max_height: u32::MAX,
+        max_bytes,
+    };
+    assert_limited(
+        open_gray_image_with_limits(&path, limits(u32::MAX, 143)),
+        LimitErrorKind::InsufficientMemory,
+    );
+    assert_eq!(
+        open_gray_image_with_limits(&path, limits(u32::MAX, 144))
+            .unwrap()
+            .dim(),
+        (4, 4)
+    );
+
+    DecodeLimits::set_global(limits(3, usize::MAX));
+    assert_limited(
+        open_image(&path, Colors::Rgb),
+        LimitErrorKind::DimensionError,
+    );
+    DecodeLimits::set_global(limits(u32::MAX, 143));
+    assert_limited(open_gray_image(&path), LimitErrorKind::InsufficientMemory);
+    DecodeLimits::set_global(limits(u32::MAX, 144));
+    assert_eq!(open_gray_image(&path).unwrap().dim(), (4, 4));
+
+    DecodeLimits::set_global(DecodeLimits::default());
+    assert_eq!(open_image(&path, Colors::Rgb).unwrap().dim(), (4, 4, 3));
+    std::fs::remove_file(path).unwrap();
+}
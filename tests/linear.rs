@@ -0,0 +1,97 @@
+mod common;
+
+#[cfg(any(feature = "hdr", feature = "tiff", feature = "png"))]
+use common::temp_path;
+use common::{assert_close, row};
+use ndarray::Array3;
+use ndarray_image::{linear_to_srgb, rgb_to_xyz, srgb_to_linear, xyz_to_rgb};
+#[cfg(any(feature = "hdr", feature = "tiff", feature = "png"))]
+use ndarray_image::{open_image_linear, save_image_linear, Colors};
+
+/// Linear values of powers of two, which Radiance HDR stores exactly, including some above one.
+#[cfg(any(feature = "hdr", feature = "tiff", feature = "png"))]
+fn linear() -> Array3<f32> {
+    Array3::from_shape_fn((3, 4, 3), |(y, x, c)| {
+        2f32.powi(y as i32 + x as i32 - c as i32 - 3)
+    })
+}
+
+#[cfg(any(feature = "hdr", feature = "tiff"))]
+fn round_trip(extension: &str) {
+    let path = temp_path(&format!("linear.{}", extension));
+    let image = linear();
+    save_image_linear(&path, image.view(), Colors::Rgb).unwrap();
+    assert_eq!(open_image_linear(&path, Colors::Rgb).unwrap(), image);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+#[cfg(feature = "hdr")]
+fn hdr_keeps_linear_values() {
+    round_trip("hdr");
+}
+
+#[test]
+#[cfg(feature = "tiff")]
+fn tiff_keeps_linear_values() {
+    round_trip("tiff");
+}
+
+#[test]
+#[cfg(feature = "png")]
+fn png_round_trips_through_srgb() {
+    let path = temp_path("linear.png");
+    let image = linear().mapv(|v| v.min(1.0));
+    save_image_linear(&path, image.view(), Colors::Rgb).unwrap();
+    let opened = open_image_linear(&path, Colors::Rgb).unwrap();
+    for (&a, &b) in opened.iter().zip(&image) {
+        // sRGB spends its 8 bits on dark values, so they round trip more precisely.
+        assert!((a - b).abs() <= 0.005 * b.max(0.1), "{} {}", a, b);
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn srgb_has_known_linear_values() {
+    let srgb = row(&[[0.0, 0.04045, 0.5], [1.0, 0.25, 0.75]]);
+    let linear = srgb_to_linear(srgb.view());
+    let expected = row(&[[0.0, 0.003_130_8, 0.214_041], [1.0, 0.050_876, 0.522_522]]);
+    assert_close(&linear, &expected, 1e-6);
+    assert_close(&linear_to_srgb(linear.view()), &srgb, 1e-6);
+}
+
+#[test]
+fn srgb_round_trips_and_keeps_alpha() {
+    let srgb = Array3::from_shape_fn((4, 64, 4), |(y, x, c)| {
+        ((y * 64 + x) * (c + 1)) as f32 / 1020.0
+    });
+    let linear = srgb_to_linear(srgb.view());
+    assert_close(&linear_to_srgb(linear.view()), &srgb, 1e-6);
+    for (&a, &b) in linear.iter().zip(&srgb).skip(3).step_by(4) {
+        assert_eq!(a, b);
+    }
+    // Two channels are gray with alpha.
+    let gray_alpha = row(&[[0.5, 0.5]]);
+    assert_eq!(srgb_to_linear(gray_alpha.view())[[0, 0, 1]], 0.5);
+}
+
+#[test]
+fn primaries_have_known_xyz() {
+    let rgb = row(&[
+        [1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [1.0, 1.0, 1.0],
+    ]);
+    let xyz = rgb_to_xyz(rgb.view()).unwrap();
+    let expected = row(&[
+        [0.412_456, 0.212_673, 0.019_334],
+        [0.357_576, 0.715_152, 0.119_192],
+        [0.180_438, 0.072_175, 0.950_304],
+        // The D65 white point.
+        [0.950_47, 1.0, 1.088_83],
+    ]);
+    assert_close(&xyz, &expected, 1e-5);
+    assert_close(&xyz_to_rgb(xyz.view()).unwrap(), &rgb, 1e-5);
+    assert!(rgb_to_xyz(Array3::zeros((1, 1, 4)).view()).is_err());
+}
@@ -0,0 +1,198 @@
+#![cfg(all(feature = "mmap", unix))]
+
+mod common;
+
+use common::temp_path;
+use image::ImageError;
+use ndarray::{s, Array3, Axis};
+use ndarray_image::{open_image_mmap, Colors, MmapImage, NdImageError, NdImageResult};
+
+fn pixels(height: usize, width: usize, channels: usize) -> Array3<u8> {
+    Array3::from_shape_fn((height, width, channels), |(y, x, c)| {
+        (y * 50 + x * 10 + c) as u8
+    })
+}
+
+/// Maps a file with the given contents, removing it again once it is mapped.
+fn map(name: &str, contents: &[u8]) -> NdImageResult<MmapImage> {
+    let path = temp_path(name);
+    std::fs::write(&path, contents).unwrap();
+    // SAFETY: the file is not modified while it is mapped, and is only unlinked.
+    let image = unsafe { open_image_mmap(&path) };
+    std::fs::remove_file(path).unwrap();
+    image
+}
+
+fn assert_unsupported(result: NdImageResult<MmapImage>, message: &str) {
+    match result {
+        Err(NdImageError::Image(ImageError::Unsupported(err))) => {
+            assert!(err.to_string().contains(message), "{}", err)
+        }
+        Err(err) => panic!("expected an unsupported error, got {:?}", err),
+        Ok(_) => panic!("expected an unsupported error, got an image"),
+    }
+}
+
+/// A binary PGM or PPM image.
+fn pnm(image: &Array3<u8>) -> Vec<u8> {
+    let (height, width, channels) = image.dim();
+    let magic = if channels == 1 { "P5" } else { "P6" };
+    let mut pnm = format!("{}\n# a comment\n{} {}\n255\n", magic, width, height).into_bytes();
+    pnm.extend(image.iter());
+    pnm
+}
+
+/// An uncompressed BMP image of RGB(A) pixels, with its rows stored from the bottom up unless
+/// `top_down` is set.
+fn bmp(image: &Array3<u8>, top_down: bool) -> Vec<u8> {
+    let (height, width, channels) = image.dim();
+    let row_stride = (width * channels * 8).div_ceil(32) * 4;
+    let size = 54 + row_stride * height;
+    let height_field = if top_down {
+        -(height as i32)
+    } else {
+        height as i32
+    };
+    let mut bmp = b"BM".to_vec();
+    bmp.extend_from_slice(&(size as u32).to_le_bytes());
+    bmp.extend_from_slice(&[0; 4]);
+    bmp.extend_from_slice(&54u32.to_le_bytes());
+    bmp.extend_from_slice(&40u32.to_le_bytes());
+    bmp.extend_from_slice(&(width as i32).to_le_bytes());
+    bmp.extend_from_slice(&height_field.to_le_bytes());
+    bmp.extend_from_slice(&1u16.to_le_bytes());
+    bmp.extend_from_slice(&(channels as u16 * 8).to_le_bytes());
+    bmp.extend_from_slice(&[0; 24]);
+    let rows: Vec<usize> = if top_down {
+        (0..height).collect()
+    } else {
+        (0..height).rev().collect()
+    };
+    for y in rows {
+        let row = image.slice(s![y, .., ..]);
+        // BMP pixels are stored in BGR(A) order.
+        for pixel in row.outer_iter() {
+            let mut pixel = pixel.to_vec();
+            pixel.swap(0, 2);
+            bmp.extend(pixel);
+        }
+        bmp.resize(bmp.len() + row_stride - width * channels, 0);
+    }
+    bmp
+}
+
+/// Replaces the value of a short TIFF tag in the first IFD of a little endian TIFF image.
+fn set_tiff_tag(tiff: &mut [u8], tag: u16, value: u16) {
+    let ifd = u32::from_le_bytes([tiff[4], tiff[5], tiff[6], tiff[7]]) as usize;
+    let count = usize::from(u16::from_le_bytes([tiff[ifd], tiff[ifd + 1]]));
+    let entry = (0..count)
+        .map(|i| ifd + 2 + i * 12)
+        .find(|&entry| u16::from_le_bytes([tiff[entry], tiff[entry + 1]]) == tag)
+        .unwrap();
+    tiff[entry + 8..entry + 10].copy_from_slice(&value.to_le_bytes());
+}
+
+#[test]
+fn pnm_images_are_viewed_in_place() {
+    for &channels in &[1, 3] {
+        let image = pixels(3, 5, channels);
+        let mapped = map(&format!("pnm-{}", channels), &pnm(&image)).unwrap();
+        assert_eq!(mapped.view(), image);
+        let colors = if channels == 1 {
+            Colors::Luma
+        } else {
+            Colors::Rgb
+        };
+        assert_eq!(mapped.colors(), colors);
+    }
+}
+
+#[test]
+fn bmp_images_are_viewed_in_bgr_order() {
+    // The rows of 5 pixels of 3 channels are padded to 4 bytes.
+    for &channels in &[3, 4] {
+        let image = pixels(3, 5, channels);
+        let mut bgr = image.clone();
+        for mut pixel in bgr.lanes_mut(Axis(2)) {
+            pixel.swap(0, 2);
+        }
+        for &top_down in &[false, true] {
+            let name = format!("bmp-{}-{}", channels, top_down);
+            let mapped = map(&name, &bmp(&image, top_down)).unwrap();
+            assert_eq!(mapped.view(), bgr);
+        }
+        let mapped = map("bmp-colors", &bmp(&image, false)).unwrap();
+        let colors = if channels == 3 {
+            Colors::Bgr
+        } else {
+            Colors::Bgra
+        };
+        assert_eq!(mapped.colors(), colors);
+    }
+}
+
+#[cfg(feature = "tiff")]
+#[test]
+fn tiff_images_are_viewed_in_place() {
+    for &(colors, channels) in &[(Colors::Luma, 1), (Colors::Rgb, 3), (Colors::Rgba, 4)] {
+        let image = pixels(4, 7, channels);
+        let path = temp_path(&format!("mmap-{}.tiff", channels));
+        ndarray_image::save_image(&path, image.view(), colors).unwrap();
+        let tiff = std::fs::read(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let mapped = map(&format!("tiff-{}", channels), &tiff).unwrap();
+        assert_eq!(mapped.view(), image);
+        assert_eq!(mapped.colors(), colors);
+    }
+}
+
+#[test]
+fn truncated_images_are_rejected() {
+    let image = pixels(3, 5, 3);
+    let mut data = pnm(&image);
+    data.truncate(data.len() - 1);
+    assert_unsupported(map("truncated-pnm", &data), "truncated");
+    let mut data = bmp(&image, false);
+    data.truncate(data.len() - 4);
+    assert_unsupported(map("truncated-bmp", &data), "truncated");
+    // The header itself ends before the sizes of the image.
+    assert_unsupported(map("truncated-header", b"BM\0\0"), "compressed or unknown");
+    assert_unsupported(map("empty", b""), "empty");
+}
+
+#[test]
+fn compressed_images_are_rejected() {
+    let mut data = bmp(&pixels(2, 2, 3), false);
+    // Run length encoding.
+    data[30] = 1;
+    assert_unsupported(map("rle-bmp", &data), "compressed");
+    assert_unsupported(map("unknown", b"\x89PNG\r\n\x1a\n"), "compressed");
+}
+
+#[cfg(feature = "tiff")]
+#[test]
+fn compressed_tiff_images_are_rejected() {
+    let path = temp_path("mmap-compressed.tiff");
+    ndarray_image::save_image(&path, pixels(2, 2, 3).view(), Colors::Rgb).unwrap();
+    let mut tiff = std::fs::read(&path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    // LZW compression.
+    set_tiff_tag(&mut tiff, 259, 5);
+    assert_unsupported(map("lzw-tiff", &tiff), "compressed");
+}
+
+#[test]
+fn oversized_headers_are_rejected() {
+    // The pixels would take more bytes than fit in memory.
+    let huge = format!("P6\n{} 2\n255\n", usize::MAX / 2);
+    assert_unsupported(map("huge-pnm", huge.as_bytes()), "truncated");
+    let huge = b"P5\n99999999999999999999999 1\n255\n";
+    assert_unsupported(map("unparsable-pnm", huge), "compressed or unknown");
+    // The pixels would start beyond the end of the file.
+    let mut data = bmp(&pixels(2, 2, 3), false);
+    data[10..14].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_unsupported(map("offset-bmp", &data), "truncated");
+    let mut data = bmp(&pixels(2, 2, 3), false);
+    data[18..22].copy_from_slice(&i32::MAX.to_le_bytes());
+    assert_unsupported(map("wide-bmp", &data), "truncated");
+}
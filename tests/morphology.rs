@@ -0,0 +1,52 @@
+use ndarray::{arr2, s, Array2, Array3, Axis};
+use ndarray_image::{close, dilate, erode, open, tophat};
+
+/// A 9x9 mask with a square of `range` rows and columns.
+fn filled(range: std::ops::Range<usize>) -> Array2<u8> {
+    let mut mask = Array2::zeros((9, 9));
+    mask.slice_mut(s![range.clone(), range]).fill(255);
+    mask
+}
+
+#[test]
+fn erode_and_dilate_a_square() {
+    let element = Array2::ones((3, 3));
+    assert_eq!(erode(filled(2..7).view(), element.view()), filled(3..6));
+    assert_eq!(dilate(filled(2..7).view(), element.view()), filled(1..8));
+    let square = filled(2..7);
+    let color = Array3::from_shape_fn((9, 9, 2), |(y, x, c)| square[[y, x]] / (c as u8 + 1));
+    let eroded = erode(color.view(), element.view());
+    assert_eq!(eroded.index_axis(Axis(2), 0), filled(3..6));
+}
+
+#[test]
+fn borders_are_ignored() {
+    let full = Array2::from_elem((4, 4), 9u8);
+    let element = Array2::ones((3, 3));
+    assert_eq!(erode(full.view(), element.view()), full);
+    assert_eq!(dilate(full.view(), element.view()), full);
+}
+
+#[test]
+fn dilation_mirrors_asymmetric_elements() {
+    let element = arr2(&[[0, 1, 1]]);
+    let row = arr2(&[[0u8, 0, 5, 0, 0]]);
+    assert_eq!(dilate(row.view(), element.view()), arr2(&[[0, 0, 5, 5, 0]]));
+    let row = arr2(&[[0u8, 5, 5, 5, 0]]);
+    assert_eq!(erode(row.view(), element.view()), arr2(&[[0, 5, 5, 0, 0]]));
+}
+
+#[test]
+fn opening_closing_and_tophat() {
+    let element = Array2::ones((3, 3));
+    let mut speckled = filled(2..7);
+    speckled[[0, 8]] = 255;
+    assert_eq!(open(speckled.view(), element.view()), filled(2..7));
+    let mut expected = Array2::zeros((9, 9));
+    expected[[0, 8]] = 255;
+    assert_eq!(tophat(speckled.view(), element.view()), expected);
+
+    let mut holed = filled(2..7);
+    holed[[4, 4]] = 0;
+    assert_eq!(close(holed.view(), element.view()), filled(2..7));
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "png")]
+
+mod common;
+
+use common::temp_path;
+use ndarray::Array3;
+use ndarray_image::{open_image_f32_normalized, save_image, Colors, ConversionError, NdImageError};
+
+#[test]
+fn normalized_images_match_normalizing_each_value() {
+    let mean = [0.485, 0.456, 0.406, 0.5];
+    let std = [0.229, 0.224, 0.225, 0.25];
+    for &(colors, channels) in &[
+        (Colors::Luma, 1),
+        (Colors::LumaA, 2),
+        (Colors::Rgb, 3),
+        (Colors::Rgba, 4),
+    ] {
+        // 7 pixels per row leave a remainder after every whole block of 4 pixels.
+        let image = Array3::from_shape_fn((5, 7, channels), |(y, x, c)| {
+            (y * 53 + x * 31 + c * 17) as u8
+        });
+        let path = temp_path(&format!("normalize-{}.png", channels));
+        save_image(&path, image.view(), colors).unwrap();
+        let (mean, std) = (&mean[..channels], &std[..channels]);
+        let normalized = open_image_f32_normalized(&path, colors, mean, std).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let expected = Array3::from_shape_fn(image.dim(), |(y, x, c)| {
+            let (scale, bias) = (1.0 / (255.0 * std[c]), -mean[c] / std[c]);
+            f32::from(image[(y, x, c)]) * scale + bias
+        });
+        assert_eq!(normalized, expected);
+    }
+}
+
+#[test]
+fn wrong_numbers_of_values_are_rejected() {
+    // The values are checked before the image is opened.
+    let missing = open_image_f32_normalized("missing.png", Colors::Rgb, &[0.5; 2], &[0.5; 3]);
+    assert!(matches!(
+        missing,
+        Err(NdImageError::Conversion(
+            ConversionError::WrongChannelCount {
+                expected: 3,
+                got: 2
+            }
+        ))
+    ));
+}
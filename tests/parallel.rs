@@ -0,0 +1,33 @@
+#![cfg(all(feature = "rayon", feature = "png"))]
+
+mod common;
+
+use common::temp_path;
+use ndarray::Array3;
+use ndarray_image::{open_images_parallel, save_images_parallel, Colors};
+
+#[test]
+fn bounded_saves_keep_the_order_of_the_images() {
+    let images: Vec<Array3<u8>> = (0..5)
+        .map(|i| Array3::from_shape_fn((3, 4, 3), |(y, x, c)| (i * 40 + y * 8 + x * 2 + c) as u8))
+        .collect();
+    let paths: Vec<_> = (0..5)
+        .map(|i| temp_path(&format!("parallel-{}.png", i)))
+        .collect();
+    for &max_in_flight in &[None, Some(0), Some(2)] {
+        let pairs: Vec<_> = paths
+            .iter()
+            .zip(&images)
+            .map(|(path, image)| (path, image.view()))
+            .collect();
+        let saved = save_images_parallel(&pairs, Colors::Rgb, max_in_flight);
+        assert!(saved.iter().all(Result::is_ok));
+        let opened = open_images_parallel(&paths, Colors::Rgb);
+        for (opened, image) in opened.into_iter().zip(&images) {
+            assert_eq!(&opened.unwrap(), image);
+        }
+    }
+    for path in paths {
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,150 @@
+#![cfg(feature = "png")]
+
+mod common;
+
+use common::temp_path;
+use ndarray::Array3;
+use ndarray_image::{
+    open_image, open_image_u16, save_image_u16_with_options, save_image_with_options, Colors,
+    PngFilter, SaveOptions,
+};
+use std::convert::TryFrom;
+
+/// Pixels that mix a gradient with noise, so that every filter has something to predict.
+fn pixels<A>(dim: (usize, usize, usize), max: u32) -> Array3<A>
+where
+    A: TryFrom<u32>,
+    A::Error: std::fmt::Debug,
+{
+    let mut state = 0x2545_f491u32;
+    Array3::from_shape_fn(dim, |(y, x, c)| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        let value = (y * 3 + x * 5 + c * 7) as u32 + (state & 0x0f);
+        A::try_from(value % (max + 1)).unwrap()
+    })
+}
+
+fn parallel(png_filter: PngFilter) -> SaveOptions {
+    SaveOptions {
+        png_filter,
+        png_parallel: true,
+        ..SaveOptions::default()
+    }
+}
+
+const FILTERS: [PngFilter; 5] = [
+    PngFilter::NoFilter,
+    PngFilter::Sub,
+    PngFilter::Up,
+    PngFilter::Avg,
+    PngFilter::Paeth,
+];
+
+#[test]
+fn parallel_png_round_trips() {
+    let path = temp_path("u8.png");
+    for &(colors, channels) in &[
+        (Colors::Luma, 1),
+        (Colors::LumaA, 2),
+        (Colors::Rgb, 3),
+        (Colors::Rgba, 4),
+    ] {
+        let image = pixels::<u8>((13, 17, channels), 255);
+        for &filter in &FILTERS {
+            save_image_with_options(&path, image.view(), colors, parallel(filter)).unwrap();
+            assert_eq!(open_image(&path, colors).unwrap(), image, "{:?}", filter);
+        }
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn parallel_png_round_trips_u16() {
+    let path = temp_path("u16.png");
+    for &(colors, channels) in &[(Colors::Luma, 1), (Colors::Rgb, 3), (Colors::Rgba, 4)] {
+        let image = pixels::<u16>((11, 19, channels), 260).mapv(|v| v * 251);
+        for &filter in &FILTERS {
+            save_image_u16_with_options(&path, image.view(), colors, parallel(filter)).unwrap();
+            assert_eq!(
+                open_image_u16(&path, colors).unwrap(),
+                image,
+                "{:?}",
+                filter
+            );
+        }
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn parallel_png_spans_several_chunks() {
+    // Over a megabyte of pixels, with a row count that does not divide evenly into chunks.
+    let path = temp_path("chunks.png");
+    let image = pixels::<u8>((1001, 523, 3), 255);
+    for &filter in &[PngFilter::Up, PngFilter::Paeth] {
+        save_image_with_options(&path, image.view(), Colors::Rgb, parallel(filter)).unwrap();
+        assert_eq!(
+            open_image(&path, Colors::Rgb).unwrap(),
+            image,
+            "{:?}",
+            filter
+        );
+    }
+    let image = pixels::<u16>((701, 389, 4), 255).mapv(|v| v * 256 + v % 7);
+    save_image_u16_with_options(&path, image.view(), Colors::Rgba, parallel(PngFilter::Sub))
+        .unwrap();
+    assert_eq!(open_image_u16(&path, Colors::Rgba).unwrap(), image);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn fast_png_round_trips() {
+    let path = temp_path("fast.png");
+    let image = pixels::<u8>((9, 14, 4), 255);
+    save_image_with_options(&path, image.view(), Colors::Rgba, SaveOptions::fast()).unwrap();
+    assert_eq!(open_image(&path, Colors::Rgba).unwrap(), image);
+    std::fs::remove_file(path).unwrap();
+}
+
+fn interlaced(png_filter: PngFilter) -> SaveOptions {
+    SaveOptions {
+        png_filter,
+        png_interlace: true,
+        ..SaveOptions::default()
+    }
+}
+
+#[test]
+fn interlaced_png_round_trips() {
+    let path = temp_path("interlaced.png");
+    // Images smaller than 8x8 leave some of the Adam7 passes without pixels.
+    for &(height, width) in &[(1, 1), (1, 5), (3, 2), (5, 3), (13, 17)] {
+        for &(colors, channels) in &[(Colors::Luma, 1), (Colors::LumaA, 2), (Colors::Rgb, 3)] {
+            let image = pixels::<u8>((height, width, channels), 255);
+            for &filter in &FILTERS {
+                let options = interlaced(filter);
+                save_image_with_options(&path, image.view(), colors, options).unwrap();
+                let opened = open_image(&path, colors).unwrap();
+                assert_eq!(opened, image, "{:?} {}x{}", filter, width, height);
+            }
+        }
+    }
+    // The interlace method is the last byte of the header.
+    assert_eq!(std::fs::read(&path).unwrap()[28], 1);
+    let image = pixels::<u16>((9, 11, 4), 260).mapv(|v| v * 251);
+    let options = interlaced(PngFilter::Paeth);
+    save_image_u16_with_options(&path, image.view(), Colors::Rgba, options).unwrap();
+    assert_eq!(open_image_u16(&path, Colors::Rgba).unwrap(), image);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn interlaced_png_spans_several_chunks() {
+    let path = temp_path("interlaced-chunks.png");
+    let image = pixels::<u8>((1001, 1523, 3), 255);
+    save_image_with_options(&path, image.view(), Colors::Rgb, interlaced(PngFilter::Up)).unwrap();
+    assert_eq!(open_image(&path, Colors::Rgb).unwrap(), image);
+    std::fs::remove_file(path).unwrap();
+}
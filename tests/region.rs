@@ -0,0 +1,176 @@
+#![cfg(feature = "tiff")]
+
+mod common;
+
+use common::temp_path;
+use image::ImageError;
+use ndarray::{s, Array3};
+use ndarray_image::{open_image_region, save_image_u16, Colors, NdImageError};
+
+/// Stands for the offset of the pixels in the values of TIFF entries.
+const PIXELS: u32 = u32::MAX;
+
+/// A little endian TIFF with one page, made of entries with a tag, a SHORT or LONG type, and
+/// values, sorted by tag, followed by the pixels.
+fn tiff(entries: &[(u16, u16, &[u32])], pixels: &[u8]) -> Vec<u8> {
+    let size = |kind: u16| if kind == 3 { 2 } else { 4 };
+    let ifd_end = 8 + 2 + entries.len() * 12 + 4;
+    let outside: usize = entries
+        .iter()
+        .map(|&(_, kind, values)| size(kind) * values.len())
+        .filter(|&len| len > 4)
+        .sum();
+    let pixels_offset = (ifd_end + outside) as u32;
+    let bytes = |kind: u16, values: &[u32]| -> Vec<u8> {
+        values
+            .iter()
+            .map(|&v| if v == PIXELS { pixels_offset } else { v })
+            .flat_map(|v| v.to_le_bytes()[..size(kind)].to_vec())
+            .collect()
+    };
+
+    let mut tiff = b"II*\0\x08\0\0\0".to_vec();
+    let mut values = Vec::new();
+    tiff.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    for &(tag, kind, entry) in entries {
+        tiff.extend_from_slice(&tag.to_le_bytes());
+        tiff.extend_from_slice(&kind.to_le_bytes());
+        tiff.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+        let mut entry = bytes(kind, entry);
+        if entry.len() > 4 {
+            let offset = (ifd_end + values.len()) as u32;
+            values.append(&mut entry);
+            entry = offset.to_le_bytes().to_vec();
+        }
+        entry.resize(4, 0);
+        tiff.extend_from_slice(&entry);
+    }
+    tiff.extend_from_slice(&0u32.to_le_bytes());
+    tiff.extend_from_slice(&values);
+    tiff.extend_from_slice(pixels);
+    tiff
+}
+
+/// A little endian TIFF of 8x2 gray pixels with `bits` per sample in one uncompressed strip.
+fn gray_tiff(bits: u16) -> Vec<u8> {
+    let strip_len = 2 * u32::from(bits);
+    tiff(
+        &[
+            (256, 3, &[8]),
+            (257, 3, &[2]),
+            (258, 3, &[u32::from(bits)]),
+            (259, 3, &[1]),
+            (262, 3, &[1]),
+            (273, 4, &[PIXELS]),
+            (278, 3, &[2]),
+            (279, 4, &[strip_len]),
+        ],
+        &vec![0xa5; strip_len as usize],
+    )
+}
+
+fn assert_unsupported(result: Result<Array3<u8>, NdImageError>) {
+    assert!(
+        matches!(result, Err(NdImageError::Image(ImageError::Unsupported(_)))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn region_of_a_16_bit_tiff() {
+    let path = temp_path("region.tiff");
+    let image = Array3::from_shape_fn((5, 6, 3), |(y, x, c)| (y * 1000 + x * 100 + c) as u16 * 9);
+    save_image_u16(&path, image.view(), Colors::Rgb).unwrap();
+    let region = open_image_region(&path, 1, 2, 3, 2, Colors::Rgb).unwrap();
+    let expected = image.slice(s![2..4, 1..4, ..]).mapv(|v| (v / 257) as u8);
+    assert!(region
+        .iter()
+        .zip(&expected)
+        .all(|(&a, &b)| a.abs_diff(b) <= 1));
+    let empty = open_image_region(&path, 1, 5, 3, 0, Colors::Rgb).unwrap();
+    assert_eq!(empty.dim(), (0, 3, 3));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn packed_tiff_depths_are_unsupported() {
+    let path = temp_path("packed.tiff");
+    for &bits in &[1, 4] {
+        std::fs::write(&path, gray_tiff(bits)).unwrap();
+        assert_unsupported(open_image_region(&path, 0, 0, 4, 2, Colors::Luma));
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn tiled_tiffs_are_unsupported() {
+    let path = temp_path("tiled.tiff");
+    let image = tiff(
+        &[
+            (256, 3, &[16]),
+            (257, 3, &[16]),
+            (258, 3, &[8]),
+            (259, 3, &[1]),
+            (262, 3, &[1]),
+            (322, 3, &[16]),
+            (323, 3, &[16]),
+            (324, 4, &[PIXELS]),
+            (325, 4, &[256]),
+        ],
+        &[0x5a; 256],
+    );
+    std::fs::write(&path, image).unwrap();
+    assert_unsupported(open_image_region(&path, 0, 0, 4, 4, Colors::Luma));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn planar_tiffs_are_unsupported() {
+    let path = temp_path("planar.tiff");
+    let image = tiff(
+        &[
+            (256, 3, &[2]),
+            (257, 3, &[2]),
+            (258, 3, &[8, 8, 8]),
+            (259, 3, &[1]),
+            (262, 3, &[2]),
+            (273, 4, &[PIXELS]),
+            (277, 3, &[3]),
+            (278, 3, &[2]),
+            (279, 4, &[12]),
+            (284, 3, &[2]),
+        ],
+        &[0x5a; 12],
+    );
+    std::fs::write(&path, image).unwrap();
+    assert_unsupported(open_image_region(&path, 0, 0, 2, 2, Colors::Rgb));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn truncated_tiff_strips_are_decoding_errors() {
+    let path = temp_path("truncated.tiff");
+    // The strip claims 16 bytes, but the file ends after 6 of them.
+    let image = tiff(
+        &[
+            (256, 3, &[8]),
+            (257, 3, &[2]),
+            (258, 3, &[8]),
+            (259, 3, &[1]),
+            (262, 3, &[1]),
+            (273, 4, &[PIXELS]),
+            (278, 3, &[2]),
+            (279, 4, &[16]),
+        ],
+        &[0xa5; 6],
+    );
+    std::fs::write(&path, image).unwrap();
+    let result = open_image_region(&path, 0, 0, 4, 2, Colors::Luma);
+    assert!(
+        matches!(result, Err(NdImageError::Image(ImageError::Decoding(_)))),
+        "{:?}",
+        result
+    );
+    std::fs::remove_file(path).unwrap();
+}
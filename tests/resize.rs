@@ -0,0 +1,57 @@
+mod common;
+
+use common::gradient;
+use ndarray::Array3;
+use ndarray_image::{resize, ResizeFilter};
+
+const FILTERS: [ResizeFilter; 3] = [
+    ResizeFilter::Nearest,
+    ResizeFilter::Bilinear,
+    ResizeFilter::Lanczos3,
+];
+
+#[test]
+fn constant_stays_constant() {
+    let image = Array3::from_elem((9, 13, 3), 77u8);
+    for &filter in &FILTERS {
+        for &dim in &[(9, 13), (4, 5), (20, 31), (1, 1), (9, 40)] {
+            let resized = resize(image.view(), dim, filter);
+            assert_eq!(
+                resized,
+                Array3::from_elem((dim.0, dim.1, 3), 77),
+                "{:?}",
+                filter
+            );
+        }
+    }
+}
+
+#[test]
+fn same_size_copies() {
+    let image = gradient((7, 10, 2));
+    for &filter in &FILTERS {
+        assert_eq!(resize(image.view(), (7, 10), filter), image, "{:?}", filter);
+    }
+}
+
+#[test]
+fn nearest_upscale_repeats_pixels() {
+    let image = gradient((3, 4, 3));
+    let resized = resize(image.view(), (6, 12), ResizeFilter::Nearest);
+    for ((y, x, c), &v) in resized.indexed_iter() {
+        assert_eq!(v, image[[y / 2, x / 3, c]]);
+    }
+}
+
+#[test]
+fn bilinear_keeps_ramps_monotonic() {
+    let image = Array3::from_shape_fn((3, 6, 1), |(_, x, _)| (x * 40) as u8);
+    for &width in &[3, 17] {
+        let resized = resize(image.view(), (5, width), ResizeFilter::Bilinear);
+        for row in resized.outer_iter() {
+            let row: Vec<u8> = row.iter().cloned().collect();
+            assert!(row.windows(2).all(|pair| pair[0] <= pair[1]), "{:?}", row);
+            assert!(row[0] < 40 && row[width - 1] > 160, "{:?}", row);
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use ndarray::{s, Array3, ArrayViewMut3};
+use ndarray_image::swap_rb;
+
+fn pixels(height: usize, width: usize, channels: usize) -> Array3<u8> {
+    Array3::from_shape_fn((height, width, channels), |(y, x, c)| {
+        (y * 131 + x * 7 + c * 3) as u8
+    })
+}
+
+/// Swaps the red and blue channels one sample at a time.
+fn swapped(image: &Array3<u8>) -> Array3<u8> {
+    let mut swapped = image.clone();
+    for ((y, x, c), value) in swapped.indexed_iter_mut() {
+        let source = match c {
+            0 => 2,
+            2 => 0,
+            c => c,
+        };
+        *value = image[(y, x, source)];
+    }
+    swapped
+}
+
+fn assert_swaps(mut view: ArrayViewMut3<'_, u8>) {
+    let expected = swapped(&view.to_owned());
+    swap_rb(view.view_mut()).unwrap();
+    assert_eq!(view, expected);
+}
+
+#[test]
+fn contiguous_images_match_swapping_each_pixel() {
+    // The SIMD path shuffles 16 pixels of 3 channels or 4 pixels of 4 channels at a time, so
+    // these sizes cover no whole block, exact blocks, and blocks with a remainder.
+    for &channels in &[3, 4] {
+        for &(height, width) in &[(1, 1), (1, 3), (1, 4), (1, 16), (2, 16), (3, 7), (5, 37)] {
+            assert_swaps(pixels(height, width, channels).view_mut());
+        }
+    }
+}
+
+#[test]
+fn offset_images_match_swapping_each_pixel() {
+    for &channels in &[3, 4] {
+        // Skipping rows keeps the view contiguous, but not aligned to the start of the array.
+        let mut image = pixels(9, 21, channels);
+        assert_swaps(image.slice_mut(s![1.., .., ..]));
+        let mut image = pixels(1, 40, channels);
+        assert_swaps(image.slice_mut(s![.., 3.., ..]));
+    }
+}
+
+#[test]
+fn strided_images_match_swapping_each_pixel() {
+    for &channels in &[3, 4] {
+        let mut image = pixels(6, 35, channels);
+        assert_swaps(image.slice_mut(s![.., ..;2, ..]));
+        let mut image = pixels(6, 35, channels);
+        assert_swaps(image.slice_mut(s![1..5, 2..30, ..]));
+        let mut image = pixels(6, 35, channels);
+        assert_swaps(image.slice_mut(s![..;-1, ..;-1, ..]));
+    }
+}
+
+#[test]
+fn swapping_twice_restores_the_image() {
+    for &channels in &[3, 4] {
+        let original = pixels(7, 53, channels);
+        let mut image = original.clone();
+        swap_rb(image.view_mut()).unwrap();
+        assert_ne!(image, original);
+        swap_rb(image.view_mut()).unwrap();
+        assert_eq!(image, original);
+    }
+}
+
+#[test]
+fn other_channel_counts_are_rejected() {
+    for &channels in &[1, 2, 5] {
+        assert!(swap_rb(pixels(2, 2, channels).view_mut()).is_err());
+    }
+}
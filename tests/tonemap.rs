@@ -0,0 +1,72 @@
+mod common;
+
+use common::row;
+use ndarray::Array3;
+use ndarray_image::{tonemap, ToneMapOperator};
+
+const OPERATORS: [ToneMapOperator; 3] = [
+    ToneMapOperator::Reinhard,
+    ToneMapOperator::Aces,
+    ToneMapOperator::Filmic,
+];
+
+#[test]
+fn reinhard_has_known_values() {
+    // 1.0 maps to 0.5, which is 0.7354 in sRGB.
+    let image = row(&[[0.0, 1.0, 3.0]]);
+    let mapped = tonemap(image.view(), ToneMapOperator::Reinhard, 0.0);
+    assert_eq!(mapped, row(&[[0, 188, 225]]));
+}
+
+#[test]
+fn exposure_is_in_stops() {
+    let image = row(&[[0.25, 0.5, 1.0], [2.0, 4.0, 8.0]]);
+    for &operator in &OPERATORS {
+        let brighter = tonemap(image.view(), operator, 1.0);
+        let doubled = tonemap(image.mapv(|v| v * 2.0).view(), operator, 0.0);
+        assert_eq!(brighter, doubled, "{:?}", operator);
+        let darker = tonemap(image.view(), operator, -2.0);
+        let quartered = tonemap(image.mapv(|v| v / 4.0).view(), operator, 0.0);
+        assert_eq!(darker, quartered, "{:?}", operator);
+    }
+}
+
+#[test]
+fn curves_are_monotonic_from_black_to_white() {
+    let ramp = Array3::from_shape_fn((1, 256, 3), |(_, x, _)| (x as f32 / 16.0).exp2() - 1.0);
+    for &operator in &OPERATORS {
+        let mapped = tonemap(ramp.view(), operator, 0.0);
+        assert_eq!(mapped[[0, 0, 0]], 0, "{:?}", operator);
+        assert!(mapped[[0, 255, 0]] >= 250, "{:?}", operator);
+        for pair in mapped.as_slice().unwrap().windows(2) {
+            assert!(pair[0] <= pair[1], "{:?}", operator);
+        }
+    }
+}
+
+#[test]
+fn extreme_values_are_clamped() {
+    let image = row(&[[f32::INFINITY, 1e30, -1.0], [f32::NEG_INFINITY, -1e30, 0.0]]);
+    for &operator in &OPERATORS {
+        let mapped = tonemap(image.view(), operator, 0.0);
+        assert_eq!(mapped, row(&[[255, 255, 0], [0, 0, 0]]), "{:?}", operator);
+        // A large exposure does not overflow either.
+        let mapped = tonemap(image.view(), operator, 200.0);
+        assert_eq!(mapped, row(&[[255, 255, 0], [0, 0, 0]]), "{:?}", operator);
+    }
+}
+
+#[test]
+fn alpha_is_only_clamped() {
+    let gray_alpha = row(&[[1.0, 0.5], [1.0, 2.0], [1.0, -1.0]]);
+    let mapped = tonemap(gray_alpha.view(), ToneMapOperator::Reinhard, 0.0);
+    assert_eq!(mapped, row(&[[188, 128], [188, 255], [188, 0]]));
+    // Exposure leaves alpha alone.
+    let rgba = row(&[[0.5, 0.5, 0.5, 0.5]]);
+    let mapped = tonemap(rgba.view(), ToneMapOperator::Reinhard, 1.0);
+    assert_eq!(mapped, row(&[[188, 188, 188, 128]]));
+    // Three channels have no alpha, so every channel is mapped.
+    let rgb = row(&[[1.0, 1.0, 0.5]]);
+    let mapped = tonemap(rgb.view(), ToneMapOperator::Reinhard, 0.0);
+    assert_eq!(mapped, row(&[[188, 188, 156]]));
+}
@@ -0,0 +1,53 @@
+use ndarray::Array3;
+use ndarray_image::{undistort, undistort_maps, CameraIntrinsics, DistortionCoeffs};
+
+const CAMERA: CameraIntrinsics = CameraIntrinsics {
+    fx: 100.0,
+    fy: 80.0,
+    cx: 50.0,
+    cy: 40.0,
+};
+
+#[test]
+fn no_distortion_is_identity() {
+    let (map_x, map_y) = undistort_maps(&CAMERA, &DistortionCoeffs::default(), (30, 45));
+    for ((y, x), &mx) in map_x.indexed_iter() {
+        assert!((mx - x as f32).abs() < 1e-4 && (map_y[[y, x]] - y as f32).abs() < 1e-4);
+    }
+    let image = Array3::from_shape_fn((30, 45, 3), |(y, x, c)| (y * 5 + x * 3 + c) as u8);
+    assert_eq!(
+        undistort(image.view(), &CAMERA, &DistortionCoeffs::default()),
+        image
+    );
+}
+
+#[test]
+fn radial_distortion_scales_with_radius() {
+    let distortion = DistortionCoeffs {
+        k1: 0.1,
+        k2: 0.01,
+        ..DistortionCoeffs::default()
+    };
+    let (map_x, map_y) = undistort_maps(&CAMERA, &distortion, (130, 160));
+    // The principal point does not move.
+    assert_eq!((map_x[[40, 50]], map_y[[40, 50]]), (50.0, 40.0));
+    // One focal length to the right, the radius is one and the point moves out by k1 + k2.
+    assert!((map_x[[40, 150]] - 161.0).abs() < 1e-3);
+    assert!((map_y[[40, 150]] - 40.0).abs() < 1e-3);
+    // One focal length down, it moves out by the same factor.
+    assert!((map_x[[120, 50]] - 50.0).abs() < 1e-3);
+    assert!((map_y[[120, 50]] - (40.0 + 80.0 * 1.11)).abs() < 1e-3);
+}
+
+#[test]
+fn tangential_distortion_shifts_along_the_axes() {
+    let distortion = DistortionCoeffs {
+        p1: 0.05,
+        p2: 0.02,
+        ..DistortionCoeffs::default()
+    };
+    let (map_x, map_y) = undistort_maps(&CAMERA, &distortion, (100, 160));
+    // At `(x, y) = (1, 0)` the shift is `(3 * p2, p1)` in normalized coordinates.
+    assert!((map_x[[40, 150]] - (150.0 + 100.0 * 0.06)).abs() < 1e-3);
+    assert!((map_y[[40, 150]] - (40.0 + 80.0 * 0.05)).abs() < 1e-3);
+}
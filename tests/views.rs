@@ -0,0 +1,193 @@
+use image::flat::SampleLayout;
+use image::{FlatSamples, ImageBuffer, Luma, Rgb};
+use ndarray_image::nshare::ToNdarray3;
+#[allow(deprecated)]
+use ndarray_image::NdImage;
+use ndarray_image::{
+    try_view_color, try_view_gray, ConversionError, IntoNdColor, IntoNdGray, NdColor, NdColorMut,
+    NdGray, NdGrayMut, ToImageBuffer,
+};
+use std::cell::Cell;
+use std::convert::TryFrom;
+use std::ops::Deref;
+
+fn samples(len: usize) -> Vec<u8> {
+    (0..len).map(|i| i as u8).collect()
+}
+
+#[test]
+#[allow(deprecated)]
+fn vec_backed_views() {
+    let mut gray = ImageBuffer::<Luma<u8>, _>::from_raw(3, 2, samples(6)).unwrap();
+    let view: NdGray = NdImage(&gray).into();
+    assert_eq!(view.dim(), (2, 3));
+    assert_eq!(view[[1, 2]], 5);
+    let mut view: NdGrayMut = NdImage(&mut gray).into();
+    view[[1, 0]] = 100;
+    assert_eq!(gray.get_pixel(0, 1), &Luma([100]));
+
+    let mut color = ImageBuffer::<Rgb<u8>, _>::from_raw(3, 2, samples(18)).unwrap();
+    let view: NdColor = NdImage(&color).into();
+    assert_eq!(view.dim(), (2, 3, 3));
+    assert_eq!(view[[1, 2, 1]], 16);
+    let mut view: NdColorMut = NdImage(&mut color).into();
+    view[[0, 1, 2]] = 200;
+    assert_eq!(color.get_pixel(1, 0), &Rgb([3, 4, 200]));
+}
+
+#[test]
+#[allow(deprecated)]
+fn boxed_slice_backed_views() {
+    let data = samples(18).into_boxed_slice();
+    let mut color = ImageBuffer::<Rgb<u8>, Box<[u8]>>::from_raw(3, 2, data).unwrap();
+    let view: NdColor = NdImage(&color).into();
+    assert_eq!(view.dim(), (2, 3, 3));
+    assert_eq!(view[[1, 0, 0]], 9);
+    let mut view: NdColorMut = NdImage(&mut color).into();
+    view[[1, 0, 0]] = 42;
+    assert_eq!(color.get_pixel(0, 1), &Rgb([42, 10, 11]));
+}
+
+#[test]
+#[allow(deprecated)]
+fn borrowed_slice_backed_views() {
+    let data = samples(6);
+    let gray = ImageBuffer::<Luma<u8>, &[u8]>::from_raw(2, 3, &data).unwrap();
+    let view: NdGray = NdImage(&gray).into();
+    assert_eq!(view.dim(), (3, 2));
+    assert_eq!(view[[2, 1]], 5);
+
+    let data = samples(18);
+    let color = ImageBuffer::<Rgb<u8>, &[u8]>::from_raw(3, 2, &data).unwrap();
+    let view: NdColor = NdImage(&color).into();
+    assert_eq!(view.as_slice(), Some(&data[..]));
+
+    let mut data = samples(18);
+    let mut color = ImageBuffer::<Rgb<u8>, &mut [u8]>::from_raw(3, 2, &mut data).unwrap();
+    let mut view: NdColorMut = NdImage(&mut color).into();
+    view[[1, 2, 2]] = 0;
+    assert_eq!(data[17], 0);
+}
+
+#[test]
+#[allow(deprecated)]
+fn views_exclude_extra_container_samples() {
+    let mut gray = ImageBuffer::<Luma<u8>, _>::from_raw(2, 2, samples(10)).unwrap();
+    let view: NdGray = NdImage(&gray).into();
+    assert_eq!(view.len(), 4);
+    let view: NdGrayMut = NdImage(&mut gray).into();
+    assert_eq!(view.len(), 4);
+
+    let mut color = ImageBuffer::<Rgb<u8>, _>::from_raw(2, 1, samples(8)).unwrap();
+    let view: NdColor = NdImage(&color).into();
+    assert_eq!(view.as_slice(), Some(&[0, 1, 2, 3, 4, 5][..]));
+    let view: NdColorMut = NdImage(&mut color).into();
+    assert_eq!(view.len(), 6);
+}
+
+#[test]
+#[allow(deprecated)]
+fn flat_samples_views_use_layout_strides() {
+    // Two rows of two RGB pixels, each row padded by two samples.
+    let mut data = samples(16);
+    let layout = SampleLayout {
+        channels: 3,
+        channel_stride: 1,
+        width: 2,
+        width_stride: 3,
+        height: 2,
+        height_stride: 8,
+    };
+    let flat = FlatSamples {
+        samples: &data[..],
+        layout,
+        color_hint: None,
+    };
+    let view = NdColor::try_from(NdImage(flat)).unwrap();
+    assert_eq!(view.dim(), (2, 2, 3));
+    assert_eq!(view.strides(), &[8, 3, 1]);
+    assert_eq!(view[[1, 1, 2]], 13);
+
+    let flat = FlatSamples {
+        samples: &data[..12],
+        layout,
+        color_hint: None,
+    };
+    assert!(NdColor::try_from(NdImage(flat)).is_err());
+
+    let aliased = SampleLayout {
+        height_stride: 3,
+        ..layout
+    };
+    let flat = FlatSamples {
+        samples: &mut data[..],
+        layout: aliased,
+        color_hint: None,
+    };
+    assert!(NdColorMut::try_from(NdImage(flat)).is_err());
+}
+
+#[test]
+fn conversion_traits_keep_their_layouts() {
+    let mut color = ImageBuffer::<Rgb<u8>, _>::from_raw(3, 2, samples(18)).unwrap();
+    let view = (&color).into_nd_color();
+    assert_eq!(view.dim(), (2, 3, 3));
+    assert_eq!(view[[1, 2, 1]], 16);
+    (&mut color).into_nd_color()[[0, 1, 2]] = 200;
+    assert_eq!(color.get_pixel(1, 0), &Rgb([3, 4, 200]));
+    let array = color.clone().into_nd_color();
+    assert_eq!(array.dim(), (2, 3, 3));
+    // The `nshare` traits put the channels first instead.
+    let planes = color.clone().into_ndarray3();
+    assert_eq!(planes.dim(), (3, 2, 3));
+    assert_eq!(planes[[2, 0, 1]], 200);
+    let image: ImageBuffer<Rgb<u8>, Vec<u8>> = array.into_image_buffer().unwrap();
+    assert_eq!(image, color);
+
+    let gray = ImageBuffer::<Luma<u8>, _>::from_raw(3, 2, samples(6)).unwrap();
+    assert_eq!((&gray).into_nd_gray().dim(), (2, 3));
+    let array = gray.clone().into_nd_gray();
+    assert_eq!(array[[1, 2]], 5);
+    let image: ImageBuffer<Luma<u8>, Vec<u8>> = array.into_image_buffer().unwrap();
+    assert_eq!(image, gray);
+}
+
+/// A container that can be shortened after an image was created from it.
+struct Shrinking {
+    data: Vec<u8>,
+    len: Cell<usize>,
+}
+
+impl Deref for Shrinking {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.data[..self.len.get()]
+    }
+}
+
+#[test]
+fn checked_views_reject_short_buffers() {
+    let container = Shrinking {
+        data: samples(6),
+        len: Cell::new(6),
+    };
+    let image = ImageBuffer::<Rgb<u8>, _>::from_raw(2, 1, container).unwrap();
+    assert_eq!(try_view_color(&image).unwrap().dim(), (1, 2, 3));
+    image.as_raw().len.set(4);
+    assert_eq!(
+        try_view_color(&image).unwrap_err(),
+        ConversionError::BufferTooShort {
+            shape: vec![1, 2, 3],
+            len: 4
+        }
+    );
+
+    let container = Shrinking {
+        data: samples(4),
+        len: Cell::new(4),
+    };
+    let image = ImageBuffer::<Luma<u8>, _>::from_raw(2, 2, container).unwrap();
+    image.as_raw().len.set(3);
+    assert!(try_view_gray(&image).is_err());
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use common::gradient;
+use ndarray::{Array2, Array3};
+use ndarray_image::{
+    remap, warp_affine, warp_perspective, BorderMode, ConversionError, Interpolation,
+};
+
+const IDENTITY: [[f32; 3]; 2] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+
+#[test]
+fn identity_affine_copies() {
+    let image = gradient((6, 9, 3));
+    for &interpolation in &[Interpolation::Nearest, Interpolation::Bilinear] {
+        let warped = warp_affine(
+            image.view(),
+            &IDENTITY,
+            (6, 9),
+            interpolation,
+            BorderMode::Constant(0),
+        );
+        assert_eq!(warped, image, "{:?}", interpolation);
+    }
+}
+
+#[test]
+fn affine_translation_shifts_and_fills_border() {
+    let image = gradient((5, 7, 2));
+    let matrix = [[1.0, 0.0, 2.0], [0.0, 1.0, -1.0]];
+    let warped = warp_affine(
+        image.view(),
+        &matrix,
+        (5, 7),
+        Interpolation::Bilinear,
+        BorderMode::Constant(255),
+    );
+    for ((y, x, c), &v) in warped.indexed_iter() {
+        let expected = if x >= 2 && y + 1 < 5 {
+            image[[y + 1, x - 2, c]]
+        } else {
+            255
+        };
+        assert_eq!(v, expected, "{:?}", (y, x, c));
+    }
+}
+
+#[test]
+fn affine_bilinear_interpolates_half_pixels() {
+    let image = Array3::from_shape_fn((1, 4, 1), |(_, x, _)| (x * 10) as u8);
+    let matrix = [[1.0, 0.0, -0.5], [0.0, 1.0, 0.0]];
+    let warped = warp_affine(
+        image.view(),
+        &matrix,
+        (1, 3),
+        Interpolation::Bilinear,
+        BorderMode::Replicate,
+    );
+    assert_eq!(warped.into_raw_vec(), vec![5, 15, 25]);
+}
+
+#[test]
+#[should_panic(expected = "cannot be inverted")]
+fn singular_affine_panics() {
+    let image = gradient((2, 2, 1));
+    let matrix = [[1.0, 2.0, 0.0], [2.0, 4.0, 0.0]];
+    warp_affine(
+        image.view(),
+        &matrix,
+        (2, 2),
+        Interpolation::Nearest,
+        BorderMode::Constant(0),
+    );
+}
+
+#[test]
+fn scaled_affine_homography_matches_affine_warp() {
+    let image = gradient((8, 8, 3));
+    let affine = [[0.8, 0.3, 1.5], [-0.2, 1.1, 0.5]];
+    // Scaling a homography does not change the transformation.
+    let homography = [[1.6, 0.6, 3.0], [-0.4, 2.2, 1.0], [0.0, 0.0, 2.0]];
+    for &interpolation in &[Interpolation::Nearest, Interpolation::Bilinear] {
+        let border = BorderMode::Constant(9);
+        let expected = warp_affine(image.view(), &affine, (7, 9), interpolation, border);
+        let warped = warp_perspective(image.view(), &homography, (7, 9), interpolation, border);
+        assert_eq!(warped, expected, "{:?}", interpolation);
+    }
+}
+
+#[test]
+fn perspective_samples_the_inverse_homography() {
+    // Bilinear sampling reproduces linear functions, so each pixel holds where it was sampled.
+    let image = Array3::from_shape_fn((20, 20, 2), |(y, x, c)| [x, y][c] as f32);
+    let h = [[1.1, 0.1, 2.0], [0.05, 0.9, 1.0], [0.01, 0.02, 1.0]];
+    let border = BorderMode::Constant(f32::NAN);
+    let warped = warp_perspective(image.view(), &h, (15, 15), Interpolation::Bilinear, border);
+    let mut inside = 0;
+    for y in 0..15 {
+        for x in 0..15 {
+            let (sx, sy) = (warped[[y, x, 0]], warped[[y, x, 1]]);
+            // Pixels sampled next to the border mix in its NaNs.
+            if sx.is_nan() || sy.is_nan() {
+                continue;
+            }
+            inside += 1;
+            let w = h[2][0] * sx + h[2][1] * sy + h[2][2];
+            let px = (h[0][0] * sx + h[0][1] * sy + h[0][2]) / w;
+            let py = (h[1][0] * sx + h[1][1] * sy + h[1][2]) / w;
+            assert!((px - x as f32).abs() < 1e-3, "{:?}", (x, y, px));
+            assert!((py - y as f32).abs() < 1e-3, "{:?}", (x, y, py));
+        }
+    }
+    assert!(inside > 100);
+}
+
+#[test]
+fn identity_remap_copies() {
+    let image = gradient((5, 6, 3));
+    let map_x = Array2::from_shape_fn((5, 6), |(_, x)| x as f32);
+    let map_y = Array2::from_shape_fn((5, 6), |(y, _)| y as f32);
+    let border = BorderMode::Constant(0);
+    let remapped = remap(
+        image.view(),
+        map_x.view(),
+        map_y.view(),
+        Interpolation::Bilinear,
+        border,
+    );
+    assert_eq!(remapped.unwrap(), image);
+}
+
+#[test]
+fn remap_flips_and_fills_non_finite_positions() {
+    let image = gradient((3, 4, 1));
+    let mut map_x = Array2::from_shape_fn((3, 4), |(_, x)| (3 - x) as f32);
+    let map_y = Array2::from_shape_fn((3, 4), |(y, _)| y as f32);
+    map_x[[1, 2]] = f32::NAN;
+    map_x[[2, 0]] = f32::INFINITY;
+    let remapped = remap(
+        image.view(),
+        map_x.view(),
+        map_y.view(),
+        Interpolation::Nearest,
+        BorderMode::Replicate,
+    )
+    .unwrap();
+    for ((y, x, c), &v) in remapped.indexed_iter() {
+        let expected = match (y, x) {
+            (1, 2) | (2, 0) => 0,
+            _ => image[[y, 3 - x, c]],
+        };
+        assert_eq!(v, expected, "{:?}", (y, x));
+    }
+}
+
+#[test]
+fn remap_rejects_mismatched_maps() {
+    let image = gradient((3, 3, 1));
+    let map_x = Array2::zeros((3, 3));
+    let map_y = Array2::zeros((3, 2));
+    let result = remap(
+        image.view(),
+        map_x.view(),
+        map_y.view(),
+        Interpolation::Nearest,
+        BorderMode::Constant(0),
+    );
+    assert_eq!(result, Err(ConversionError::BadShape { shape: vec![3, 2] }));
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{assert_close, row};
+use ndarray::Array3;
+use ndarray_image::{rgb_to_ycbcr, rgb_to_ycbcr_f32, ycbcr_to_rgb, ycbcr_to_rgb_f32};
+
+fn rgb_grid() -> Array3<u8> {
+    Array3::from_shape_fn((8, 8, 3), |(y, x, c)| {
+        [y * 36, x * 36, (y + x) * 18][c] as u8
+    })
+}
+
+#[test]
+fn primaries_have_known_ycbcr() {
+    let rgb = row(&[
+        [255, 255, 255],
+        [0, 0, 0],
+        [255, 0, 0],
+        [0, 255, 0],
+        [0, 0, 255],
+    ]);
+    let ycbcr = rgb_to_ycbcr(rgb.view()).unwrap();
+    let expected = row(&[
+        [255, 128, 128],
+        [0, 128, 128],
+        [76, 85, 255],
+        [150, 44, 21],
+        [29, 255, 107],
+    ]);
+    assert_eq!(ycbcr, expected);
+
+    let unit = rgb.mapv(|v| f32::from(v) / 255.0);
+    let ycbcr = rgb_to_ycbcr_f32(unit.view()).unwrap();
+    let expected = row(&[
+        [1.0, 0.5, 0.5],
+        [0.0, 0.5, 0.5],
+        [0.299, 0.331_264, 1.0],
+        [0.587, 0.168_736, 0.081_312],
+        [0.114, 1.0, 0.418_688],
+    ]);
+    assert_close(&ycbcr, &expected, 1e-6);
+    assert_close(&ycbcr_to_rgb_f32(ycbcr.view()).unwrap(), &unit, 1e-5);
+}
+
+#[test]
+fn ycbcr_round_trips() {
+    let rgb = rgb_grid();
+    let round_tripped = ycbcr_to_rgb(rgb_to_ycbcr(rgb.view()).unwrap().view()).unwrap();
+    for (&a, &b) in round_tripped.iter().zip(&rgb) {
+        assert!((i32::from(a) - i32::from(b)).abs() <= 1, "{} {}", a, b);
+    }
+    let unit = rgb.mapv(|v| f32::from(v) / 255.0);
+    let ycbcr = rgb_to_ycbcr_f32(unit.view()).unwrap();
+    assert_close(&ycbcr_to_rgb_f32(ycbcr.view()).unwrap(), &unit, 1e-5);
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn ycbcr_arrays_are_saved_as_rgb() {
+    use ndarray_image::{open_image, save_image, Colors};
+
+    let path = common::temp_path("ycbcr.png");
+    let rgb = rgb_grid();
+    let ycbcr = rgb_to_ycbcr(rgb.view()).unwrap();
+    save_image(&path, ycbcr.view(), Colors::YCbCr).unwrap();
+    let opened = open_image(&path, Colors::Rgb).unwrap();
+    assert_eq!(opened, ycbcr_to_rgb(ycbcr.view()).unwrap());
+    let expected = rgb_to_ycbcr(opened.view()).unwrap();
+    assert_eq!(open_image(&path, Colors::YCbCr).unwrap(), expected);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use common::row;
+use ndarray::Array3;
+use ndarray_image::{rgb_to_yuv420, yuv420_to_rgb, ConversionError, Yuv420Format, YuvMatrix};
+
+const FORMATS: [Yuv420Format; 2] = [Yuv420Format::Nv12, Yuv420Format::I420];
+
+const MATRICES: [YuvMatrix; 2] = [YuvMatrix::Bt601, YuvMatrix::Bt709];
+
+/// Sizes with even and odd widths and heights, including ones with a single chroma sample.
+const SIZES: [(usize, usize); 6] = [(1, 1), (2, 2), (3, 5), (5, 3), (4, 7), (8, 6)];
+
+#[test]
+fn primaries_have_known_yuv() {
+    for &(matrix, red) in &[
+        (YuvMatrix::Bt601, [81, 90, 240]),
+        (YuvMatrix::Bt709, [63, 102, 240]),
+    ] {
+        for &(rgb, yuv) in &[
+            ([0, 0, 0], [16, 128, 128]),
+            ([255, 255, 255], [235, 128, 128]),
+            ([128, 128, 128], [126, 128, 128]),
+            ([255, 0, 0], red),
+        ] {
+            let image = row(&[rgb]);
+            for &format in &FORMATS {
+                // A single pixel has one sample in each plane, in the same order for both formats.
+                let frame = rgb_to_yuv420(image.view(), format, matrix).unwrap();
+                assert_eq!(frame, yuv, "{:?} {:?} {:?}", rgb, matrix, format);
+            }
+        }
+    }
+}
+
+#[test]
+fn frames_have_rounded_up_chroma_planes() {
+    let image = Array3::<u8>::zeros((3, 5, 3));
+    for &format in &FORMATS {
+        let frame = rgb_to_yuv420(image.view(), format, YuvMatrix::Bt601).unwrap();
+        // 15 luma samples, then 3 by 2 samples of each chroma channel.
+        assert_eq!(frame.len(), 15 + 2 * 3 * 2);
+        let rgb = yuv420_to_rgb(&frame, 5, 3, format, YuvMatrix::Bt601).unwrap();
+        assert_eq!(rgb, image);
+    }
+}
+
+#[test]
+fn formats_store_the_same_samples() {
+    let image = Array3::from_shape_fn((5, 7, 3), |(y, x, c)| (y * 40 + x * 20 + c * 70) as u8);
+    let nv12 = rgb_to_yuv420(image.view(), Yuv420Format::Nv12, YuvMatrix::Bt709).unwrap();
+    let i420 = rgb_to_yuv420(image.view(), Yuv420Format::I420, YuvMatrix::Bt709).unwrap();
+    let luma = 5 * 7;
+    let chroma = 4 * 3;
+    assert_eq!(nv12[..luma], i420[..luma]);
+    for sample in 0..chroma {
+        assert_eq!(nv12[luma + 2 * sample], i420[luma + sample]);
+        assert_eq!(nv12[luma + 2 * sample + 1], i420[luma + chroma + sample]);
+    }
+    let from_nv12 = yuv420_to_rgb(&nv12, 7, 5, Yuv420Format::Nv12, YuvMatrix::Bt709).unwrap();
+    let from_i420 = yuv420_to_rgb(&i420, 7, 5, Yuv420Format::I420, YuvMatrix::Bt709).unwrap();
+    assert_eq!(from_nv12, from_i420);
+}
+
+#[test]
+fn flat_colors_round_trip() {
+    let colors = [
+        [255, 0, 0],
+        [0, 255, 0],
+        [0, 0, 255],
+        [200, 120, 40],
+        [17, 90, 230],
+    ];
+    for &(height, width) in &SIZES {
+        for &format in &FORMATS {
+            for &matrix in &MATRICES {
+                for color in &colors {
+                    let image = Array3::from_shape_fn((height, width, 3), |(_, _, c)| color[c]);
+                    let frame = rgb_to_yuv420(image.view(), format, matrix).unwrap();
+                    let rgb = yuv420_to_rgb(&frame, width, height, format, matrix).unwrap();
+                    // Limited range quantizes each value to a little more than one step.
+                    for (&a, &b) in rgb.iter().zip(&image) {
+                        assert!(
+                            (i16::from(a) - i16::from(b)).abs() <= 2,
+                            "{:?} at {}x{} {:?} {:?}: {} != {}",
+                            color,
+                            width,
+                            height,
+                            format,
+                            matrix,
+                            a,
+                            b
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn short_buffers_and_wrong_channel_counts_are_rejected() {
+    for &format in &FORMATS {
+        assert_eq!(
+            yuv420_to_rgb(&[16; 26], 5, 3, format, YuvMatrix::Bt601),
+            Err(ConversionError::BufferTooShort {
+                shape: vec![3, 5, 3],
+                len: 26
+            })
+        );
+        let image = Array3::<u8>::zeros((2, 2, 4));
+        assert_eq!(
+            rgb_to_yuv420(image.view(), format, YuvMatrix::Bt601),
+            Err(ConversionError::WrongChannelCount {
+                expected: 3,
+                got: 4
+            })
+        );
+    }
+}